@@ -3,7 +3,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         // eprintln!("\x1b[32m[INFO]\x1b[0m building proto files...");
         // println!("cargo:warning=Building test proto file...");
         tonic_prost_build::configure()
-            //.file_descriptor_set_path("helloworld_descriptor.bin")
+            // read by the gRPC-JSON transcoding tests
+            .file_descriptor_set_path(
+                std::path::Path::new(&std::env::var("OUT_DIR")?).join("helloworld_descriptor.bin"),
+            )
             .build_client(true)
             .build_server(true)
             .compile_protos(
@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use prometheus::IntGauge;
+use tokio::time::Instant;
+
+use crate::{
+    admission::resizable_semaphore::{ResizablePermit, ResizableSemaphore},
+    config::limits_config::LimitsConfig,
+    core::proxy_error::ProxyError,
+    telemetry::metrics::Metrics,
+};
+
+/// Caps the calls handled at once, queueing those over the limit for a
+/// while before shedding them
+pub struct InFlightLimiter {
+    semaphore: ResizableSemaphore,
+    queue_timeout_ms: AtomicU64,
+    metrics: Metrics,
+}
+
+/// A call counted as in flight until dropped
+pub struct InFlightPermit {
+    _permit: ResizablePermit,
+    in_flight: IntGauge,
+    queued: bool,
+}
+
+impl InFlightPermit {
+    /// Whether the call waited for another one to complete
+    pub fn was_queued(&self) -> bool {
+        self.queued
+    }
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        self.in_flight.dec();
+    }
+}
+
+impl InFlightLimiter {
+    pub fn new(config: &LimitsConfig, metrics: Metrics) -> Self {
+        Self {
+            semaphore: ResizableSemaphore::new(config.max_in_flight),
+            queue_timeout_ms: AtomicU64::new(config.queue_timeout_ms),
+            metrics,
+        }
+    }
+
+    /// Applies new limits, the calls in flight over them completing
+    pub fn reconfigure(&self, config: &LimitsConfig) {
+        self.semaphore.resize(config.max_in_flight);
+        self.queue_timeout_ms
+            .store(config.queue_timeout_ms, Ordering::Relaxed);
+    }
+
+    /// Waits up to the queue timeout for the call to be admitted, failing
+    /// with a timeout when the call reaches its `deadline` first
+    pub async fn acquire(&self, deadline: Option<Instant>) -> Result<InFlightPermit, ProxyError> {
+        let mut queued = false;
+        let permit = match self.semaphore.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                queued = true;
+                self.wait(deadline).await.inspect_err(|_| {
+                    self.metrics.downstream_shed.inc();
+                })?
+            }
+        };
+        let in_flight = self.metrics.downstream_in_flight.clone();
+        in_flight.inc();
+        Ok(InFlightPermit {
+            _permit: permit,
+            in_flight,
+            queued,
+        })
+    }
+
+    async fn wait(&self, deadline: Option<Instant>) -> Result<ResizablePermit, ProxyError> {
+        let queue_timeout = Duration::from_millis(self.queue_timeout_ms.load(Ordering::Relaxed));
+        if queue_timeout.is_zero() {
+            return Err(ProxyError::Overloaded);
+        }
+        let queue_deadline = Instant::now() + queue_timeout;
+        let expires = deadline.filter(|deadline| *deadline < queue_deadline);
+        let queued = &self.metrics.downstream_queued;
+        queued.inc();
+        let permit =
+            tokio::time::timeout_at(expires.unwrap_or(queue_deadline), self.semaphore.acquire())
+                .await;
+        queued.dec();
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            Err(_) if expires.is_some() => Err(ProxyError::Timeout),
+            _ => Err(ProxyError::Overloaded),
+        }
+    }
+}
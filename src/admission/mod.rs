@@ -0,0 +1,2 @@
+pub mod in_flight_limiter;
+pub mod resizable_semaphore;
@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{
+    AcquireError, Notify, OwnedSemaphorePermit, Semaphore, TryAcquireError, futures::Notified,
+};
+
+/// A semaphore whose number of permits can change while some are held,
+/// `None` standing for no limit.
+///
+/// Permits held over a lowered limit are forgotten once released instead of
+/// being handed to the tasks waiting for one.
+pub struct ResizableSemaphore {
+    semaphore: Arc<Semaphore>,
+    size: Arc<Mutex<Size>>,
+    resized: Notify,
+}
+
+struct Size {
+    permits: usize,
+    /// Permits in use to forget once released
+    excess: usize,
+}
+
+/// A permit released to its semaphore on drop
+pub struct ResizablePermit {
+    permit: Option<OwnedSemaphorePermit>,
+    size: Arc<Mutex<Size>>,
+}
+
+impl Drop for ResizablePermit {
+    fn drop(&mut self) {
+        let mut size = self.size.lock().unwrap();
+        if size.excess > 0 {
+            size.excess -= 1;
+            if let Some(permit) = self.permit.take() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+impl ResizableSemaphore {
+    pub fn new(max: Option<usize>) -> Self {
+        let permits = max.unwrap_or(Semaphore::MAX_PERMITS);
+        Self {
+            semaphore: Arc::new(Semaphore::new(permits)),
+            size: Arc::new(Mutex::new(Size { permits, excess: 0 })),
+            resized: Notify::new(),
+        }
+    }
+
+    /// Applies a new limit, the permits held over it being forgotten as they are released
+    pub fn resize(&self, max: Option<usize>) {
+        let permits = max.unwrap_or(Semaphore::MAX_PERMITS);
+        let mut size = self.size.lock().unwrap();
+        if permits > size.permits {
+            let added = permits - size.permits;
+            let kept = added.min(size.excess);
+            size.excess -= kept;
+            self.semaphore.add_permits(added - kept);
+        } else {
+            let removed = size.permits - permits;
+            let forgotten = self.semaphore.forget_permits(removed);
+            size.excess += removed - forgotten;
+        }
+        size.permits = permits;
+        self.resized.notify_waiters();
+    }
+
+    /// Resolves once the limit is changed
+    pub fn resized(&self) -> Notified<'_> {
+        self.resized.notified()
+    }
+
+    pub async fn acquire(&self) -> Result<ResizablePermit, AcquireError> {
+        let permit = self.semaphore.clone().acquire_owned().await?;
+        Ok(self.wrap(permit))
+    }
+
+    pub fn try_acquire(&self) -> Result<ResizablePermit, TryAcquireError> {
+        let permit = self.semaphore.clone().try_acquire_owned()?;
+        Ok(self.wrap(permit))
+    }
+
+    fn wrap(&self, permit: OwnedSemaphorePermit) -> ResizablePermit {
+        ResizablePermit {
+            permit: Some(permit),
+            size: self.size.clone(),
+        }
+    }
+}
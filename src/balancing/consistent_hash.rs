@@ -0,0 +1,72 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use http::{HeaderMap, HeaderName, uri::Authority};
+
+use crate::{
+    balancing::{load_balancer::LoadBalancer, round_robin::RoundRobin},
+    upstream::endpoint::Endpoint,
+};
+
+/// Points per endpoint on the ring, more points spread keys more evenly
+const VIRTUAL_NODES: usize = 100;
+
+/// Hash ring keyed by a header value, so that the calls of a session stick to
+/// one endpoint and only the keys of a removed endpoint move elsewhere
+pub struct ConsistentHash {
+    header: HeaderName,
+    // sorted by hash, keyed by authority so that the ring does not change
+    // when unhealthy endpoints are left out of the candidates
+    ring: Vec<(u64, Authority)>,
+    fallback: RoundRobin,
+}
+
+impl ConsistentHash {
+    pub fn new(header: HeaderName, endpoints: &[Endpoint]) -> Self {
+        let mut ring: Vec<_> = endpoints
+            .iter()
+            .flat_map(|endpoint| {
+                (0..VIRTUAL_NODES).map(move |node| {
+                    (
+                        hash(&(endpoint.authority.as_str(), node)),
+                        endpoint.authority.clone(),
+                    )
+                })
+            })
+            .collect();
+        ring.sort_unstable_by_key(|(point, _)| *point);
+        Self {
+            header,
+            ring,
+            fallback: RoundRobin::new(),
+        }
+    }
+}
+
+impl LoadBalancer for ConsistentHash {
+    fn policy(&self) -> &'static str {
+        "consistent_hash"
+    }
+
+    fn pick(&self, endpoints: &[Endpoint], headers: &HeaderMap) -> usize {
+        let Some(value) = headers.get(&self.header) else {
+            return self.fallback.pick(endpoints, headers);
+        };
+        let key = hash(&value.as_bytes());
+        // first candidate clockwise of the key, wrapping around
+        let position = self.ring.partition_point(|(point, _)| *point < key);
+        (0..self.ring.len())
+            .map(|offset| &self.ring[(position + offset) % self.ring.len()].1)
+            .find_map(|authority| {
+                endpoints
+                    .iter()
+                    .position(|endpoint| endpoint.authority == *authority)
+            })
+            .unwrap_or_else(|| self.fallback.pick(endpoints, headers))
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http::HeaderMap;
+
+use crate::{balancing::load_balancer::LoadBalancer, upstream::endpoint::Endpoint};
+
+/// The endpoint with the fewest calls in flight
+pub struct LeastRequest {
+    // rotates the scan so that ties do not all land on the first endpoint
+    start: AtomicUsize,
+}
+
+impl LeastRequest {
+    pub fn new() -> Self {
+        Self {
+            start: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Default for LeastRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadBalancer for LeastRequest {
+    fn policy(&self) -> &'static str {
+        "least_request"
+    }
+
+    fn pick(&self, endpoints: &[Endpoint], _headers: &HeaderMap) -> usize {
+        let start = self.start.fetch_add(1, Ordering::Relaxed);
+        (0..endpoints.len())
+            .map(|i| (start + i) % endpoints.len())
+            .min_by_key(|&index| endpoints[index].in_flight())
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,36 @@
+use http::{HeaderMap, HeaderName};
+use tower::BoxError;
+
+use crate::{
+    balancing::{
+        consistent_hash::ConsistentHash, least_request::LeastRequest,
+        power_of_two_choices::PowerOfTwoChoices, round_robin::RoundRobin,
+    },
+    config::lb_policy_config::LbPolicyConfig,
+    upstream::endpoint::Endpoint,
+};
+
+/// Picks the endpoint of every call sent to a cluster
+pub trait LoadBalancer: Send + Sync {
+    /// Name of the policy, as reported in metrics
+    fn policy(&self) -> &'static str;
+
+    /// Index of the chosen endpoint, `endpoints` is never empty
+    /// but may be a subset of the cluster when some are unhealthy
+    fn pick(&self, endpoints: &[Endpoint], headers: &HeaderMap) -> usize;
+}
+
+pub fn from_config(
+    config: &LbPolicyConfig,
+    endpoints: &[Endpoint],
+) -> Result<Box<dyn LoadBalancer>, BoxError> {
+    Ok(match config {
+        LbPolicyConfig::RoundRobin => Box::new(RoundRobin::new()),
+        LbPolicyConfig::LeastRequest => Box::new(LeastRequest::new()),
+        LbPolicyConfig::PowerOfTwoChoices => Box::new(PowerOfTwoChoices),
+        LbPolicyConfig::ConsistentHash { header } => Box::new(ConsistentHash::new(
+            HeaderName::from_bytes(header.as_bytes())?,
+            endpoints,
+        )),
+    })
+}
@@ -0,0 +1,5 @@
+pub mod consistent_hash;
+pub mod least_request;
+pub mod load_balancer;
+pub mod power_of_two_choices;
+pub mod round_robin;
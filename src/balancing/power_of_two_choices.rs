@@ -0,0 +1,29 @@
+use http::HeaderMap;
+
+use crate::{balancing::load_balancer::LoadBalancer, upstream::endpoint::Endpoint};
+
+/// The endpoint with fewer calls in flight among two distinct endpoints
+/// drawn at random, cheaper than a full scan on large clusters
+pub struct PowerOfTwoChoices;
+
+impl LoadBalancer for PowerOfTwoChoices {
+    fn policy(&self) -> &'static str {
+        "power_of_two_choices"
+    }
+
+    fn pick(&self, endpoints: &[Endpoint], _headers: &HeaderMap) -> usize {
+        if endpoints.len() < 2 {
+            return 0;
+        }
+        let first = fastrand::usize(..endpoints.len());
+        let mut second = fastrand::usize(..endpoints.len() - 1);
+        if second >= first {
+            second += 1;
+        }
+        if endpoints[second].in_flight() < endpoints[first].in_flight() {
+            second
+        } else {
+            first
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http::HeaderMap;
+
+use crate::{balancing::load_balancer::LoadBalancer, upstream::endpoint::Endpoint};
+
+/// Every endpoint in turn
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl RoundRobin {
+    pub fn new() -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Default for RoundRobin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadBalancer for RoundRobin {
+    fn policy(&self) -> &'static str {
+        "round_robin"
+    }
+
+    fn pick(&self, endpoints: &[Endpoint], _headers: &HeaderMap) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % endpoints.len()
+    }
+}
@@ -1,16 +1,329 @@
-use clap::Parser;
+use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::config::{
+    cluster_config::ClusterConfig,
+    config_file::{ConfigFile, ConfigFileError},
+    health_check_config::HealthCheckConfig,
+    lb_policy_config::LbPolicyConfig,
+    proxy_config::{DEFAULT_CLUSTER, ProxyConfig},
+    route_config::RouteConfig,
+    tls_config::TlsConfig,
+    upstream_tls_config::UpstreamTlsConfig,
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum LbPolicyArg {
+    RoundRobin,
+    LeastRequest,
+    PowerOfTwoChoices,
+    ConsistentHash,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Parse and validate the configuration file, then exit
+    CheckConfig,
+}
+
+/// Flags and `GRIFFIN_*` environment variables override the configuration
+/// file, the forward server options applying to the `default` cluster.
+#[derive(Parser, Clone, Debug)]
 #[command(name = "server", about = "Run the server with options")]
 pub struct Args {
-    #[arg(long, default_value = "127.0.0.1", help = "Proxy server host")]
-    pub proxy_host: String,
-    #[arg(long, default_value_t = 8080, help = "Proxy server port")]
-    pub proxy_port: u16,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "GRIFFIN_CONFIG",
+        help = "TOML configuration file"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_PROXY_HOST",
+        help = "Proxy server host [default: 127.0.0.1]"
+    )]
+    pub proxy_host: Option<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_PROXY_PORT",
+        help = "Proxy server port [default: 8080]"
+    )]
+    pub proxy_port: Option<u16>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_HOST",
+        help = "Forward server host [default: 127.0.0.1]"
+    )]
+    pub forward_host: Option<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_PORT",
+        help = "Forward server port [default: 3000]"
+    )]
+    pub forward_port: Option<u16>,
+
+    #[arg(
+        long = "forward-endpoint",
+        env = "GRIFFIN_FORWARD_ENDPOINTS",
+        value_delimiter = ',',
+        help = "Additional forward server (host:port) sharing the load"
+    )]
+    pub forward_endpoints: Vec<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        env = "GRIFFIN_LB_POLICY",
+        help = "How calls are spread over the forward servers [default: round-robin]"
+    )]
+    pub lb_policy: Option<LbPolicyArg>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_LB_HASH_HEADER",
+        required_if_eq("lb_policy", "consistent-hash"),
+        help = "Metadata whose value selects the forward server with consistent-hash"
+    )]
+    pub lb_hash_header: Option<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_HEALTH_CHECK_INTERVAL_MS",
+        help = "Check the forward servers with grpc.health.v1 every given milliseconds"
+    )]
+    pub health_check_interval_ms: Option<u64>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_HEALTH_CHECK_SERVICE",
+        requires = "health_check_interval_ms",
+        help = "Service name sent in health checks, the whole server by default"
+    )]
+    pub health_check_service: Option<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_TLS",
+        help = "Connect to the forward server with TLS"
+    )]
+    pub forward_tls: bool,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_CA",
+        help = "PEM CA bundle trusted for the forward server, system roots by default"
+    )]
+    pub forward_ca: Option<PathBuf>,
 
-    #[arg(long, default_value = "127.0.0.1", help = "Forward server host")]
-    pub forward_host: String,
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_CERT",
+        requires = "forward_key",
+        help = "PEM client certificate presented to the forward server (mTLS)"
+    )]
+    pub forward_cert: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_KEY",
+        requires = "forward_cert",
+        help = "PEM private key of the client certificate"
+    )]
+    pub forward_key: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_SERVER_NAME",
+        help = "Server name used for SNI and certificate verification of the forward server"
+    )]
+    pub forward_server_name: Option<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_FORWARD_INSECURE_SKIP_VERIFY",
+        help = "Accept any certificate from the forward server (development only)"
+    )]
+    pub forward_insecure_skip_verify: bool,
+
+    #[arg(
+        long = "cors-allowed-origin",
+        env = "GRIFFIN_CORS_ALLOWED_ORIGINS",
+        value_delimiter = ',',
+        help = "Origin allowed to call the proxy from a browser (exact, *, https://*.example.com or regex:<pattern>)"
+    )]
+    pub cors_allowed_origins: Vec<String>,
+
+    #[arg(
+        long = "cors-allowed-header",
+        env = "GRIFFIN_CORS_ALLOWED_HEADERS",
+        value_delimiter = ',',
+        help = "Custom request metadata allowed in CORS requests"
+    )]
+    pub cors_allowed_headers: Vec<String>,
+
+    #[arg(
+        long = "cors-exposed-header",
+        env = "GRIFFIN_CORS_EXPOSED_HEADERS",
+        value_delimiter = ',',
+        help = "Response metadata exposed to browsers"
+    )]
+    pub cors_exposed_headers: Vec<String>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_TLS_CERT",
+        requires = "tls_key",
+        help = "PEM certificate chain served by the proxy, reloaded when it changes"
+    )]
+    pub tls_cert: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_TLS_KEY",
+        requires = "tls_cert",
+        help = "PEM private key of the proxy certificate"
+    )]
+    pub tls_key: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "GRIFFIN_DRAIN_TIMEOUT_MS",
+        help = "Milliseconds given to in-flight calls to finish on SIGTERM or SIGINT [default: 30000]"
+    )]
+    pub drain_timeout_ms: Option<u64>,
+}
+
+impl Args {
+    /// The configuration file if any, or the defaults, overridden by the flags
+    pub fn into_config(self) -> Result<ProxyConfig, ConfigFileError> {
+        let mut config = match &self.config {
+            Some(path) => ConfigFile::read(path)?.parse()?,
+            None => ProxyConfig::default(),
+        };
+        self.apply(&mut config);
+        Ok(config)
+    }
+
+    fn apply(self, config: &mut ProxyConfig) {
+        if self.proxy_host.is_some() || self.proxy_port.is_some() {
+            config.listen =
+                override_authority(&config.listen, self.proxy_host.clone(), self.proxy_port);
+        }
+        if self.forward_host.is_some()
+            || self.forward_port.is_some()
+            || !self.forward_endpoints.is_empty()
+            || self.lb_policy.is_some()
+            || self.health_check_interval_ms.is_some()
+            || self.forward_tls
+            || self.forward_ca.is_some()
+            || self.forward_cert.is_some()
+            || self.forward_server_name.is_some()
+            || self.forward_insecure_skip_verify
+        {
+            apply_forward(&self, default_cluster(config));
+        }
+        if !self.cors_allowed_origins.is_empty() {
+            config.cors.allowed_origins = self.cors_allowed_origins;
+        }
+        if !self.cors_allowed_headers.is_empty() {
+            config.cors.allowed_headers = self.cors_allowed_headers;
+        }
+        if !self.cors_exposed_headers.is_empty() {
+            config.cors.exposed_headers = self.cors_exposed_headers;
+        }
+        if let (Some(cert_path), Some(key_path)) = (self.tls_cert, self.tls_key) {
+            config.tls = Some(TlsConfig::new(cert_path, key_path));
+        }
+        if let Some(drain_timeout_ms) = self.drain_timeout_ms {
+            config.drain_timeout_ms = drain_timeout_ms;
+        }
+    }
+}
+
+fn apply_forward(args: &Args, cluster: &mut ClusterConfig) {
+    if args.forward_host.is_some() || args.forward_port.is_some() {
+        cluster.endpoints[0] = override_authority(
+            &cluster.endpoints[0],
+            args.forward_host.clone(),
+            args.forward_port,
+        );
+    }
+    cluster
+        .endpoints
+        .extend(args.forward_endpoints.iter().cloned());
+    if let Some(lb_policy) = args.lb_policy {
+        cluster.lb_policy = match lb_policy {
+            LbPolicyArg::RoundRobin => LbPolicyConfig::RoundRobin,
+            LbPolicyArg::LeastRequest => LbPolicyConfig::LeastRequest,
+            LbPolicyArg::PowerOfTwoChoices => LbPolicyConfig::PowerOfTwoChoices,
+            LbPolicyArg::ConsistentHash => LbPolicyConfig::ConsistentHash {
+                header: args.lb_hash_header.clone().unwrap_or_default(),
+            },
+        };
+    }
+    if let Some(interval_ms) = args.health_check_interval_ms {
+        cluster.health_check = Some(HealthCheckConfig {
+            interval_ms,
+            service: args.health_check_service.clone().unwrap_or_default(),
+            ..HealthCheckConfig::default()
+        });
+    }
+    // any upstream TLS option implies TLS
+    if args.forward_tls
+        || args.forward_ca.is_some()
+        || args.forward_cert.is_some()
+        || args.forward_server_name.is_some()
+        || args.forward_insecure_skip_verify
+    {
+        cluster.tls = Some(UpstreamTlsConfig {
+            ca_path: args.forward_ca.clone(),
+            client_cert_path: args.forward_cert.clone(),
+            client_key_path: args.forward_key.clone(),
+            server_name: args.forward_server_name.clone(),
+            insecure_skip_verify: args.forward_insecure_skip_verify,
+        });
+    }
+}
+
+/// The cluster the forward options apply to, added with a catch-all route
+/// when the configuration file does not define it
+fn default_cluster(config: &mut ProxyConfig) -> &mut ClusterConfig {
+    let index = match config
+        .clusters
+        .iter()
+        .position(|c| c.name == DEFAULT_CLUSTER)
+    {
+        Some(index) => index,
+        None => {
+            config
+                .clusters
+                .push(ClusterConfig::new(DEFAULT_CLUSTER, "127.0.0.1:3000"));
+            config.routes.push(RouteConfig::new("*", DEFAULT_CLUSTER));
+            config.clusters.len() - 1
+        }
+    };
+    let cluster = &mut config.clusters[index];
+    if cluster.endpoints.is_empty() {
+        cluster.endpoints.push("127.0.0.1:3000".to_string());
+    }
+    cluster
+}
 
-    #[arg(long, default_value_t = 3000, help = "Forward server port")]
-    pub forward_port: u16,
+/// `authority` with its host or port replaced
+fn override_authority(authority: &str, host: Option<String>, port: Option<u16>) -> String {
+    let (current_host, current_port) = authority.rsplit_once(':').unwrap_or((authority, ""));
+    format!(
+        "{}:{}",
+        host.as_deref().unwrap_or(current_host),
+        port.map_or(current_port.to_string(), |port| port.to_string())
+    )
 }
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+use http::{HeaderName, uri::Authority};
+use tower::BoxError;
+
+use crate::{
+    compression::compression_policy::CompressionPolicy,
+    config::{
+        config_file::{ConfigFile, ConfigFileError},
+        lb_policy_config::LbPolicyConfig,
+        proxy_config::ProxyConfig,
+    },
+    cors::{allowed_origin::AllowedOrigin, cors_policy::CorsPolicy},
+    rate_limit::rate_limiter::RateLimiter,
+    retry::{hedging_policy::HedgingPolicy, retry_policy::RetryPolicy},
+    routing::{header_match::HeaderMatch, path_match::PathMatch},
+    server::server_builder::check_http2,
+    tls::{client_tls::ClientTls, server_tls::ServerTls},
+    transcoding::json_transcoder::JsonTranscoder,
+};
+
+/// Every error of the configuration file at `path`, each with its location
+pub fn check_config(path: &Path) -> Vec<ConfigFileError> {
+    let file = match ConfigFile::read(path) {
+        Ok(file) => file,
+        Err(err) => return vec![err],
+    };
+    let config = match file.parse() {
+        Ok(config) => config,
+        Err(err) => return vec![err],
+    };
+    validate(&config)
+        .into_iter()
+        .map(|(field, err)| file.error(&field, err))
+        .collect()
+}
+
+/// Every error that would prevent the proxy from starting with `config`,
+/// keyed by the field it comes from. Each part is built on its own so that
+/// all errors are reported rather than only the first one.
+pub fn validate(config: &ProxyConfig) -> Vec<(String, BoxError)> {
+    let mut errors: Vec<(String, BoxError)> = Vec::new();
+    let mut check = |field: String, result: Result<(), BoxError>| {
+        if let Err(err) = result {
+            errors.push((field, err));
+        }
+    };
+
+    check(
+        "listen".to_string(),
+        match config.listen.rsplit_once(':') {
+            Some((_, port)) if port.parse::<u16>().is_ok() => Ok(()),
+            _ => Err(format!("Invalid address {:?}, expected host:port", config.listen).into()),
+        },
+    );
+
+    if config.clusters.is_empty() {
+        check(
+            "clusters".to_string(),
+            Err("At least one cluster is required".into()),
+        );
+    }
+    let mut names = HashSet::new();
+    for (i, cluster) in config.clusters.iter().enumerate() {
+        let field = format!("clusters[{}]", i);
+        if !names.insert(cluster.name.as_str()) {
+            check(
+                format!("{}.name", field),
+                Err(format!("Duplicate cluster {:?}", cluster.name).into()),
+            );
+        }
+        if cluster.endpoints.is_empty() {
+            check(
+                format!("{}.endpoints", field),
+                Err("At least one endpoint is required".into()),
+            );
+        }
+        for (j, endpoint) in cluster.endpoints.iter().enumerate() {
+            check(
+                format!("{}.endpoints[{}]", field, j),
+                Authority::from_str(endpoint)
+                    .map(|_| ())
+                    .map_err(|e| format!("Invalid authority {:?}: {}", endpoint, e).into()),
+            );
+        }
+        if let LbPolicyConfig::ConsistentHash { header } = &cluster.lb_policy {
+            check(
+                format!("{}.lb_policy", field),
+                HeaderName::from_bytes(header.as_bytes())
+                    .map(|_| ())
+                    .map_err(|e| format!("Invalid header name {:?}: {}", header, e).into()),
+            );
+        }
+        if let Some(health_check) = &cluster.health_check {
+            let positive = [
+                ("interval_ms", health_check.interval_ms),
+                ("timeout_ms", health_check.timeout_ms),
+                ("healthy_threshold", health_check.healthy_threshold.into()),
+                (
+                    "unhealthy_threshold",
+                    health_check.unhealthy_threshold.into(),
+                ),
+            ];
+            for (name, value) in positive {
+                if value == 0 {
+                    check(
+                        format!("{}.health_check.{}", field, name),
+                        Err("Must be greater than 0".into()),
+                    );
+                }
+            }
+        }
+        if let Some(outlier_detection) = &cluster.outlier_detection {
+            let positive = [
+                (
+                    "consecutive_failures",
+                    outlier_detection.consecutive_failures.into(),
+                ),
+                ("base_ejection_ms", outlier_detection.base_ejection_ms),
+                ("max_ejection_ms", outlier_detection.max_ejection_ms),
+            ];
+            for (name, value) in positive {
+                if value == 0 {
+                    check(
+                        format!("{}.outlier_detection.{}", field, name),
+                        Err("Must be greater than 0".into()),
+                    );
+                }
+            }
+            if outlier_detection.max_ejection_percent > 100 {
+                check(
+                    format!("{}.outlier_detection.max_ejection_percent", field),
+                    Err("Must be at most 100".into()),
+                );
+            }
+        }
+        let limits = [
+            ("max_connections", cluster.circuit_breaker.max_connections),
+            (
+                "max_pending_requests",
+                cluster.circuit_breaker.max_pending_requests,
+            ),
+            (
+                "max_concurrent_streams",
+                cluster.circuit_breaker.max_concurrent_streams,
+            ),
+        ];
+        for (name, value) in limits {
+            if value == Some(0) {
+                check(
+                    format!("{}.circuit_breaker.{}", field, name),
+                    Err("Must be greater than 0".into()),
+                );
+            }
+        }
+        check(
+            format!("{}.compression", field),
+            CompressionPolicy::from_config(&cluster.compression).map(|_| ()),
+        );
+        if let Some(tls) = &cluster.tls {
+            check(format!("{}.tls", field), ClientTls::new(tls).map(|_| ()));
+        }
+    }
+
+    for (i, route) in config.routes.iter().enumerate() {
+        let field = format!("routes[{}]", i);
+        check(
+            format!("{}.path", field),
+            PathMatch::from_str(&route.path).map(|_| ()),
+        );
+        if !names.contains(route.cluster.as_str()) {
+            check(
+                format!("{}.cluster", field),
+                Err(format!("Unknown cluster {:?}", route.cluster).into()),
+            );
+        }
+        for (j, header) in route.headers.iter().enumerate() {
+            check(
+                format!("{}.headers[{}]", field, j),
+                HeaderMatch::from_config(header).map(|_| ()),
+            );
+        }
+        for (name, value) in [
+            ("default_timeout_ms", route.default_timeout_ms),
+            ("max_timeout_ms", route.max_timeout_ms),
+        ] {
+            if value == Some(0) {
+                check(
+                    format!("{}.{}", field, name),
+                    Err("Must be greater than 0".into()),
+                );
+            }
+        }
+        if let (Some(default), Some(max)) = (route.default_timeout_ms, route.max_timeout_ms)
+            && default > max
+        {
+            check(
+                format!("{}.default_timeout_ms", field),
+                Err(format!("Exceeds max_timeout_ms ({})", max).into()),
+            );
+        }
+        if let Some(retry_policy) = &route.retry_policy {
+            check(
+                format!("{}.retry_policy", field),
+                RetryPolicy::from_config(retry_policy).map(|_| ()),
+            );
+        }
+        if let Some(hedging_policy) = &route.hedging_policy {
+            check(
+                format!("{}.hedging_policy", field),
+                if route.retry_policy.is_some() {
+                    Err("A route cannot have both a retry and a hedging policy".into())
+                } else {
+                    HedgingPolicy::from_config(hedging_policy).map(|_| ())
+                },
+            );
+        }
+        for (j, rate_limit) in route.rate_limits.iter().enumerate() {
+            check(
+                format!("{}.rate_limits[{}]", field, j),
+                RateLimiter::from_config(&route.path, rate_limit).map(|_| ()),
+            );
+        }
+    }
+
+    check(
+        "server.http2".to_string(),
+        check_http2(&config.server.http2),
+    );
+    check("pool.http2".to_string(), check_http2(&config.pool.http2));
+    if config.pool.connect_timeout_ms == 0 {
+        check(
+            "pool.connect_timeout_ms".to_string(),
+            Err("Must be greater than 0".into()),
+        );
+    }
+    for (name, value) in [
+        ("max_connection_age_ms", config.server.max_connection_age_ms),
+        (
+            "max_connection_idle_ms",
+            config.server.max_connection_idle_ms,
+        ),
+    ] {
+        if value == Some(0) {
+            check(
+                format!("server.{}", name),
+                Err("Must be greater than 0".into()),
+            );
+        }
+    }
+
+    let limits = [
+        ("max_connections", config.limits.max_connections),
+        ("max_in_flight", config.limits.max_in_flight),
+        (
+            "max_concurrent_streams_per_connection",
+            config
+                .limits
+                .max_concurrent_streams_per_connection
+                .map(|max| max as usize),
+        ),
+    ];
+    for (name, value) in limits {
+        if value == Some(0) {
+            check(
+                format!("limits.{}", name),
+                Err("Must be greater than 0".into()),
+            );
+        }
+    }
+
+    let mut origins_valid = true;
+    for (i, origin) in config.cors.allowed_origins.iter().enumerate() {
+        if let Err(err) = AllowedOrigin::from_str(origin) {
+            origins_valid = false;
+            check(format!("cors.allowed_origins[{}]", i), Err(err));
+        }
+    }
+    // header lists, once the origins are known to be fine
+    if origins_valid {
+        check(
+            "cors".to_string(),
+            CorsPolicy::from_config(&config.cors, None).map(|_| ()),
+        );
+    }
+
+    if let Some(tls) = &config.tls {
+        check("tls".to_string(), ServerTls::new(tls).map(|_| ()));
+        if tls.reload_interval_ms == 0 {
+            check(
+                "tls.reload_interval_ms".to_string(),
+                Err("Must be greater than 0".into()),
+            );
+        }
+    }
+    if let Some(transcoding) = &config.transcoding {
+        check(
+            "transcoding".to_string(),
+            JsonTranscoder::from_config(transcoding).map(|_| ()),
+        );
+    }
+
+    errors
+}
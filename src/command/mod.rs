@@ -1 +1,4 @@
 pub mod args;
+pub mod check_config;
+pub mod reload_signal;
+pub mod shutdown_signal;
@@ -0,0 +1,24 @@
+use tokio::sync::watch;
+
+/// Notifies `reload_tx` on every SIGHUP, until its receivers are dropped
+pub async fn reload_signal(reload_tx: watch::Sender<()>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                eprintln!("Failed to listen for SIGHUP: {:?}", err);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            println!("SIGHUP received, reloading the configuration");
+            if reload_tx.send(()).is_err() {
+                break;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    drop(reload_tx);
+}
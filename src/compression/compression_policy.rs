@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use async_stream::try_stream;
+use bytes::Bytes;
+use http::{Request, Response, header::CONTENT_LENGTH};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use tower::BoxError;
+
+use crate::{
+    compression::{
+        encoding::{Encoding, GRPC_ACCEPT_ENCODING, GRPC_ENCODING},
+        message_transcoder::MessageTranscoder,
+    },
+    config::compression_config::CompressionConfig,
+    core::{
+        proxy_error::ProxyError,
+        status::{Code, Status},
+        stream_response::{DynStream, StreamResponse, UpstreamBody},
+    },
+};
+
+/// Negotiates the encoding of the messages separately with the client and
+/// the servers of a cluster, re-encoding the messages one side cannot decode
+pub struct CompressionPolicy {
+    accept_encodings: Vec<Encoding>,
+    max_message_size: usize,
+}
+
+impl CompressionPolicy {
+    pub fn from_config(config: &CompressionConfig) -> Result<Self, BoxError> {
+        let accept_encodings = config
+            .accept_encodings
+            .iter()
+            .map(|name| Encoding::from_str(name))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            accept_encodings,
+            max_message_size: config.max_message_bytes,
+        })
+    }
+
+    /// Largest message of the calls to the cluster, compressed or not
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Re-encodes the request messages when the servers cannot decode them,
+    /// and offers every encoding of the proxy for the response.
+    /// Returns the encodings the client accepts in the response.
+    pub fn transcode_request(
+        &self,
+        req: Request<UpstreamBody>,
+    ) -> Result<(Request<UpstreamBody>, Vec<Encoding>), ProxyError> {
+        let (mut parts, body) = req.into_parts();
+        let client_accepts = Encoding::accepted(&parts.headers);
+        parts
+            .headers
+            .insert(GRPC_ACCEPT_ENCODING, Encoding::accept_header());
+
+        let from =
+            Encoding::from_headers(&parts.headers).map_err(ProxyError::UnsupportedEncoding)?;
+        if from == Encoding::Identity || self.accept_encodings.contains(&from) {
+            return Ok((Request::from_parts(parts, body), client_accepts));
+        }
+        let to = self
+            .accept_encodings
+            .first()
+            .copied()
+            .unwrap_or(Encoding::Identity);
+        set_encoding(&mut parts.headers, to);
+        parts.headers.remove(CONTENT_LENGTH);
+
+        let mut transcoder = MessageTranscoder::new(from, to, self.max_message_size);
+        let mut body = body;
+        let stream = try_stream! {
+            while let Some(frame) = body.frame().await {
+                match frame?.into_data() {
+                    Ok(data) => {
+                        let messages = transcoder.push(&data)?;
+                        if !messages.is_empty() {
+                            yield Frame::data(messages);
+                        }
+                    }
+                    Err(frame) => yield frame,
+                }
+            }
+            transcoder.finish()?;
+        };
+        let body = StreamBody::new(Box::pin(stream)).boxed_unsync();
+        Ok((Request::from_parts(parts, body), client_accepts))
+    }
+
+    /// Re-encodes the response messages in an encoding of `client_accepts`
+    /// when the client cannot decode them
+    pub fn transcode_response<B>(
+        &self,
+        res: Response<B>,
+        client_accepts: &[Encoding],
+    ) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
+        let (mut parts, mut body) = res.into_parts();
+        // the proxy decodes what the servers may not
+        parts
+            .headers
+            .insert(GRPC_ACCEPT_ENCODING, Encoding::accept_header());
+        let transcoder = match Encoding::from_headers(&parts.headers) {
+            Ok(from) if !client_accepts.contains(&from) => {
+                let to = client_accepts[0];
+                set_encoding(&mut parts.headers, to);
+                Some(MessageTranscoder::new(from, to, self.max_message_size))
+            }
+            // an encoding the proxy cannot decode is left to the client
+            _ => None,
+        };
+
+        let stream = try_stream! {
+            let Some(mut transcoder) = transcoder else {
+                while let Some(frame) = body.frame().await {
+                    yield frame?;
+                }
+                return;
+            };
+            while let Some(frame) = body.frame().await {
+                let result = match frame?.into_data() {
+                    Ok(data) => transcoder.push(&data).map(Frame::data),
+                    Err(frame) => transcoder.finish().map(|_| frame),
+                };
+                match result {
+                    Ok(frame) => {
+                        if frame.data_ref().is_none_or(|data| !data.is_empty()) {
+                            yield frame;
+                        }
+                    }
+                    // the upstream stream is reset when the body is dropped
+                    Err(err) => {
+                        let status = Status::new(Code::Internal, format!("Failed to re-encode the response: {}", err));
+                        yield Frame::trailers(status.to_header_map());
+                        break;
+                    }
+                }
+            }
+        };
+        let boxed: DynStream = Box::pin(stream);
+        Response::from_parts(parts, StreamBody::new(boxed))
+    }
+}
+
+fn set_encoding(headers: &mut http::HeaderMap, encoding: Encoding) {
+    match encoding {
+        Encoding::Identity => headers.remove(GRPC_ENCODING),
+        encoding => headers.insert(GRPC_ENCODING, encoding.name().parse().unwrap()),
+    };
+}
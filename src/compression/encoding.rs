@@ -0,0 +1,121 @@
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use flate2::{
+    Compression,
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use tower::BoxError;
+
+pub const GRPC_ENCODING: HeaderName = HeaderName::from_static("grpc-encoding");
+pub const GRPC_ACCEPT_ENCODING: HeaderName = HeaderName::from_static("grpc-accept-encoding");
+
+/// Compression of the messages of a call, as named in `grpc-encoding`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Identity,
+    Gzip,
+    /// zlib, as HTTP names it
+    Deflate,
+    Zstd,
+}
+
+impl Encoding {
+    /// Every encoding the proxy decodes and encodes
+    pub const SUPPORTED: [Encoding; 4] = [
+        Encoding::Identity,
+        Encoding::Gzip,
+        Encoding::Deflate,
+        Encoding::Zstd,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// The encoding of the messages of a request or response, identity
+    /// when unset, or the name of an encoding the proxy does not know
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, String> {
+        match headers.get(GRPC_ENCODING) {
+            Some(value) => {
+                let name = value.to_str().unwrap_or_default().trim();
+                Encoding::from_str(name).map_err(|_| name.to_string())
+            }
+            None => Ok(Encoding::Identity),
+        }
+    }
+
+    /// The known encodings of `grpc-accept-encoding` in order of preference,
+    /// ending with identity which is always accepted
+    pub fn accepted(headers: &HeaderMap) -> Vec<Encoding> {
+        let mut accepted: Vec<_> = headers
+            .get_all(GRPC_ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|name| Encoding::from_str(name.trim()).ok())
+            .filter(|encoding| *encoding != Encoding::Identity)
+            .collect();
+        accepted.push(Encoding::Identity);
+        accepted
+    }
+
+    /// A `grpc-accept-encoding` value listing every supported encoding
+    pub fn accept_header() -> HeaderValue {
+        let names: Vec<_> = Encoding::SUPPORTED.iter().map(Encoding::name).collect();
+        HeaderValue::from_str(&names.join(",")).unwrap()
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, BoxError> {
+        match self {
+            Encoding::Identity => Ok(data.to_vec()),
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Encoding::Zstd => Ok(zstd::encode_all(data, 0)?),
+        }
+    }
+
+    /// Fails when the decompressed message would exceed `limit` bytes
+    pub fn decompress(&self, data: &[u8], limit: usize) -> Result<Vec<u8>, BoxError> {
+        let reader: Box<dyn Read + '_> = match self {
+            Encoding::Identity => Box::new(data),
+            Encoding::Gzip => Box::new(GzDecoder::new(data)),
+            Encoding::Deflate => Box::new(ZlibDecoder::new(data)),
+            Encoding::Zstd => Box::new(zstd::Decoder::new(data)?),
+        };
+        let mut decompressed = Vec::new();
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut decompressed)?;
+        if decompressed.len() > limit {
+            return Err(format!("Decompressed message exceeds {} bytes", limit).into());
+        }
+        Ok(decompressed)
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = BoxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Encoding::SUPPORTED
+            .into_iter()
+            .find(|encoding| encoding.name() == s)
+            .ok_or_else(|| format!("Unknown encoding {:?}", s).into())
+    }
+}
@@ -0,0 +1,71 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tower::BoxError;
+
+use crate::compression::encoding::Encoding;
+
+/// Compressed-Flag, then Message-Length as a big-endian u32
+const PREFIX_LEN: usize = 5;
+
+/// Re-encodes the length-prefixed messages of a gRPC body, received in
+/// data frames of any size, from one encoding to another
+pub struct MessageTranscoder {
+    from: Encoding,
+    to: Encoding,
+    max_message_size: usize,
+    buf: BytesMut,
+}
+
+impl MessageTranscoder {
+    pub fn new(from: Encoding, to: Encoding, max_message_size: usize) -> Self {
+        Self {
+            from,
+            to,
+            max_message_size,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// The messages completed by `data`, re-encoded
+    pub fn push(&mut self, data: &[u8]) -> Result<Bytes, BoxError> {
+        self.buf.extend_from_slice(data);
+        let mut out = BytesMut::new();
+        while self.buf.len() >= PREFIX_LEN {
+            let compressed = self.buf[0] & 1 == 1;
+            let len = u32::from_be_bytes(self.buf[1..PREFIX_LEN].try_into().unwrap()) as usize;
+            if len > self.max_message_size {
+                return Err(format!("Message exceeds {} bytes", self.max_message_size).into());
+            }
+            if self.buf.len() < PREFIX_LEN + len {
+                break;
+            }
+            self.buf.advance(PREFIX_LEN);
+            let message = self.buf.split_to(len);
+            // uncompressed messages are valid whatever the encoding of the call
+            if !compressed {
+                put_message(&mut out, false, &message);
+                continue;
+            }
+            let decompressed = self.from.decompress(&message, self.max_message_size)?;
+            match self.to {
+                Encoding::Identity => put_message(&mut out, false, &decompressed),
+                to => put_message(&mut out, true, &to.compress(&decompressed)?),
+            }
+        }
+        Ok(out.freeze())
+    }
+
+    /// Fails if the body ended in the middle of a message
+    pub fn finish(&self) -> Result<(), BoxError> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err("Truncated message".into())
+        }
+    }
+}
+
+fn put_message(out: &mut BytesMut, compressed: bool, message: &[u8]) {
+    out.put_u8(compressed as u8);
+    out.put_u32(message.len() as u32);
+    out.put_slice(message);
+}
@@ -0,0 +1,3 @@
+pub mod compression_policy;
+pub mod encoding;
+pub mod message_transcoder;
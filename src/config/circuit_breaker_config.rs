@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+/// Limits of a cluster past which calls fail at once with UNAVAILABLE
+/// instead of piling up on struggling servers. Unset limits are unlimited.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// Connections opened at most to the endpoints of the cluster,
+    /// further calls share the open ones
+    pub max_connections: Option<usize>,
+    /// Calls waiting at most for a connection to be opened
+    pub max_pending_requests: Option<usize>,
+    /// Calls in flight at most to the cluster
+    pub max_concurrent_streams: Option<usize>,
+}
@@ -0,0 +1,43 @@
+use serde::Deserialize;
+
+use crate::config::{
+    circuit_breaker_config::CircuitBreakerConfig, compression_config::CompressionConfig,
+    health_check_config::HealthCheckConfig, lb_policy_config::LbPolicyConfig,
+    outlier_detection_config::OutlierDetectionConfig, upstream_tls_config::UpstreamTlsConfig,
+};
+
+/// A named group of upstream gRPC servers that routes forward to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterConfig {
+    pub name: String,
+    /// `host:port` of every upstream server of the cluster
+    pub endpoints: Vec<String>,
+    #[serde(default)]
+    pub lb_policy: LbPolicyConfig,
+    /// Endpoints failing their health checks are taken out of rotation.
+    /// Every endpoint is assumed healthy when unset.
+    pub health_check: Option<HealthCheckConfig>,
+    /// Speak TLS to the upstream servers when set, cleartext h2c otherwise
+    pub tls: Option<UpstreamTlsConfig>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub outlier_detection: Option<OutlierDetectionConfig>,
+    #[serde(default)]
+    pub compression: CompressionConfig,
+}
+
+impl ClusterConfig {
+    pub fn new(name: impl Into<String>, authority: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            endpoints: vec![authority.into()],
+            lb_policy: LbPolicyConfig::default(),
+            health_check: None,
+            tls: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            outlier_detection: None,
+            compression: CompressionConfig::default(),
+        }
+    }
+}
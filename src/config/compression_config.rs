@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+/// How the messages of the calls to a cluster are compressed
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionConfig {
+    /// Encodings the servers decode, in order of preference. Requests in
+    /// another encoding are re-encoded in the first one, or uncompressed
+    /// when the list is empty.
+    pub accept_encodings: Vec<String>,
+    /// Largest message re-encoded, before and after decompression,
+    /// or converted from or to JSON
+    pub max_message_bytes: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            accept_encodings: vec!["gzip".into(), "deflate".into(), "zstd".into()],
+            max_message_bytes: 4 << 20,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use toml::de::{DeTable, DeValue};
+
+use crate::config::proxy_config::ProxyConfig;
+
+/// A TOML configuration file, kept around to point errors at their location
+pub struct ConfigFile {
+    path: PathBuf,
+    source: String,
+}
+
+/// An error in a configuration file, with its line and column when known
+#[derive(Debug)]
+pub struct ConfigFileError {
+    pub path: PathBuf,
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ConfigFile {
+    pub fn read(path: &Path) -> Result<Self, ConfigFileError> {
+        let source = std::fs::read_to_string(path).map_err(|e| ConfigFileError {
+            path: path.to_path_buf(),
+            location: None,
+            message: format!("Cannot read the configuration file: {}", e),
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The configuration, relative file paths being resolved against
+    /// the directory of the file
+    pub fn parse(&self) -> Result<ProxyConfig, ConfigFileError> {
+        let mut config: ProxyConfig =
+            toml::from_str(&self.source).map_err(|e| ConfigFileError {
+                path: self.path.clone(),
+                location: e.span().map(|span| self.position(span.start)),
+                message: e.message().trim_end().to_string(),
+            })?;
+
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        };
+        if let Some(tls) = &mut config.tls {
+            resolve(&mut tls.cert_path);
+            resolve(&mut tls.key_path);
+        }
+        for tls in config.clusters.iter_mut().filter_map(|c| c.tls.as_mut()) {
+            tls.ca_path.as_mut().map(resolve);
+            tls.client_cert_path.as_mut().map(resolve);
+            tls.client_key_path.as_mut().map(resolve);
+        }
+        if let Some(transcoding) = &mut config.transcoding {
+            resolve(&mut transcoding.descriptor_set_path);
+        }
+        Ok(config)
+    }
+
+    /// An error about `field`, a path such as `clusters[0].tls.ca_path`,
+    /// located at the deepest part of that path present in the file
+    pub fn error(&self, field: &str, message: impl fmt::Display) -> ConfigFileError {
+        ConfigFileError {
+            path: self.path.clone(),
+            location: self.locate(field).map(|span| self.position(span.start)),
+            message: format!("{}: {}", field, message),
+        }
+    }
+
+    fn locate(&self, field: &str) -> Option<Range<usize>> {
+        let root = DeTable::parse(&self.source).ok()?;
+        let root = DeValue::Table(root.into_inner());
+        let mut value = &root;
+        let mut span = None;
+        for part in field.split('.') {
+            let (key, indexes) = part.split_once('[').unwrap_or((part, ""));
+            let Some(next) = value.get(key) else {
+                return span;
+            };
+            span = Some(next.span());
+            value = next.get_ref();
+            for index in indexes.split('[') {
+                let Ok(index) = index.trim_end_matches(']').parse::<usize>() else {
+                    continue;
+                };
+                let Some(next) = value.get(index) else {
+                    return span;
+                };
+                span = Some(next.span());
+                value = next.get_ref();
+            }
+        }
+        span
+    }
+
+    /// 1-based line and column of a byte offset
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        (line, column)
+    }
+}
+
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{}:{}:{}: {}",
+                self.path.display(),
+                line,
+                column,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigFileError {}
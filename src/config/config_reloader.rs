@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::watch;
+use tower::BoxError;
+
+use crate::{
+    command::check_config::validate, config::proxy_config::ProxyConfig, context::ProxyContext,
+};
+
+type LoadConfig = Box<dyn Fn() -> Result<ProxyConfig, BoxError> + Send + Sync>;
+
+/// Reloads the configuration when its file is modified or when `trigger`
+/// is notified, typically on SIGHUP.
+///
+/// Everything but the listener and its TLS settings is replaced.
+pub struct ConfigReloader {
+    path: PathBuf,
+    interval: Duration,
+    trigger: watch::Receiver<()>,
+    load: LoadConfig,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigReloader {
+    /// `load` builds the new configuration, usually by reading `path`
+    /// and applying the same overrides as on startup
+    pub fn new(
+        path: PathBuf,
+        interval: Duration,
+        trigger: watch::Receiver<()>,
+        load: impl Fn() -> Result<ProxyConfig, BoxError> + Send + Sync + 'static,
+    ) -> Self {
+        let modified = modified_time(&path);
+        Self {
+            path,
+            interval,
+            trigger,
+            load: Box::new(load),
+            modified,
+        }
+    }
+
+    /// Polls the file every `interval` and waits for the trigger,
+    /// applying each new valid configuration to `ctx`
+    pub async fn watch(mut self, ctx: Arc<ProxyContext>) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut triggerable = true;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let modified = modified_time(&self.path);
+                    if modified == self.modified {
+                        continue;
+                    }
+                    self.modified = modified;
+                }
+                changed = self.trigger.changed(), if triggerable => {
+                    if changed.is_err() {
+                        triggerable = false;
+                        continue;
+                    }
+                }
+            }
+            match self.reload(&ctx) {
+                Ok(()) => {
+                    ctx.metrics.config_reloads.inc();
+                    println!("Configuration reloaded from {:?}", self.path);
+                }
+                Err(err) => {
+                    ctx.metrics.config_reload_failures.inc();
+                    eprintln!(
+                        "Failed to reload configuration from {:?}: {}",
+                        self.path, err
+                    );
+                }
+            }
+        }
+    }
+
+    fn reload(&self, ctx: &Arc<ProxyContext>) -> Result<(), BoxError> {
+        let config = (self.load)()?;
+        let errors = validate(&config);
+        if !errors.is_empty() {
+            let errors: Vec<_> = errors
+                .iter()
+                .map(|(field, err)| format!("{}: {}", field, err))
+                .collect();
+            return Err(errors.join(", ").into());
+        }
+        ctx.reload(&config)
+    }
+}
+
+/// `None` while the file is missing, so that it is reloaded once it is back
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+/// CORS settings for browser clients.
+///
+/// Origins are matched against `allowed_origins`, each entry being either
+/// an exact origin (`https://app.example.com`), `*` for any origin,
+/// a wildcard subdomain (`https://*.example.com`) or a regular expression
+/// prefixed with `regex:`. An empty list rejects every cross-origin request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    /// Custom metadata the browser is allowed to send,
+    /// on top of the headers grpc-web clients always need.
+    pub allowed_headers: Vec<String>,
+    /// Response metadata the browser is allowed to read,
+    /// on top of `grpc-status` and `grpc-message`.
+    pub exposed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_headers: Vec::new(),
+            exposed_headers: Vec::new(),
+            allow_credentials: false,
+            max_age_secs: 24 * 60 * 60,
+        }
+    }
+}
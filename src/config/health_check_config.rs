@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+/// Active `grpc.health.v1.Health/Check` probing of every endpoint of a cluster.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthCheckConfig {
+    pub interval_ms: u64,
+    /// A check not answered in time counts as a failure
+    pub timeout_ms: u64,
+    /// Service name sent in the check, the empty string is the whole server
+    pub service: String,
+    /// Consecutive successes bringing an endpoint back into rotation
+    pub healthy_threshold: u32,
+    /// Consecutive failures taking an endpoint out of rotation
+    pub unhealthy_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 5000,
+            timeout_ms: 1000,
+            service: String::new(),
+            healthy_threshold: 2,
+            unhealthy_threshold: 3,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+/// Copies of a call sent to other endpoints while the previous ones have not
+/// answered, after the hedging policy of the gRPC service config.
+///
+/// Only meant for idempotent unary methods.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HedgingPolicyConfig {
+    /// Attempts including the first one, from 2 up to 5
+    pub max_attempts: u32,
+    /// Delay before each hedged attempt
+    pub hedging_delay_ms: u64,
+    /// Status code names that do not end the call, such as `UNAVAILABLE`.
+    /// Any other status is returned to the client as the call's answer.
+    pub non_fatal_status_codes: Vec<String>,
+    /// Request bytes kept to be sent again, calls sending more are not hedged
+    pub buffer_limit_bytes: usize,
+}
+
+impl Default for HedgingPolicyConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 2,
+            hedging_delay_ms: 50,
+            non_fatal_status_codes: Vec::new(),
+            buffer_limit_bytes: 1 << 20,
+        }
+    }
+}
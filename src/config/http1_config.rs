@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+/// HTTP/1.1 settings of the client connections
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http1Config {
+    /// Serve several requests on a connection
+    pub keep_alive: bool,
+    /// Time a client has to send the headers of a request
+    pub header_read_timeout_ms: u64,
+    /// Largest buffer of a connection, bounding the size of the request headers
+    pub max_buf_size: Option<usize>,
+}
+
+impl Default for Http1Config {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            header_read_timeout_ms: 30_000,
+            max_buf_size: None,
+        }
+    }
+}
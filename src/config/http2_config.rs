@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+/// HTTP/2 settings of a connection, hyper defaults when unset
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http2Config {
+    pub initial_stream_window_size: Option<u32>,
+    pub initial_connection_window_size: Option<u32>,
+    /// Sizes the windows from the bandwidth-delay product of the connection,
+    /// overriding the initial window sizes
+    pub adaptive_window: bool,
+    pub max_frame_size: Option<u32>,
+    pub max_header_list_size: Option<u32>,
+    /// Interval of the PING frames checking the peer is still alive
+    pub keep_alive_interval_ms: Option<u64>,
+    /// Time the peer has to acknowledge a PING before the connection is closed
+    pub keep_alive_timeout_ms: u64,
+}
+
+impl Default for Http2Config {
+    fn default() -> Self {
+        Self {
+            initial_stream_window_size: None,
+            initial_connection_window_size: None,
+            adaptive_window: false,
+            max_frame_size: None,
+            max_header_list_size: None,
+            keep_alive_interval_ms: None,
+            keep_alive_timeout_ms: 20_000,
+        }
+    }
+}
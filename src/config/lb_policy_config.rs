@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+/// How a cluster spreads calls over its endpoints.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum LbPolicyConfig {
+    #[default]
+    RoundRobin,
+    /// The endpoint with the fewest calls in flight
+    LeastRequest,
+    /// The less loaded of two endpoints picked at random
+    PowerOfTwoChoices,
+    /// Calls carrying the same `header` value go to the same endpoint,
+    /// calls without it are spread round robin
+    ConsistentHash { header: String },
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+/// Bounds on the load clients put on the proxy, unset limits being unlimited.
+/// A reload lowering them lets the calls and connections over the new limits complete.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Client connections open at once, further ones wait to be accepted
+    pub max_connections: Option<usize>,
+    /// Streams a client may open at once on an HTTP/2 connection,
+    /// advertised in SETTINGS_MAX_CONCURRENT_STREAMS
+    pub max_concurrent_streams_per_connection: Option<u32>,
+    /// Calls in flight at once over every connection
+    pub max_in_flight: Option<usize>,
+    /// Time a call over `max_in_flight` waits for another one to complete
+    /// before failing with UNAVAILABLE, 0 shedding it at once. Calls reaching
+    /// their deadline first fail with DEADLINE_EXCEEDED.
+    pub queue_timeout_ms: u64,
+}
@@ -0,0 +1,22 @@
+pub mod circuit_breaker_config;
+pub mod cluster_config;
+pub mod compression_config;
+pub mod config_file;
+pub mod config_reloader;
+pub mod cors_config;
+pub mod health_check_config;
+pub mod hedging_policy_config;
+pub mod http1_config;
+pub mod http2_config;
+pub mod lb_policy_config;
+pub mod limits_config;
+pub mod outlier_detection_config;
+pub mod pool_config;
+pub mod proxy_config;
+pub mod rate_limit_config;
+pub mod retry_policy_config;
+pub mod route_config;
+pub mod server_config;
+pub mod tls_config;
+pub mod transcoding_config;
+pub mod upstream_tls_config;
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+/// Ejects from load balancing the endpoints failing several calls in a row,
+/// with connection errors, 5xx HTTP statuses or UNAVAILABLE.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutlierDetectionConfig {
+    pub consecutive_failures: u32,
+    /// Ejection time, multiplied by the number of times the endpoint was
+    /// ejected, one ejection being forgotten for each such time spent in rotation
+    pub base_ejection_ms: u64,
+    pub max_ejection_ms: u64,
+    /// Share of the endpoints of the cluster that can be ejected at once,
+    /// at least one can always be
+    pub max_ejection_percent: u32,
+}
+
+impl Default for OutlierDetectionConfig {
+    fn default() -> Self {
+        Self {
+            consecutive_failures: 5,
+            base_ejection_ms: 30_000,
+            max_ejection_ms: 300_000,
+            max_ejection_percent: 50,
+        }
+    }
+}
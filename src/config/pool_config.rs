@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+use crate::config::http2_config::Http2Config;
+
+/// Limits of the upstream HTTP/2 connection pool.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolConfig {
+    /// Connections opened at most to a single upstream authority
+    pub max_connections_per_authority: usize,
+    /// Streams multiplexed on one connection before another one is opened,
+    /// lowered to the SETTINGS_MAX_CONCURRENT_STREAMS of the upstream server
+    /// once it is received.
+    pub max_concurrent_streams: usize,
+    /// Time given to open a connection, TLS and HTTP/2 handshakes included
+    pub connect_timeout_ms: u64,
+    /// Settings of the connections opened from now on. PINGs are also sent
+    /// on idle connections so that dead ones are evicted before being used.
+    pub http2: Http2Config,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections_per_authority: 4,
+            max_concurrent_streams: 100,
+            connect_timeout_ms: 5000,
+            http2: Http2Config::default(),
+        }
+    }
+}
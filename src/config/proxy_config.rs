@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+use crate::config::{
+    cluster_config::ClusterConfig, cors_config::CorsConfig, limits_config::LimitsConfig,
+    pool_config::PoolConfig, route_config::RouteConfig, server_config::ServerConfig,
+    tls_config::TlsConfig, transcoding_config::TranscodingConfig,
+};
+
+pub const DEFAULT_CLUSTER: &str = "default";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// `host:port` the binary listens on
+    pub listen: String,
+    pub clusters: Vec<ClusterConfig>,
+    /// Calls matching no route are answered with UNIMPLEMENTED
+    pub routes: Vec<RouteConfig>,
+    pub cors: CorsConfig,
+    pub pool: PoolConfig,
+    pub limits: LimitsConfig,
+    pub server: ServerConfig,
+    /// Serve TLS instead of cleartext when set
+    pub tls: Option<TlsConfig>,
+    /// Serve REST clients the methods annotated with `google.api.http`
+    pub transcoding: Option<TranscodingConfig>,
+    /// Time given to in-flight calls to finish on shutdown
+    /// before the remaining connections are closed
+    pub drain_timeout_ms: u64,
+}
+
+impl ProxyConfig {
+    /// Forwards every call to a single upstream server,
+    /// the cluster named [`DEFAULT_CLUSTER`]
+    pub fn new(forward_authority: impl Into<String>) -> Self {
+        Self {
+            listen: "127.0.0.1:8080".to_string(),
+            clusters: vec![ClusterConfig::new(DEFAULT_CLUSTER, forward_authority)],
+            routes: vec![RouteConfig::new("*", DEFAULT_CLUSTER)],
+            cors: CorsConfig::default(),
+            pool: PoolConfig::default(),
+            limits: LimitsConfig::default(),
+            server: ServerConfig::default(),
+            tls: None,
+            transcoding: None,
+            drain_timeout_ms: 30_000,
+        }
+    }
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self::new("127.0.0.1:3000")
+    }
+}
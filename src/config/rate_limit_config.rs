@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+/// A token bucket admitting `requests_per_second` calls on average
+/// and up to `burst` at once, per value of `key`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_second: f64,
+    pub burst: u32,
+    #[serde(default)]
+    pub key: RateLimitKeyConfig,
+}
+
+/// What calls share a bucket
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum RateLimitKeyConfig {
+    /// Every call of the route
+    #[default]
+    Route,
+    /// Calls from the same client IP address
+    PeerIp,
+    /// Calls with the same value of this metadata, such as `x-api-key`.
+    /// Calls without it share a bucket.
+    Header(String),
+}
@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+/// Retries of the calls failing with one of `retryable_status_codes`
+/// before any response has been sent to the client, after the retry policy
+/// of the gRPC service config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicyConfig {
+    /// Attempts including the first one, from 2 up to 5
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub backoff_multiplier: f64,
+    /// Status code names such as `UNAVAILABLE`
+    pub retryable_status_codes: Vec<String>,
+    /// Request bytes kept to be replayed, calls sending more are not retried
+    pub buffer_limit_bytes: usize,
+}
+
+impl Default for RetryPolicyConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            backoff_multiplier: 2.0,
+            retryable_status_codes: vec!["UNAVAILABLE".to_string()],
+            buffer_limit_bytes: 1 << 20,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use serde::Deserialize;
+
+use crate::config::{
+    hedging_policy_config::HedgingPolicyConfig, rate_limit_config::RateLimitConfig,
+    retry_policy_config::RetryPolicyConfig,
+};
+
+/// Sends the calls matching `path` and `headers` to `cluster`.
+///
+/// Routes are evaluated in order and the first match wins.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    /// `/package.Service/Method` for a single method,
+    /// a pattern ending with `*` such as `/package.Service/*` for a prefix,
+    /// or `*` for every call
+    pub path: String,
+    /// Headers that must all match as well
+    #[serde(default)]
+    pub headers: Vec<HeaderMatchConfig>,
+    pub cluster: String,
+    /// `grpc-timeout` given to calls sent without one
+    pub default_timeout_ms: Option<u64>,
+    /// Longest `grpc-timeout` allowed, shorter ones are kept as they are.
+    /// Calls without one get this timeout unless `default_timeout_ms` is set.
+    pub max_timeout_ms: Option<u64>,
+    pub retry_policy: Option<RetryPolicyConfig>,
+    /// Exclusive with `retry_policy`
+    pub hedging_policy: Option<HedgingPolicyConfig>,
+    /// Calls over any of these limits fail with RESOURCE_EXHAUSTED
+    #[serde(default)]
+    pub rate_limits: Vec<RateLimitConfig>,
+}
+
+impl RouteConfig {
+    pub fn new(path: impl Into<String>, cluster: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            headers: Vec::new(),
+            cluster: cluster.into(),
+            default_timeout_ms: None,
+            max_timeout_ms: None,
+            retry_policy: None,
+            hedging_policy: None,
+            rate_limits: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderMatchConfig {
+    pub name: String,
+    /// The exact value, a regular expression prefixed with `regex:`,
+    /// or `None` to only require the header to be present
+    pub value: Option<String>,
+}
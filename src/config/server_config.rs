@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+use crate::config::{http1_config::Http1Config, http2_config::Http2Config};
+
+/// Protocol settings and lifetime of the client connections, those accepted
+/// after a reload using the new ones.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub http1: Http1Config,
+    pub http2: Http2Config,
+    /// Age after which a connection is sent a GOAWAY, or Connection: close on
+    /// HTTP/1, so that clients reconnect and spread over the proxy replicas
+    pub max_connection_age_ms: Option<u64>,
+    /// Time given to the calls of a connection past its max age to complete
+    /// before it is closed, unlimited when unset
+    pub max_connection_age_grace_ms: Option<u64>,
+    /// Time without any call after which a connection is sent a GOAWAY
+    pub max_connection_idle_ms: Option<u64>,
+}
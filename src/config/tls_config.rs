@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Deserializer, de::Error};
+
+/// TLS termination on the proxy listener.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf certificate first
+    pub cert_path: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: PathBuf,
+    /// How often the files are checked for changes, more than 0
+    #[serde(
+        default = "default_reload_interval_ms",
+        deserialize_with = "deserialize_reload_interval_ms"
+    )]
+    pub reload_interval_ms: u64,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            reload_interval_ms: default_reload_interval_ms(),
+        }
+    }
+}
+
+fn default_reload_interval_ms() -> u64 {
+    10_000
+}
+
+fn deserialize_reload_interval_ms<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    match u64::deserialize(deserializer)? {
+        0 => Err(D::Error::custom(
+            "reload_interval_ms must be greater than 0",
+        )),
+        interval => Ok(interval),
+    }
+}
@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// REST clients calling the methods annotated with `google.api.http`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TranscodingConfig {
+    /// `FileDescriptorSet` built with `protoc --include_imports --descriptor_set_out`
+    pub descriptor_set_path: PathBuf,
+    /// Full names of the services exposed, every service when empty
+    #[serde(default)]
+    pub services: Vec<String>,
+}
+
+impl TranscodingConfig {
+    pub fn new(descriptor_set_path: impl Into<PathBuf>) -> Self {
+        Self {
+            descriptor_set_path: descriptor_set_path.into(),
+            services: Vec::new(),
+        }
+    }
+}
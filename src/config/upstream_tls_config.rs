@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// TLS towards an upstream gRPC server.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpstreamTlsConfig {
+    /// PEM bundle of the CAs trusted for the upstream server,
+    /// the system roots are used when unset
+    pub ca_path: Option<PathBuf>,
+    /// PEM client certificate chain presented for mTLS
+    pub client_cert_path: Option<PathBuf>,
+    /// PEM private key of the client certificate
+    pub client_key_path: Option<PathBuf>,
+    /// Name sent in SNI and verified against the server certificate,
+    /// the host of the upstream authority when unset
+    pub server_name: Option<String>,
+    /// Accept any server certificate, for development only
+    pub insecure_skip_verify: bool,
+}
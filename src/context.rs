@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio::task::JoinHandle;
+use tower::BoxError;
+
+use crate::{
+    admission::{in_flight_limiter::InFlightLimiter, resizable_semaphore::ResizableSemaphore},
+    config::proxy_config::ProxyConfig,
+    cors::cors_policy::CorsPolicy,
+    health::health_checker::HealthChecker,
+    health::proxy_health::ProxyHealth,
+    routing::route_table::RouteTable,
+    server::server_builder::{ServerSettings, check_http2},
+    telemetry::metrics::Metrics,
+    transcoding::json_transcoder::JsonTranscoder,
+    upstream::connection_pool::ConnectionPool,
+};
+
+/// The part of the configuration replaced on reload.
+///
+/// A request keeps the snapshot it started with until it completes.
+pub struct ProxyState {
+    pub routes: RouteTable,
+    pub cors: CorsPolicy,
+    pub transcoder: Option<JsonTranscoder>,
+}
+
+impl ProxyState {
+    pub fn new(config: &ProxyConfig) -> Result<Self, BoxError> {
+        let transcoder = config
+            .transcoding
+            .as_ref()
+            .map(JsonTranscoder::from_config)
+            .transpose()?;
+        Ok(Self {
+            routes: RouteTable::new(&config.clusters, &config.routes)?,
+            cors: CorsPolicy::from_config(&config.cors, transcoder.as_ref())?,
+            transcoder,
+        })
+    }
+}
+
+/// State shared by every request handled by the proxy,
+/// built from the [`ProxyConfig`].
+pub struct ProxyContext {
+    state: RwLock<Arc<ProxyState>>,
+    pub pool: ConnectionPool,
+    pub metrics: Metrics,
+    pub health: ProxyHealth,
+    pub in_flight: InFlightLimiter,
+    /// Client connections open at once
+    pub connection_slots: ResizableSemaphore,
+    server: RwLock<Arc<ServerSettings>>,
+    /// `https` when the listener terminates TLS, `http` otherwise
+    pub scheme: &'static str,
+    health_checkers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl ProxyContext {
+    pub fn new(config: &ProxyConfig) -> Result<Self, BoxError> {
+        check_http2(&config.pool.http2)?;
+        let metrics = Metrics::new();
+        Ok(Self {
+            state: RwLock::new(Arc::new(ProxyState::new(config)?)),
+            pool: ConnectionPool::new(config.pool.clone(), metrics.clone()),
+            in_flight: InFlightLimiter::new(&config.limits, metrics.clone()),
+            connection_slots: ResizableSemaphore::new(config.limits.max_connections),
+            server: RwLock::new(Arc::new(ServerSettings::new(config)?)),
+            scheme: if config.tls.is_some() {
+                "https"
+            } else {
+                "http"
+            },
+            metrics,
+            health: ProxyHealth::new(),
+            health_checkers: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// The routes, clusters, CORS policy and transcoding rules new requests are handled with
+    pub fn state(&self) -> Arc<ProxyState> {
+        self.state.read().unwrap().clone()
+    }
+
+    /// The settings of the client connections accepted from now on
+    pub fn server(&self) -> Arc<ServerSettings> {
+        self.server.read().unwrap().clone()
+    }
+
+    /// Swaps the state, limits and connection settings for those of `config`,
+    /// leaving the current ones untouched when `config` is invalid
+    pub fn reload(self: &Arc<Self>, config: &ProxyConfig) -> Result<(), BoxError> {
+        let state = Arc::new(ProxyState::new(config)?);
+        let server = Arc::new(ServerSettings::new(config)?);
+        check_http2(&config.pool.http2)?;
+        self.pool.reconfigure(config.pool.clone());
+        *self.state.write().unwrap() = state;
+        *self.server.write().unwrap() = server;
+        self.in_flight.reconfigure(&config.limits);
+        self.connection_slots.resize(config.limits.max_connections);
+        self.start_health_checks();
+        self.health.notify();
+        Ok(())
+    }
+
+    /// Health checks the endpoints of the current clusters,
+    /// stopping the checks of the previous ones
+    pub fn start_health_checks(self: &Arc<Self>) {
+        let checkers = HealthChecker::spawn_all(self, &self.state().routes);
+        let previous = std::mem::replace(&mut *self.health_checkers.lock().unwrap(), checkers);
+        for checker in previous {
+            checker.abort();
+        }
+    }
+
+    pub fn stop_health_checks(&self) {
+        for checker in self.health_checkers.lock().unwrap().drain(..) {
+            checker.abort();
+        }
+    }
+}
@@ -1,14 +1,27 @@
+use async_stream::try_stream;
 use bytes::Bytes;
-use http::{HeaderValue, Request};
+use http::{HeaderValue, Request, Response, StatusCode};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Incoming;
 use hyper::client::conn::http2;
+use tokio::time::{Instant, timeout_at};
 use tower::BoxError;
 
-use crate::core::stream_response::StreamResponse;
-use crate::core::{grpc_kind_plain::GrpcKindPlain, grpc_kind_web::GrpcKindWeb};
+use crate::core::proxy_error::ProxyError;
+use crate::core::status::Status;
+use crate::core::stream_response::{DynStream, StreamResponse, UpstreamBody, from_frame};
+use crate::core::{
+    grpc_kind_connect::GrpcKindConnect, grpc_kind_plain::GrpcKindPlain, grpc_kind_web::GrpcKindWeb,
+    grpc_kind_web_text::GrpcKindWebText,
+};
 
+#[derive(Clone, Copy)]
 pub enum GrpcKind {
     Web(GrpcKindWeb),
+    WebText(GrpcKindWebText),
     Plain(GrpcKindPlain),
+    Connect(GrpcKindConnect),
 }
 impl GrpcKind {
     pub fn from_content_type(content_type: &HeaderValue) -> Option<Self> {
@@ -18,32 +31,137 @@ impl GrpcKind {
             || content_type == "application/grpc-web+proto"
         {
             Some(GrpcKind::Web(GrpcKindWeb))
+        } else if content_type == "application/grpc-web-text"
+            || content_type == "application/grpc-web-text+proto"
+        {
+            Some(GrpcKind::WebText(GrpcKindWebText))
         } else {
-            None
+            GrpcKindConnect::from_content_type(content_type).map(GrpcKind::Connect)
         }
     }
-    pub async fn forward<B>(
-        self,
-        mut sender: http2::SendRequest<B>,
-        mut req: Request<B>,
-    ) -> Result<StreamResponse, BoxError>
+
+    pub fn content_type(&self) -> HeaderValue {
+        match self {
+            GrpcKind::Plain(_) => HeaderValue::from_static("application/grpc"),
+            GrpcKind::Web(_) => HeaderValue::from_static("application/grpc-web+proto"),
+            GrpcKind::WebText(_) => HeaderValue::from_static("application/grpc-web-text+proto"),
+            GrpcKind::Connect(kind) => kind.content_type(),
+        }
+    }
+
+    /// The frame ending a response body with `status`
+    pub fn status_frame(&self, status: &Status) -> Frame<Bytes> {
+        match self {
+            GrpcKind::Plain(kind) => kind.status_frame(status),
+            GrpcKind::Web(kind) => kind.status_frame(status),
+            GrpcKind::WebText(kind) => kind.status_frame(status),
+            GrpcKind::Connect(kind) => kind.status_frame(status),
+        }
+    }
+
+    /// A response made only of `status`, for calls failed by the proxy
+    pub fn status_response(&self, status: &Status) -> StreamResponse {
+        if let GrpcKind::Connect(kind) = self {
+            return kind.status_response(status);
+        }
+        let mut res = from_frame(self.status_frame(status));
+        *res.status_mut() = StatusCode::OK;
+        res.headers_mut()
+            .insert(http::header::CONTENT_TYPE, self.content_type());
+        res
+    }
+
+    /// The request as plain gRPC, whatever the encoding it was received in
+    pub fn decode_request<B>(&self, req: Request<B>) -> Request<UpstreamBody>
     where
         B: hyper::body::Body<Data = Bytes> + Send + 'static,
         B::Error: Into<BoxError>,
     {
         match self {
-            GrpcKind::Web(ref kind) => kind.modify_request(&mut req),
-            GrpcKind::Plain(_) => {}
+            GrpcKind::Web(kind) => {
+                let mut req = req.map(|body| body.map_err(Into::into).boxed_unsync());
+                kind.modify_request(&mut req);
+                req
+            }
+            GrpcKind::WebText(kind) => kind.modify_request(req),
+            GrpcKind::Plain(_) => req.map(|body| body.map_err(Into::into).boxed_unsync()),
+            GrpcKind::Connect(kind) => kind.modify_request(req),
         }
+    }
 
-        let res = sender
-            .send_request(req)
-            .await
-            .map_err(Into::<BoxError>::into)?;
+    /// A length-prefixed message of a response body built by the proxy
+    pub fn message_frame(&self, message: Bytes) -> Frame<Bytes> {
+        match self {
+            GrpcKind::WebText(kind) => kind.message_frame(message),
+            GrpcKind::Connect(kind) => kind.message_frame(message),
+            GrpcKind::Plain(_) | GrpcKind::Web(_) => Frame::data(message),
+        }
+    }
 
+    /// Sends a plain gRPC request, failing with [`ProxyError::Timeout`]
+    /// when its response headers do not arrive before `deadline`
+    pub async fn send(
+        mut sender: http2::SendRequest<UpstreamBody>,
+        req: Request<UpstreamBody>,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Incoming>, ProxyError> {
+        let send = sender.send_request(req);
+        let res = match deadline {
+            Some(deadline) => timeout_at(deadline, send)
+                .await
+                .map_err(|_| ProxyError::Timeout)?,
+            None => send.await,
+        }
+        .map_err(|err| ProxyError::Upstream(err.into()))?;
+
+        if res.status() != StatusCode::OK {
+            return Err(ProxyError::UpstreamHttpStatus(res.status()));
+        }
+        Ok(res)
+    }
+
+    /// The upstream response in the encoding of the call,
+    /// ended with DEADLINE_EXCEEDED if still streaming at `deadline`.
+    /// Unary Connect responses are only returned once the call has completed.
+    pub async fn respond<B>(&self, res: Response<B>, deadline: Option<Instant>) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
+        let res = match self {
+            GrpcKind::Plain(kind) => kind.modify_response(res),
+            GrpcKind::Web(kind) => kind.modify_response(res),
+            GrpcKind::WebText(kind) => kind.modify_response(res),
+            GrpcKind::Connect(kind) => kind.modify_response(res),
+        };
+        let res = match deadline {
+            Some(deadline) => self.end_at(res, deadline),
+            None => res,
+        };
         match self {
-            GrpcKind::Plain(ref kind) => Ok(kind.modify_response(res)),
-            GrpcKind::Web(ref kind) => Ok(kind.modify_response(res)),
+            GrpcKind::Connect(kind) if !kind.is_streaming() => kind.unary_response(res).await,
+            _ => res,
         }
     }
+
+    /// Ends the response body with DEADLINE_EXCEEDED once `deadline` passes,
+    /// resetting the upstream stream
+    fn end_at(&self, res: StreamResponse, deadline: Instant) -> StreamResponse {
+        let kind = *self;
+        res.map(|mut body| {
+            let forward_stream = try_stream! {
+                loop {
+                    match timeout_at(deadline, body.frame()).await {
+                        Ok(Some(frame)) => yield frame?,
+                        Ok(None) => break,
+                        Err(_) => {
+                            yield kind.status_frame(&ProxyError::Timeout.status());
+                            break;
+                        }
+                    }
+                }
+            };
+            let boxed: DynStream = Box::pin(forward_stream);
+            StreamBody::new(boxed)
+        })
+    }
 }
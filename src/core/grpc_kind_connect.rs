@@ -0,0 +1,400 @@
+use std::time::Duration;
+
+use async_stream::try_stream;
+use base64::{
+    Engine, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_core::Stream;
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, TE},
+};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full, Limited, StreamBody};
+use percent_encoding::percent_decode_str;
+use serde_json::{Map, Value, json};
+use tower::BoxError;
+
+use crate::{
+    compression::encoding::{GRPC_ACCEPT_ENCODING, GRPC_ENCODING},
+    core::{
+        grpc_kind_json::single_message,
+        grpc_kind_plain::GrpcKindPlain,
+        grpc_timeout::{GRPC_TIMEOUT, encode_grpc_timeout},
+        status::{Code, GRPC_MESSAGE, GRPC_STATUS, Status},
+        stream_response::{DynStream, StreamResponse, UpstreamBody, from_frame},
+    },
+    telemetry::metrics::from_full_bytes,
+};
+
+const CONNECT_TIMEOUT_MS: HeaderName = HeaderName::from_static("connect-timeout-ms");
+const CONNECT_CONTENT_ENCODING: HeaderName = HeaderName::from_static("connect-content-encoding");
+const CONNECT_ACCEPT_ENCODING: HeaderName = HeaderName::from_static("connect-accept-encoding");
+
+/// Flag of the message ending a streaming response with the status of the call
+const END_STREAM_FLAG: u8 = 0x02;
+
+/// Largest unary request or response body buffered by the proxy
+const MAX_BODY_BYTES: usize = 4 << 20;
+
+/// The base64 of GET messages, URL-safe with optional padding
+const BASE64_URL: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// How the messages of a Connect call are serialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Proto,
+    Json,
+}
+
+/// The Connect protocol, spoken by connectrpc clients. Streaming calls frame
+/// their messages like gRPC and end with a JSON message carrying the status,
+/// unary calls send a bare message and answer errors with an HTTP status and
+/// a JSON body, so their response is only sent once the call has completed.
+///
+/// <https://connectrpc.com/docs/protocol>
+#[derive(Clone, Copy)]
+pub struct GrpcKindConnect {
+    codec: Codec,
+    streaming: bool,
+}
+
+impl GrpcKindConnect {
+    pub fn from_content_type(content_type: &HeaderValue) -> Option<Self> {
+        let (codec, streaming) = match content_type.to_str().ok()? {
+            "application/connect+proto" => (Codec::Proto, true),
+            "application/connect+json" => (Codec::Json, true),
+            "application/proto" => (Codec::Proto, false),
+            "application/json" => (Codec::Json, false),
+            _ => return None,
+        };
+        Some(Self { codec, streaming })
+    }
+
+    /// A Connect call from its content type, or a unary call sent as a GET
+    /// request with its message in the query
+    pub fn from_request(parts: &http::request::Parts) -> Option<Self> {
+        if let Some(content_type) = parts.headers.get(CONTENT_TYPE) {
+            return Self::from_content_type(content_type);
+        }
+        if parts.method != Method::GET {
+            return None;
+        }
+        let codec = match query_param(parts.uri.query()?, "encoding")?.as_slice() {
+            b"proto" => Codec::Proto,
+            b"json" => Codec::Json,
+            _ => return None,
+        };
+        Some(Self {
+            codec,
+            streaming: false,
+        })
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    pub fn content_type(&self) -> HeaderValue {
+        HeaderValue::from_static(match (self.streaming, self.codec) {
+            (true, Codec::Proto) => "application/connect+proto",
+            (true, Codec::Json) => "application/connect+json",
+            (false, Codec::Proto) => "application/proto",
+            (false, Codec::Json) => "application/json",
+        })
+    }
+
+    /// Streaming calls end with an end-of-stream message, the status of unary
+    /// calls stays in trailers until their response is completed
+    pub fn status_frame(&self, status: &Status) -> Frame<Bytes> {
+        if self.streaming {
+            Frame::data(end_stream_message(&status.to_header_map()))
+        } else {
+            Frame::trailers(status.to_header_map())
+        }
+    }
+
+    pub fn status_response(&self, status: &Status) -> StreamResponse {
+        if !self.streaming {
+            return error_response(status, &HeaderMap::new(), &HeaderMap::new());
+        }
+        let mut res = from_frame(self.status_frame(status));
+        res.headers_mut().insert(CONTENT_TYPE, self.content_type());
+        res
+    }
+
+    /// Unary calls send their message without its length prefix
+    pub fn message_frame(&self, message: Bytes) -> Frame<Bytes> {
+        if self.streaming {
+            Frame::data(message)
+        } else {
+            Frame::data(message.slice(5.min(message.len())..))
+        }
+    }
+
+    pub fn modify_request<B>(&self, req: Request<B>) -> Request<UpstreamBody>
+    where
+        B: hyper::body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let (mut parts, body) = req.into_parts();
+        let body = body.map_err(Into::into).boxed_unsync();
+        let headers = &mut parts.headers;
+        if let Some(timeout) = headers.remove(CONNECT_TIMEOUT_MS).and_then(parse_timeout) {
+            headers.insert(GRPC_TIMEOUT, encode_grpc_timeout(timeout));
+        }
+
+        let body = if self.streaming {
+            // the envelopes of streaming calls are gRPC messages
+            rename_header(headers, CONNECT_CONTENT_ENCODING, GRPC_ENCODING);
+            rename_header(headers, CONNECT_ACCEPT_ENCODING, GRPC_ACCEPT_ENCODING);
+            body
+        } else {
+            // unary responses are sent uncompressed
+            headers.remove(GRPC_ACCEPT_ENCODING);
+            let (body, encoding) = if parts.method == Method::GET {
+                let query = parts.uri.query().unwrap_or_default();
+                let message = get_message(query);
+                let encoding = query_param(query, "compression")
+                    .and_then(|name| HeaderValue::from_bytes(&name).ok());
+                parts.method = Method::POST;
+                parts.uri = parts.uri.path().parse().unwrap();
+                let body = StreamBody::new(message_body(message)).boxed_unsync();
+                (body, encoding)
+            } else {
+                (body, headers.remove(CONTENT_ENCODING))
+            };
+            let encoding = encoding.filter(|encoding| encoding != "identity");
+            let compressed = encoding.is_some();
+            if let Some(encoding) = encoding {
+                headers.insert(GRPC_ENCODING, encoding);
+            }
+            StreamBody::new(frame_body(body, compressed)).boxed_unsync()
+        };
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        headers.insert(TE, HeaderValue::from_static("trailers"));
+        headers.remove(CONTENT_LENGTH);
+        Request::from_parts(parts, body)
+    }
+
+    /// Streaming responses end with the status and trailers in an
+    /// end-of-stream message. Unary responses are left as gRPC until
+    /// [`Self::unary_response`] completes them.
+    pub fn modify_response<B>(&self, res: Response<B>) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
+        if !self.streaming {
+            return GrpcKindPlain.modify_response(res);
+        }
+        let (mut parts, mut body) = res.into_parts();
+        // the headers carry the status of Trailers-Only responses
+        let mut trailers = HeaderMap::new();
+        for name in [GRPC_STATUS, GRPC_MESSAGE] {
+            if let Some(value) = parts.headers.remove(name) {
+                trailers.insert(name, value);
+            }
+        }
+        rename_header(&mut parts.headers, GRPC_ENCODING, CONNECT_CONTENT_ENCODING);
+        rename_header(
+            &mut parts.headers,
+            GRPC_ACCEPT_ENCODING,
+            CONNECT_ACCEPT_ENCODING,
+        );
+        parts.headers.insert(CONTENT_TYPE, self.content_type());
+
+        let stream = try_stream! {
+            while let Some(frame) = body.frame().await {
+                match frame?.into_trailers() {
+                    Ok(received) => {
+                        trailers = received;
+                        break;
+                    }
+                    Err(frame) => yield frame,
+                }
+            }
+            yield Frame::data(end_stream_message(&trailers));
+        };
+        let boxed: DynStream = Box::pin(stream);
+        Response::from_parts(parts, StreamBody::new(boxed))
+    }
+
+    /// The message of the completed unary call, or its status as a Connect
+    /// error. The trailers are sent as headers prefixed with `trailer-`.
+    pub async fn unary_response(&self, res: StreamResponse) -> StreamResponse {
+        let (parts, body) = res.into_parts();
+        let collected = match Limited::new(body, MAX_BODY_BYTES).collect().await {
+            Ok(collected) => collected,
+            Err(err) => {
+                let status = Status::new(
+                    Code::Internal,
+                    format!("Failed to read the response: {}", err),
+                );
+                return self.status_response(&status);
+            }
+        };
+        let trailers = collected.trailers().cloned().unwrap_or_default();
+        let status = Status::from_headers(&trailers)
+            .or_else(|| Status::from_headers(&parts.headers))
+            .unwrap_or_else(|| Status::new(Code::Internal, "Missing grpc-status"));
+        if status.code() != Code::Ok {
+            return error_response(&status, &parts.headers, &trailers);
+        }
+
+        let data = collected.to_bytes();
+        let message = match single_message(&data) {
+            Ok(message) => Bytes::copy_from_slice(message),
+            Err(err) => {
+                let status = Status::new(
+                    Code::Internal,
+                    format!("Failed to read the response: {}", err),
+                );
+                return self.status_response(&status);
+            }
+        };
+        let mut res = from_full_bytes(Full::new(message));
+        add_metadata(res.headers_mut(), &parts.headers, &trailers);
+        res.headers_mut().insert(CONTENT_TYPE, self.content_type());
+        res
+    }
+}
+
+/// A unary error: the HTTP status of its code and a JSON body
+fn error_response(status: &Status, headers: &HeaderMap, trailers: &HeaderMap) -> StreamResponse {
+    let body = error_json(status).to_string();
+    let mut res = from_full_bytes(Full::new(Bytes::from(body)));
+    *res.status_mut() = match status.code() {
+        Code::Ok => StatusCode::INTERNAL_SERVER_ERROR,
+        code => code.http_status(),
+    };
+    add_metadata(res.headers_mut(), headers, trailers);
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    res
+}
+
+fn error_json(status: &Status) -> Value {
+    let code = match status.code() {
+        Code::Cancelled => "canceled".to_string(),
+        code => code.name().to_lowercase(),
+    };
+    json!({ "code": code, "message": status.message() })
+}
+
+/// The end-of-stream message of the gRPC `trailers`
+fn end_stream_message(trailers: &HeaderMap) -> Bytes {
+    let status = Status::from_headers(trailers)
+        .unwrap_or_else(|| Status::new(Code::Internal, "Missing grpc-status"));
+    let mut metadata = Map::new();
+    for name in trailers.keys().filter(|name| is_metadata(name)) {
+        let values = trailers
+            .get_all(name)
+            .iter()
+            .map(|value| Value::from(String::from_utf8_lossy(value.as_bytes())))
+            .collect();
+        metadata.insert(name.to_string(), Value::Array(values));
+    }
+    let mut end = json!({ "metadata": metadata });
+    if status.code() != Code::Ok {
+        end["error"] = error_json(&status);
+    }
+    let end = end.to_string();
+
+    let mut message = BytesMut::with_capacity(5 + end.len());
+    message.put_u8(END_STREAM_FLAG);
+    message.put_u32(end.len() as u32);
+    message.put_slice(end.as_bytes());
+    message.freeze()
+}
+
+/// Copies the metadata of a unary call, its trailers prefixed with `trailer-`
+fn add_metadata(to: &mut HeaderMap, headers: &HeaderMap, trailers: &HeaderMap) {
+    for (name, value) in headers.iter().filter(|(name, _)| is_metadata(name)) {
+        to.append(name, value.clone());
+    }
+    for (name, value) in trailers.iter().filter(|(name, _)| is_metadata(name)) {
+        if let Ok(name) = HeaderName::from_bytes(format!("trailer-{}", name).as_bytes()) {
+            to.append(name, value.clone());
+        }
+    }
+}
+
+/// Whether a header of the upstream response is metadata of the call rather
+/// than a part of the gRPC protocol
+fn is_metadata(name: &HeaderName) -> bool {
+    name != CONTENT_TYPE && name != CONTENT_LENGTH && !name.as_str().starts_with("grpc-")
+}
+
+fn rename_header(headers: &mut HeaderMap, from: HeaderName, to: HeaderName) {
+    if let Some(value) = headers.remove(from) {
+        headers.insert(to, value);
+    }
+}
+
+/// `connect-timeout-ms`, at most 10 digits
+fn parse_timeout(value: HeaderValue) -> Option<Duration> {
+    let value = value.to_str().ok()?;
+    if value.is_empty() || value.len() > 10 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(Duration::from_millis(value.parse().ok()?))
+}
+
+/// The decoded value of the first `name` parameter of a query
+fn query_param(query: &str, name: &str) -> Option<Vec<u8>> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| percent_decode_str(&value.replace('+', " ")).collect())
+    })
+}
+
+/// The message of a GET request, empty when the query has none
+fn get_message(query: &str) -> Result<Bytes, BoxError> {
+    let message = query_param(query, "message").unwrap_or_default();
+    if query_param(query, "base64").is_some_and(|base64| base64 == b"1") {
+        Ok(BASE64_URL.decode(message)?.into())
+    } else {
+        Ok(message.into())
+    }
+}
+
+fn message_body(
+    message: Result<Bytes, BoxError>,
+) -> impl Stream<Item = Result<Frame<Bytes>, BoxError>> {
+    try_stream! {
+        yield Frame::data(message?);
+    }
+}
+
+/// Frames the whole body of a unary request as the single message of the call
+fn frame_body(
+    mut body: UpstreamBody,
+    compressed: bool,
+) -> impl Stream<Item = Result<Frame<Bytes>, BoxError>> {
+    try_stream! {
+        let mut message = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame?.into_data() {
+                message.extend_from_slice(&data);
+                if message.len() > MAX_BODY_BYTES {
+                    Err(format!("Request exceeds {} bytes", MAX_BODY_BYTES))?;
+                }
+            }
+        }
+        let mut framed = BytesMut::with_capacity(5 + message.len());
+        framed.put_u8(compressed as u8);
+        framed.put_u32(message.len() as u32);
+        framed.put_slice(&message);
+        yield Frame::data(framed.freeze());
+    }
+}
@@ -0,0 +1,146 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{HeaderValue, Method, Request, header};
+use http_body_util::{BodyExt, Full, Limited};
+use tower::BoxError;
+
+use crate::{
+    compression::encoding::{GRPC_ACCEPT_ENCODING, GRPC_ENCODING},
+    core::{
+        proxy_error::ProxyError,
+        status::{Code, Status},
+        stream_response::StreamResponse,
+    },
+    telemetry::metrics::from_full_bytes,
+    transcoding::json_transcoder::JsonCall,
+};
+
+/// Largest JSON request or gRPC response body buffered by the proxy
+const MAX_BODY_BYTES: usize = 4 << 20;
+
+/// REST clients send JSON over plain HTTP, mapped onto a unary gRPC call by
+/// the `google.api.http` rule of its method. The gRPC status becomes the
+/// HTTP status, so the response is only sent once the call has completed.
+pub struct GrpcKindJson<'a> {
+    call: JsonCall<'a>,
+}
+
+impl<'a> GrpcKindJson<'a> {
+    pub fn new(call: JsonCall<'a>) -> Self {
+        Self { call }
+    }
+
+    /// A JSON error with the HTTP status matching `status`
+    pub fn status_response(&self, status: &Status) -> StreamResponse {
+        let body = serde_json::json!({
+            "code": status.code() as i32,
+            "message": status.message(),
+            "details": [],
+        });
+        json_response(status.code(), body.to_string().into())
+    }
+
+    /// The REST request as a plain gRPC call
+    pub async fn decode_request<B>(
+        &self,
+        req: Request<B>,
+    ) -> Result<Request<Full<Bytes>>, ProxyError>
+    where
+        B: hyper::body::Body<Data = Bytes>,
+        B::Error: Into<BoxError>,
+    {
+        let (mut parts, body) = req.into_parts();
+        let body = Limited::new(body, MAX_BODY_BYTES)
+            .collect()
+            .await
+            .map_err(ProxyError::InvalidJson)?
+            .to_bytes();
+        let message = self
+            .call
+            .request_message(parts.uri.query(), &body)
+            .map_err(ProxyError::InvalidJson)?;
+
+        let mut framed = BytesMut::with_capacity(5 + message.len());
+        framed.put_u8(0);
+        framed.put_u32(message.len() as u32);
+        framed.put_slice(&message);
+
+        parts.method = Method::POST;
+        parts.uri = self
+            .call
+            .grpc_path()
+            .parse()
+            .map_err(|err| ProxyError::Protocol(Box::new(err)))?;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        // the response message is read by the proxy, uncompressed
+        parts.headers.remove(GRPC_ENCODING);
+        parts.headers.remove(GRPC_ACCEPT_ENCODING);
+        parts.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc"),
+        );
+        parts
+            .headers
+            .insert(header::TE, HeaderValue::from_static("trailers"));
+        Ok(Request::from_parts(parts, Full::new(framed.freeze())))
+    }
+
+    /// The response message of the completed call as JSON,
+    /// or its status as a JSON error
+    pub async fn respond(&self, res: StreamResponse) -> StreamResponse {
+        let (parts, body) = res.into_parts();
+        let collected = match Limited::new(body, MAX_BODY_BYTES).collect().await {
+            Ok(collected) => collected,
+            Err(err) => {
+                let status = Status::new(
+                    Code::Internal,
+                    format!("Failed to read the response: {}", err),
+                );
+                return self.status_response(&status);
+            }
+        };
+        // a Trailers-Only response carries the status in its headers
+        let status = collected
+            .trailers()
+            .and_then(Status::from_headers)
+            .or_else(|| Status::from_headers(&parts.headers))
+            .unwrap_or_else(|| Status::new(Code::Internal, "Missing grpc-status"));
+        if status.code() != Code::Ok {
+            return self.status_response(&status);
+        }
+
+        let data = collected.to_bytes();
+        match single_message(&data).and_then(|message| self.call.response_json(message)) {
+            Ok(json) => json_response(Code::Ok, json.into()),
+            Err(err) => {
+                let status = Status::new(
+                    Code::Internal,
+                    format!("Failed to transcode the response: {}", err),
+                );
+                self.status_response(&status)
+            }
+        }
+    }
+}
+
+/// The message of a unary response body, decompressed by the cluster policy
+pub fn single_message(data: &[u8]) -> Result<&[u8], BoxError> {
+    let prefix = data.get(..5).ok_or("Missing response message")?;
+    if prefix[0] != 0 {
+        return Err("Compressed response message".into());
+    }
+    let len = u32::from_be_bytes(prefix[1..5].try_into().unwrap()) as usize;
+    match data.get(5..) {
+        Some(message) if message.len() == len => Ok(message),
+        _ => Err("Expected a single response message".into()),
+    }
+}
+
+fn json_response(code: Code, body: Bytes) -> StreamResponse {
+    let mut res = from_full_bytes(Full::new(body));
+    *res.status_mut() = code.http_status();
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    res
+}
@@ -1,20 +1,35 @@
 use async_stream::try_stream;
+use bytes::Bytes;
 use http::Response;
+use http_body::Frame;
 use http_body_util::{BodyExt, StreamBody};
-use hyper::body::Incoming;
 
-use crate::core::stream_response::StreamResponse;
+use crate::core::{
+    status::Status,
+    stream_response::{DynStream, StreamResponse},
+};
+#[derive(Clone, Copy)]
 pub struct GrpcKindPlain;
 
 impl GrpcKindPlain {
-    pub fn modify_response(&self, res: Response<Incoming>) -> StreamResponse {
+    /// gRPC carries the status in HTTP/2 trailers
+    pub fn status_frame(&self, status: &Status) -> Frame<Bytes> {
+        Frame::trailers(status.to_header_map())
+    }
+
+    pub fn modify_response<B>(&self, res: Response<B>) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
+        // the headers carry the status of Trailers-Only responses
+        let (parts, mut incoming) = res.into_parts();
         let forward_stream = try_stream! {
-                let mut incoming = res.into_body();
             while let Some(frame) = incoming.frame().await {
                 let frame = frame?;
                 yield frame;
             }
         };
-        StreamResponse::new(StreamBody::new(Box::pin(forward_stream)))
+        let boxed: DynStream = Box::pin(forward_stream);
+        Response::from_parts(parts, StreamBody::new(boxed))
     }
 }
@@ -1,15 +1,24 @@
 use async_stream::try_stream;
+use bytes::Bytes;
 use http::{HeaderValue, Request, Response};
 use http_body::Frame;
 use http_body_util::{BodyExt, StreamBody};
-use hyper::body::Incoming;
 
 use crate::{
-    core::stream_response::{DynStream, StreamResponse},
+    core::{
+        status::Status,
+        stream_response::{DynStream, StreamResponse},
+    },
     trailers::Trailers,
 };
+#[derive(Clone, Copy)]
 pub struct GrpcKindWeb;
 impl GrpcKindWeb {
+    /// grpc-web carries the status in a trailers frame at the end of the body
+    pub fn status_frame(&self, status: &Status) -> Frame<Bytes> {
+        Frame::data(Trailers::new(status.to_header_map()).into_to_frame())
+    }
+
     pub fn modify_request<B>(&self, req: &mut Request<B>)
     where
         B: hyper::body::Body,
@@ -21,7 +30,10 @@ impl GrpcKindWeb {
         req.headers_mut().remove(hyper::header::CONTENT_LENGTH);
     }
 
-    pub fn modify_response(&self, res: Response<Incoming>) -> StreamResponse {
+    pub fn modify_response<B>(&self, res: Response<B>) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
         let (parts, mut body) = res.into_parts();
 
         let forward_stream = try_stream! {
@@ -0,0 +1,71 @@
+use bytes::Bytes;
+use http::{HeaderValue, Request, header::CONTENT_TYPE};
+use prost_reflect::MethodDescriptor;
+use tower::BoxError;
+
+use crate::{
+    compression::encoding::{GRPC_ACCEPT_ENCODING, GRPC_ENCODING},
+    core::{
+        grpc_kind::GrpcKind,
+        grpc_kind_web::GrpcKindWeb,
+        status::Status,
+        stream_response::{StreamResponse, UpstreamBody},
+    },
+    transcoding::message_converter::{json_to_proto_request, proto_to_json_response},
+};
+
+/// grpc-web with canonical proto3 JSON messages instead of protobuf, which
+/// some JS clients use for debuggability. The messages are converted with
+/// the descriptor of the method, the framing is that of [`GrpcKindWeb`].
+pub struct GrpcKindWebJson {
+    method: MethodDescriptor,
+    max_message_size: usize,
+}
+
+impl GrpcKindWebJson {
+    pub fn new(method: MethodDescriptor, max_message_size: usize) -> Self {
+        Self {
+            method,
+            max_message_size,
+        }
+    }
+
+    /// A response made only of `status`, for calls failed by the proxy,
+    /// possibly before the method is known
+    pub fn status_response(status: &Status) -> StreamResponse {
+        let mut res = GrpcKind::Web(GrpcKindWeb).status_response(status);
+        res.headers_mut().insert(CONTENT_TYPE, content_type());
+        res
+    }
+
+    /// The request as grpc-web with protobuf messages
+    pub fn encode_request<B>(&self, req: Request<B>) -> Request<UpstreamBody>
+    where
+        B: hyper::body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let mut req = json_to_proto_request(req, self.method.input(), self.max_message_size);
+        // the messages are converted, so they must travel uncompressed
+        req.headers_mut().remove(GRPC_ENCODING);
+        req.headers_mut().remove(GRPC_ACCEPT_ENCODING);
+        req
+    }
+
+    /// Converts the messages of a grpc-web response to JSON, ending the call
+    /// with INTERNAL if one does not fit the response message
+    pub fn respond(&self, res: StreamResponse) -> StreamResponse {
+        let kind = GrpcKind::Web(GrpcKindWeb);
+        let mut res =
+            proto_to_json_response(res, self.method.output(), kind, self.max_message_size);
+        res.headers_mut().insert(CONTENT_TYPE, content_type());
+        res
+    }
+}
+
+pub fn is_web_json(content_type: &HeaderValue) -> bool {
+    content_type == "application/grpc-web+json"
+}
+
+fn content_type() -> HeaderValue {
+    HeaderValue::from_static("application/grpc-web+json")
+}
@@ -0,0 +1,111 @@
+use async_stream::try_stream;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::Stream;
+use http::{HeaderValue, Request, Response};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use tower::BoxError;
+
+use crate::core::{
+    grpc_kind_web::GrpcKindWeb,
+    status::Status,
+    stream_response::{DynStream, StreamResponse, UpstreamBody},
+};
+
+/// grpc-web-text carries the grpc-web framing encoded as base64,
+/// which is what browsers fall back to when streaming over XHR.
+#[derive(Clone, Copy)]
+pub struct GrpcKindWebText;
+impl GrpcKindWebText {
+    pub fn status_frame(&self, status: &Status) -> Frame<Bytes> {
+        match GrpcKindWeb.status_frame(status).into_data() {
+            Ok(data) => Frame::data(Bytes::from(STANDARD.encode(data))),
+            Err(frame) => frame,
+        }
+    }
+
+    pub fn message_frame(&self, message: Bytes) -> Frame<Bytes> {
+        Frame::data(Bytes::from(STANDARD.encode(message)))
+    }
+
+    pub fn modify_request<B>(&self, req: Request<B>) -> Request<UpstreamBody>
+    where
+        B: hyper::body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let mut req = req.map(|body| {
+            let body = body.map_err(Into::into).boxed_unsync();
+            StreamBody::new(decode_body(body)).boxed_unsync()
+        });
+        GrpcKindWeb.modify_request(&mut req);
+        req
+    }
+
+    pub fn modify_response<B>(&self, res: Response<B>) -> StreamResponse
+    where
+        B: hyper::body::Body<Data = Bytes, Error = hyper::Error> + Send + Unpin + 'static,
+    {
+        let (mut parts, mut body) = GrpcKindWeb.modify_response(res).into_parts();
+
+        let forward_stream = try_stream! {
+            while let Some(frame) = body.frame().await {
+                // data frames, including the trailers frame built by
+                // GrpcKindWeb, are encoded one by one with their own padding
+                match frame?.into_data() {
+                    Ok(data) => yield Frame::data(Bytes::from(STANDARD.encode(data))),
+                    Err(frame) => yield frame,
+                }
+            }
+        };
+
+        let boxed: DynStream = Box::pin(forward_stream);
+        parts.headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc-web-text+proto"),
+        );
+        Response::from_parts(parts, StreamBody::new(boxed))
+    }
+}
+
+/// Decode a base64 request body as it arrives.
+///
+/// Only complete 4-byte groups are decoded, the remainder is kept until the
+/// next chunk so that a group split across chunks is still decoded correctly.
+/// Clients may also concatenate separately padded segments, so decoding is
+/// restarted after every group that ends with padding.
+fn decode_body(mut body: UpstreamBody) -> impl Stream<Item = Result<Frame<Bytes>, BoxError>> {
+    try_stream! {
+        let mut buf = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            let frame = frame?;
+            match frame.into_data() {
+                Ok(data) => {
+                    buf.extend(data.iter().filter(|b| !b.is_ascii_whitespace()));
+                    let decoded = decode_groups(&mut buf)?;
+                    if !decoded.is_empty() {
+                        yield Frame::data(decoded);
+                    }
+                }
+                Err(frame) => yield frame,
+            }
+        }
+        if !buf.is_empty() {
+            Err("Truncated base64 request body")?;
+        }
+    }
+}
+
+fn decode_groups(buf: &mut BytesMut) -> Result<Bytes, BoxError> {
+    let complete = buf.len() - buf.len() % 4;
+    let mut decoded = Vec::with_capacity(complete / 4 * 3);
+    let mut start = 0;
+    for end in (4..=complete).step_by(4) {
+        if buf[end - 1] == b'=' || end == complete {
+            STANDARD.decode_vec(&buf[start..end], &mut decoded)?;
+            start = end;
+        }
+    }
+    buf.advance(complete);
+    Ok(Bytes::from(decoded))
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use tokio::time::Instant;
+
+pub const GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+
+/// Largest value of a `grpc-timeout` header, 8 digits at most
+const MAX_VALUE: u64 = 99_999_999;
+
+/// The duration of a `grpc-timeout` header: up to 8 digits followed by
+/// `H`, `M`, `S`, `m`, `u` or `n` for hours down to nanoseconds
+pub fn parse_grpc_timeout(value: &HeaderValue) -> Option<Duration> {
+    let value = value.to_str().ok()?;
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// A `grpc-timeout` header value in the finest unit that fits in 8 digits,
+/// rounded down so that the deadline is never extended
+pub fn encode_grpc_timeout(timeout: Duration) -> HeaderValue {
+    let nanos = timeout.as_nanos();
+    let units: [(u128, &str); 6] = [
+        (1, "n"),
+        (1_000, "u"),
+        (1_000_000, "m"),
+        (1_000_000_000, "S"),
+        (60_000_000_000, "M"),
+        (3_600_000_000_000, "H"),
+    ];
+    let (amount, unit) = units
+        .iter()
+        .map(|(scale, unit)| (nanos / scale, *unit))
+        .find(|(amount, _)| *amount <= MAX_VALUE as u128)
+        .unwrap_or((MAX_VALUE as u128, "H"));
+    HeaderValue::from_str(&format!("{}{}", amount, unit)).unwrap()
+}
+
+/// When a call received now with `headers` must have completed
+pub fn grpc_deadline(headers: &HeaderMap) -> Option<Instant> {
+    let timeout = parse_grpc_timeout(headers.get(GRPC_TIMEOUT)?)?;
+    Some(Instant::now() + timeout)
+}
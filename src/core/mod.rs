@@ -1,4 +1,12 @@
 pub mod grpc_kind;
+pub mod grpc_kind_connect;
+pub mod grpc_kind_json;
 pub mod grpc_kind_plain;
 pub mod grpc_kind_web;
+pub mod grpc_kind_web_json;
+pub mod grpc_kind_web_text;
+pub mod grpc_timeout;
+pub mod peer_addr;
+pub mod proxy_error;
+pub mod status;
 pub mod stream_response;
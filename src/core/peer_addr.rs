@@ -0,0 +1,6 @@
+use std::net::SocketAddr;
+
+/// Address of the client connection a request came from,
+/// stored in the request extensions
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(pub SocketAddr);
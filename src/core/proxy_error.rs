@@ -0,0 +1,93 @@
+use std::fmt;
+
+use http::{HeaderValue, StatusCode};
+use tower::BoxError;
+
+use crate::core::status::{Code, Status};
+
+/// Failures of the proxy itself, as opposed to errors returned by the
+/// upstream server which are forwarded untouched.
+#[derive(Debug)]
+pub enum ProxyError {
+    MissingContentType,
+    UnsupportedContentType(HeaderValue),
+    /// The `grpc-encoding` of the request is not one the proxy decodes
+    UnsupportedEncoding(String),
+    /// No route matches the path and headers of the call
+    NoRoute(String),
+    /// The JSON request cannot be mapped onto the request message
+    InvalidJson(BoxError),
+    /// The descriptor of the method is needed but not in the descriptor set
+    UnknownMethod(String),
+    InvalidAuthority(BoxError),
+    /// The upstream server could not be reached, or refused the HTTP/2 handshake
+    Connect(BoxError),
+    /// The upstream stream failed before the response headers were received
+    Upstream(BoxError),
+    /// The upstream server answered with something else than a gRPC response
+    UpstreamHttpStatus(StatusCode),
+    /// The request or response violates the gRPC protocol
+    Protocol(BoxError),
+    Timeout,
+    /// A circuit breaker limit of the cluster was reached
+    CircuitOpen(&'static str),
+    /// A rate limit of the route was reached
+    RateLimited,
+    /// Too many calls are in flight, the call was shed
+    Overloaded,
+}
+
+impl ProxyError {
+    pub fn code(&self) -> Code {
+        match self {
+            ProxyError::MissingContentType
+            | ProxyError::UnsupportedContentType(_)
+            | ProxyError::UnsupportedEncoding(_)
+            | ProxyError::NoRoute(_)
+            | ProxyError::UnknownMethod(_) => Code::Unimplemented,
+            ProxyError::Connect(_)
+            | ProxyError::Upstream(_)
+            | ProxyError::CircuitOpen(_)
+            | ProxyError::Overloaded => Code::Unavailable,
+            ProxyError::UpstreamHttpStatus(status) => Code::from_http_status(*status),
+            ProxyError::InvalidJson(_) => Code::InvalidArgument,
+            ProxyError::InvalidAuthority(_) | ProxyError::Protocol(_) => Code::Internal,
+            ProxyError::Timeout => Code::DeadlineExceeded,
+            ProxyError::RateLimited => Code::ResourceExhausted,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        Status::new(self.code(), self.to_string())
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyError::MissingContentType => write!(f, "Missing Content-Type header"),
+            ProxyError::UnsupportedContentType(content_type) => {
+                write!(f, "Unsupported Content-Type header: {:?}", content_type)
+            }
+            ProxyError::UnsupportedEncoding(encoding) => {
+                write!(f, "Unsupported grpc-encoding: {:?}", encoding)
+            }
+            ProxyError::NoRoute(path) => write!(f, "No route for {}", path),
+            ProxyError::InvalidJson(err) => write!(f, "Invalid JSON request: {}", err),
+            ProxyError::UnknownMethod(path) => write!(f, "No descriptor for {}", path),
+            ProxyError::InvalidAuthority(err) => write!(f, "Invalid upstream authority: {}", err),
+            ProxyError::Connect(err) => write!(f, "Upstream connection failed: {}", err),
+            ProxyError::Upstream(err) => write!(f, "Upstream request failed: {}", err),
+            ProxyError::UpstreamHttpStatus(status) => {
+                write!(f, "Upstream returned HTTP status {}", status)
+            }
+            ProxyError::Protocol(err) => write!(f, "gRPC protocol violation: {}", err),
+            ProxyError::Timeout => write!(f, "Deadline exceeded"),
+            ProxyError::CircuitOpen(limit) => write!(f, "Circuit breaker open: {} reached", limit),
+            ProxyError::RateLimited => write!(f, "Rate limit exceeded"),
+            ProxyError::Overloaded => write!(f, "Too many calls in flight"),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
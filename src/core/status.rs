@@ -0,0 +1,221 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use http_body_util::Full;
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+
+use crate::{core::stream_response::StreamResponse, telemetry::metrics::from_full_bytes};
+
+pub const GRPC_STATUS: &str = "grpc-status";
+pub const GRPC_MESSAGE: &str = "grpc-message";
+
+// https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md#responses
+// grpc-message is percent-encoded outside of printable ASCII
+const GRPC_MESSAGE_ENCODE_SET: &AsciiSet = &CONTROLS.add(b'%');
+
+/// gRPC status codes
+///
+/// <https://github.com/grpc/grpc/blob/master/doc/statuscodes.md>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    Ok = 0,
+    Cancelled = 1,
+    Unknown = 2,
+    InvalidArgument = 3,
+    DeadlineExceeded = 4,
+    NotFound = 5,
+    AlreadyExists = 6,
+    PermissionDenied = 7,
+    ResourceExhausted = 8,
+    FailedPrecondition = 9,
+    Aborted = 10,
+    OutOfRange = 11,
+    Unimplemented = 12,
+    Internal = 13,
+    Unavailable = 14,
+    DataLoss = 15,
+    Unauthenticated = 16,
+}
+
+impl Code {
+    pub fn from_i32(code: i32) -> Code {
+        match code {
+            0 => Code::Ok,
+            1 => Code::Cancelled,
+            2 => Code::Unknown,
+            3 => Code::InvalidArgument,
+            4 => Code::DeadlineExceeded,
+            5 => Code::NotFound,
+            6 => Code::AlreadyExists,
+            7 => Code::PermissionDenied,
+            8 => Code::ResourceExhausted,
+            9 => Code::FailedPrecondition,
+            10 => Code::Aborted,
+            11 => Code::OutOfRange,
+            12 => Code::Unimplemented,
+            13 => Code::Internal,
+            14 => Code::Unavailable,
+            15 => Code::DataLoss,
+            16 => Code::Unauthenticated,
+            _ => Code::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Code::Ok => "OK",
+            Code::Cancelled => "CANCELLED",
+            Code::Unknown => "UNKNOWN",
+            Code::InvalidArgument => "INVALID_ARGUMENT",
+            Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Code::NotFound => "NOT_FOUND",
+            Code::AlreadyExists => "ALREADY_EXISTS",
+            Code::PermissionDenied => "PERMISSION_DENIED",
+            Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Code::FailedPrecondition => "FAILED_PRECONDITION",
+            Code::Aborted => "ABORTED",
+            Code::OutOfRange => "OUT_OF_RANGE",
+            Code::Unimplemented => "UNIMPLEMENTED",
+            Code::Internal => "INTERNAL",
+            Code::Unavailable => "UNAVAILABLE",
+            Code::DataLoss => "DATA_LOSS",
+            Code::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
+
+    /// Reads the `grpc-status` header of a response or its trailers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Code> {
+        let code = headers.get(GRPC_STATUS)?.to_str().ok()?.parse().ok()?;
+        Some(Code::from_i32(code))
+    }
+
+    /// The HTTP status answering a call failed with this code, for clients
+    /// that do not speak gRPC
+    ///
+    /// <https://github.com/googleapis/googleapis/blob/master/google/rpc/code.proto>
+    pub fn http_status(&self) -> StatusCode {
+        match self {
+            Code::Ok => StatusCode::OK,
+            Code::Cancelled => StatusCode::from_u16(499).unwrap(),
+            Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+            Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+                StatusCode::BAD_REQUEST
+            }
+            Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            Code::NotFound => StatusCode::NOT_FOUND,
+            Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+            Code::PermissionDenied => StatusCode::FORBIDDEN,
+            Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+            Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+            Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Maps the HTTP status of a response that is not a gRPC response
+    ///
+    /// <https://github.com/grpc/grpc/blob/master/doc/http-grpc-status-mapping.md>
+    pub fn from_http_status(status: StatusCode) -> Code {
+        match status {
+            StatusCode::BAD_REQUEST => Code::Internal,
+            StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+            StatusCode::FORBIDDEN => Code::PermissionDenied,
+            StatusCode::NOT_FOUND => Code::Unimplemented,
+            StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => Code::Unavailable,
+            _ => Code::Unknown,
+        }
+    }
+}
+
+/// Parses the upper case names of the gRPC service config, such as `UNAVAILABLE`
+impl FromStr for Code {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        (0..=16)
+            .map(Code::from_i32)
+            .find(|code| code.name() == name)
+            .ok_or_else(|| format!("Unknown status code {:?}", name))
+    }
+}
+
+impl From<Code> for HeaderValue {
+    fn from(code: Code) -> Self {
+        HeaderValue::from(code as i32)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Status {
+    code: Code,
+    message: String,
+}
+
+impl Status {
+    pub fn new(code: Code, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Reads `grpc-status` and `grpc-message` from a header map
+    pub fn from_headers(headers: &HeaderMap) -> Option<Status> {
+        let code = Code::from_headers(headers)?;
+        let message = headers
+            .get(GRPC_MESSAGE)
+            .and_then(|message| message.to_str().ok())
+            .map(|message| percent_decode_str(message).decode_utf8_lossy().into_owned())
+            .unwrap_or_default();
+        Some(Status::new(code, message))
+    }
+
+    /// `grpc-status` and `grpc-message` as headers or trailers
+    pub fn to_header_map(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        self.add_header(&mut headers);
+        headers
+    }
+
+    pub fn add_header(&self, headers: &mut HeaderMap) {
+        headers.insert(GRPC_STATUS, self.code.into());
+        if !self.message.is_empty() {
+            let message = utf8_percent_encode(&self.message, GRPC_MESSAGE_ENCODE_SET).to_string();
+            // percent-encoding leaves only visible ASCII
+            headers.insert(GRPC_MESSAGE, HeaderValue::from_str(&message).unwrap());
+        }
+    }
+
+    /// A Trailers-Only response carrying the status in its headers,
+    /// understood by both gRPC and grpc-web clients.
+    pub fn trailers_only_response(&self) -> StreamResponse {
+        let mut res = from_full_bytes(Full::new(Bytes::new()));
+        *res.status_mut() = StatusCode::OK;
+        let headers = res.headers_mut();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc"),
+        );
+        self.add_header(headers);
+        res
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
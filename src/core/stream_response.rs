@@ -1,11 +1,77 @@
+use async_stream::try_stream;
 use bytes::Bytes;
 
 use futures_core::Stream;
-use http::Response;
+use http::{HeaderMap, Response};
 
 use http_body::Frame;
-use http_body_util::StreamBody;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, StreamBody};
 use std::pin::Pin;
+use tower::BoxError;
 
 pub type DynStream = Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, hyper::Error>> + Send>>;
 pub type StreamResponse = Response<StreamBody<DynStream>>;
+
+/// Request body sent to the upstream gRPC server once the downstream
+/// body has been translated to plain gRPC framing.
+pub type UpstreamBody = UnsyncBoxBody<Bytes, BoxError>;
+
+/// Keeps `guard` alive until the response body has been streamed to the end,
+/// or dropped by the client.
+pub fn hold_until_end<T>(res: StreamResponse, guard: T) -> StreamResponse
+where
+    T: Send + 'static,
+{
+    res.map(|mut body| {
+        let forward_stream = try_stream! {
+            let _guard = guard;
+            while let Some(frame) = body.frame().await {
+                yield frame?;
+            }
+        };
+        let boxed: DynStream = Box::pin(forward_stream);
+        StreamBody::new(boxed)
+    })
+}
+
+/// Calls `end` with the trailers of the response once its body has been
+/// streamed, `None` when it ended without any or with an error. It is not
+/// called when the body is dropped before its end.
+pub fn on_end<F>(res: StreamResponse, end: F) -> StreamResponse
+where
+    F: FnOnce(Option<&HeaderMap>) + Send + 'static,
+{
+    res.map(|mut body| {
+        let forward_stream = try_stream! {
+            let mut end = Some(end);
+            while let Some(frame) = body.frame().await {
+                let frame = frame.inspect_err(|_| {
+                    if let Some(end) = end.take() {
+                        end(None);
+                    }
+                })?;
+                if let Some(trailers) = frame.trailers_ref()
+                    && let Some(end) = end.take()
+                {
+                    end(Some(trailers));
+                }
+                yield frame;
+            }
+            if let Some(end) = end.take() {
+                end(None);
+            }
+        };
+        let boxed: DynStream = Box::pin(forward_stream);
+        StreamBody::new(boxed)
+    })
+}
+
+/// A response whose body is made of a single frame
+pub fn from_frame(frame: Frame<Bytes>) -> StreamResponse {
+    let forward_stream = try_stream! {
+        yield frame;
+    };
+    let boxed: DynStream = Box::pin(forward_stream);
+    Response::new(StreamBody::new(boxed))
+}
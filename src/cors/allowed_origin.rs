@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use regex::Regex;
+use tower::BoxError;
+
+const REGEX_PREFIX: &str = "regex:";
+
+#[derive(Debug, Clone)]
+pub enum AllowedOrigin {
+    Any,
+    Exact(String),
+    /// `https://*.example.com` is kept as prefix `https://`
+    /// and suffix `.example.com`
+    WildcardSubdomain {
+        prefix: String,
+        suffix: String,
+    },
+    Regex(Regex),
+}
+
+impl AllowedOrigin {
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            AllowedOrigin::Any => true,
+            AllowedOrigin::Exact(allowed) => allowed.eq_ignore_ascii_case(origin),
+            AllowedOrigin::WildcardSubdomain { prefix, suffix } => {
+                // without a scheme in the pattern, any scheme is accepted
+                let origin = match prefix.is_empty() {
+                    true => origin.split_once("://").map_or(origin, |(_, host)| host),
+                    false => origin,
+                };
+                origin
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+                    .is_some_and(|subdomain| {
+                        !subdomain.is_empty()
+                            && subdomain
+                                .bytes()
+                                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+                    })
+            }
+            AllowedOrigin::Regex(regex) => regex.is_match(origin),
+        }
+    }
+}
+
+impl FromStr for AllowedOrigin {
+    type Err = BoxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            Ok(AllowedOrigin::Any)
+        } else if let Some(pattern) = s.strip_prefix(REGEX_PREFIX) {
+            let regex = Regex::new(&format!("^(?:{})$", pattern))
+                .map_err(|e| format!("Invalid origin regex {:?}: {}", pattern, e))?;
+            Ok(AllowedOrigin::Regex(regex))
+        } else if let Some((prefix, suffix)) = s.split_once("*.") {
+            if suffix.is_empty() || suffix.contains('*') {
+                return Err(format!("Invalid wildcard origin {:?}", s).into());
+            }
+            Ok(AllowedOrigin::WildcardSubdomain {
+                prefix: prefix.to_ascii_lowercase(),
+                suffix: format!(".{}", suffix.to_ascii_lowercase()),
+            })
+        } else if s.contains('*') {
+            Err(format!("Invalid wildcard origin {:?}", s).into())
+        } else {
+            Ok(AllowedOrigin::Exact(s.trim_end_matches('/').to_string()))
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD, HOST, ORIGIN, VARY,
+    },
+    request::Parts,
+};
+use http_body_util::Full;
+use tower::BoxError;
+
+use crate::{
+    config::cors_config::CorsConfig, core::stream_response::StreamResponse,
+    cors::allowed_origin::AllowedOrigin, telemetry::metrics::from_full_bytes,
+    transcoding::json_transcoder::JsonTranscoder,
+};
+
+/// Headers every grpc-web or Connect client may send
+const DEFAULT_ALLOWED_HEADERS: [&str; 6] = [
+    "content-type",
+    "x-grpc-web",
+    "x-user-agent",
+    "grpc-timeout",
+    "connect-protocol-version",
+    "connect-timeout-ms",
+];
+/// Headers every grpc-web client needs to read the call status
+const DEFAULT_EXPOSED_HEADERS: [&str; 2] = ["grpc-status", "grpc-message"];
+/// Methods of grpc-web calls and Connect unary GET requests
+const DEFAULT_ALLOWED_METHODS: [Method; 3] = [Method::POST, Method::GET, Method::OPTIONS];
+
+pub struct CorsPolicy {
+    allowed_origins: Vec<AllowedOrigin>,
+    allowed_headers: HeaderValue,
+    allowed_methods: HeaderValue,
+    exposed_headers: HeaderValue,
+    allow_credentials: bool,
+    max_age: HeaderValue,
+}
+
+impl CorsPolicy {
+    /// The methods of the HTTP rules of `transcoder` are allowed as well
+    pub fn from_config(
+        config: &CorsConfig,
+        transcoder: Option<&JsonTranscoder>,
+    ) -> Result<Self, BoxError> {
+        let allowed_origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| AllowedOrigin::from_str(origin))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            allowed_origins,
+            allowed_headers: join_header_names(&DEFAULT_ALLOWED_HEADERS, &config.allowed_headers)?,
+            allowed_methods: join_methods(transcoder)?,
+            exposed_headers: join_header_names(&DEFAULT_EXPOSED_HEADERS, &config.exposed_headers)?,
+            allow_credentials: config.allow_credentials,
+            max_age: HeaderValue::from(config.max_age_secs),
+        })
+    }
+
+    pub fn is_preflight(method: &Method, headers: &HeaderMap) -> bool {
+        method == Method::OPTIONS
+            && headers.contains_key(ORIGIN)
+            && headers.contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    /// Whether the page sending `origin` was served by the host the request is
+    /// sent to, over the `scheme` of the listener
+    pub fn is_same_origin(origin: &HeaderValue, parts: &Parts, scheme: &str) -> bool {
+        let host = parts
+            .uri
+            .authority()
+            .map(|authority| authority.as_str())
+            .or_else(|| parts.headers.get(HOST)?.to_str().ok());
+        let origin = origin
+            .to_str()
+            .ok()
+            .and_then(|origin| origin.split_once("://"));
+        match (host, origin) {
+            (Some(host), Some((origin_scheme, origin_host))) => {
+                origin_scheme.eq_ignore_ascii_case(scheme) && origin_host.eq_ignore_ascii_case(host)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_allowed(&self, origin: &HeaderValue) -> bool {
+        origin.to_str().is_ok_and(|origin| {
+            self.allowed_origins
+                .iter()
+                .any(|allowed| allowed.matches(origin))
+        })
+    }
+
+    /// Answers a preflight request without reaching the upstream server
+    pub fn preflight_response(&self, origin: &HeaderValue) -> StreamResponse {
+        let mut res = empty_response(StatusCode::NO_CONTENT);
+        let headers = res.headers_mut();
+        self.insert_origin(headers, origin);
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, self.allowed_methods.clone());
+        headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, self.allowed_headers.clone());
+        headers.insert(ACCESS_CONTROL_MAX_AGE, self.max_age.clone());
+        res
+    }
+
+    pub fn forbidden_response() -> StreamResponse {
+        empty_response(StatusCode::FORBIDDEN)
+    }
+
+    /// Adds the CORS headers to the response of an allowed cross-origin request
+    pub fn apply(&self, origin: &HeaderValue, headers: &mut HeaderMap) {
+        self.insert_origin(headers, origin);
+        headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, self.exposed_headers.clone());
+    }
+
+    fn insert_origin(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        headers.append(VARY, HeaderValue::from_static("origin"));
+        if self.allow_credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+fn join_header_names(defaults: &[&str], extra: &[String]) -> Result<HeaderValue, BoxError> {
+    let mut names: Vec<String> = defaults.iter().map(|n| n.to_string()).collect();
+    for name in extra {
+        let name = HeaderName::from_str(name.trim())
+            .map_err(|e| format!("Invalid CORS header name {:?}: {}", name, e))?;
+        if !names.iter().any(|n| n == name.as_str()) {
+            names.push(name.as_str().to_string());
+        }
+    }
+    Ok(HeaderValue::from_str(&names.join(","))?)
+}
+
+fn join_methods(transcoder: Option<&JsonTranscoder>) -> Result<HeaderValue, BoxError> {
+    let mut methods = DEFAULT_ALLOWED_METHODS.to_vec();
+    for method in transcoder
+        .into_iter()
+        .flat_map(JsonTranscoder::http_methods)
+    {
+        if !methods.contains(method) {
+            methods.push(method.clone());
+        }
+    }
+    let names: Vec<&str> = methods.iter().map(Method::as_str).collect();
+    Ok(HeaderValue::from_str(&names.join(", "))?)
+}
+
+fn empty_response(status: StatusCode) -> StreamResponse {
+    let mut res = from_full_bytes(Full::new(Bytes::new()));
+    *res.status_mut() = status;
+    res
+}
@@ -0,0 +1,2 @@
+pub mod allowed_origin;
+pub mod cors_policy;
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::{
+    Request, StatusCode,
+    header::{CONTENT_TYPE, TE},
+};
+use http_body_util::{BodyExt, Full};
+use tokio::task::JoinHandle;
+use tower::BoxError;
+
+use crate::{
+    config::health_check_config::HealthCheckConfig,
+    context::ProxyContext,
+    core::status::{Code, Status},
+    health::health_message::{CHECK_PATH, ServingStatus, decode_response, encode_request},
+    routing::route_table::RouteTable,
+    upstream::{cluster::Cluster, endpoint::Endpoint},
+};
+
+/// Probes one endpoint with `grpc.health.v1.Health/Check` and takes it in or
+/// out of load-balancing rotation after enough consecutive results.
+pub struct HealthChecker {
+    ctx: Arc<ProxyContext>,
+    cluster: Arc<Cluster>,
+    endpoint: Endpoint,
+    config: HealthCheckConfig,
+}
+
+impl HealthChecker {
+    /// One task per endpoint of the clusters with health checks,
+    /// running until aborted
+    pub fn spawn_all(ctx: &Arc<ProxyContext>, routes: &RouteTable) -> Vec<JoinHandle<()>> {
+        routes
+            .clusters()
+            .iter()
+            .flat_map(|cluster| {
+                let config = cluster.health_check.clone();
+                cluster.endpoints.iter().filter_map(move |endpoint| {
+                    let checker = HealthChecker {
+                        ctx: ctx.clone(),
+                        cluster: cluster.clone(),
+                        endpoint: endpoint.clone(),
+                        config: config.clone()?,
+                    };
+                    Some(tokio::spawn(checker.run()))
+                })
+            })
+            .collect()
+    }
+
+    async fn run(self) {
+        let gauge = self
+            .ctx
+            .metrics
+            .endpoint_healthy
+            .with_label_values(&[&self.cluster.name, self.endpoint.authority.as_str()]);
+        gauge.set(self.endpoint.is_healthy() as i64);
+
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let mut interval = tokio::time::interval(Duration::from_millis(self.config.interval_ms));
+        let (mut successes, mut failures) = (0, 0);
+        loop {
+            interval.tick().await;
+            let result = match tokio::time::timeout(timeout, self.check()).await {
+                Ok(Ok(ServingStatus::Serving)) => Ok(()),
+                Ok(Ok(status)) => Err(format!("{:?}", status).into()),
+                Ok(Err(err)) => Err(err),
+                Err(_) => Err(BoxError::from("Health check timed out")),
+            };
+            match result {
+                Ok(()) => {
+                    successes += 1;
+                    failures = 0;
+                    if !self.endpoint.is_healthy() && successes >= self.config.healthy_threshold {
+                        println!(
+                            "Endpoint {} of cluster {} is healthy",
+                            self.endpoint.authority, self.cluster.name
+                        );
+                        self.endpoint.set_healthy(true);
+                        self.ctx.health.notify();
+                    }
+                }
+                Err(err) => {
+                    failures += 1;
+                    successes = 0;
+                    if self.endpoint.is_healthy() && failures >= self.config.unhealthy_threshold {
+                        eprintln!(
+                            "Endpoint {} of cluster {} is unhealthy: {}",
+                            self.endpoint.authority, self.cluster.name, err
+                        );
+                        self.endpoint.set_healthy(false);
+                        self.ctx.health.notify();
+                    }
+                }
+            }
+            gauge.set(self.endpoint.is_healthy() as i64);
+        }
+    }
+
+    async fn check(&self) -> Result<ServingStatus, BoxError> {
+        let pooled = self.ctx.pool.get(&self.endpoint).await?;
+        let body = Full::new(encode_request(&self.config.service))
+            .map_err(|never| match never {})
+            .boxed_unsync();
+        let req = Request::post(self.endpoint.uri(CHECK_PATH)?)
+            .header(CONTENT_TYPE, "application/grpc")
+            .header(TE, "trailers")
+            .body(body)?;
+        let res = pooled.sender().send_request(req).await?;
+        if res.status() != StatusCode::OK {
+            return Err(format!("HTTP status {}", res.status()).into());
+        }
+        // trailers-only responses carry the status in the headers
+        if let Some(status) = Status::from_headers(res.headers()) {
+            return Err(format!("{:?}: {}", status.code(), status.message()).into());
+        }
+
+        let collected = res.into_body().collect().await?;
+        let status = collected
+            .trailers()
+            .and_then(Status::from_headers)
+            .ok_or("Missing grpc-status trailer")?;
+        if status.code() != Code::Ok {
+            return Err(format!("{:?}: {}", status.code(), status.message()).into());
+        }
+        decode_response(&collected.to_bytes())
+    }
+}
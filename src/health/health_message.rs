@@ -0,0 +1,147 @@
+//! Hand-written codec of the `grpc.health.v1` messages, too small to justify
+//! generated protobuf code in the proxy.
+//!
+//! <https://github.com/grpc/grpc/blob/master/doc/health-checking.md>
+
+use bytes::{BufMut, Bytes, BytesMut};
+use tower::BoxError;
+
+pub const CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+pub const WATCH_PATH: &str = "/grpc.health.v1.Health/Watch";
+
+/// `grpc.health.v1.HealthCheckResponse.ServingStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServingStatus {
+    Unknown = 0,
+    Serving = 1,
+    NotServing = 2,
+    ServiceUnknown = 3,
+}
+
+impl ServingStatus {
+    pub fn from_u64(value: u64) -> ServingStatus {
+        match value {
+            1 => ServingStatus::Serving,
+            2 => ServingStatus::NotServing,
+            3 => ServingStatus::ServiceUnknown,
+            _ => ServingStatus::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServingStatus::Unknown => "UNKNOWN",
+            ServingStatus::Serving => "SERVING",
+            ServingStatus::NotServing => "NOT_SERVING",
+            ServingStatus::ServiceUnknown => "SERVICE_UNKNOWN",
+        }
+    }
+}
+
+/// `HealthCheckRequest { string service = 1; }` as a length-prefixed gRPC message
+pub fn encode_request(service: &str) -> Bytes {
+    let mut message = BytesMut::new();
+    if !service.is_empty() {
+        message.put_u8(1 << 3 | 2);
+        put_varint(&mut message, service.len() as u64);
+        message.put_slice(service.as_bytes());
+    }
+    frame(&message)
+}
+
+/// The service name of a length-prefixed `HealthCheckRequest`
+pub fn decode_request(body: &[u8]) -> Result<String, BoxError> {
+    let mut message = unframe(body)?;
+    let mut service = String::new();
+    while !message.is_empty() {
+        let key = get_varint(&mut message)?;
+        match (key >> 3, key & 7) {
+            (1, 2) => {
+                let len = get_varint(&mut message)? as usize;
+                let value = message.get(..len).ok_or("Truncated field")?;
+                service = String::from_utf8(value.to_vec())?;
+                message = &message[len..];
+            }
+            (_, wire_type) => skip_field(&mut message, wire_type)?,
+        }
+    }
+    Ok(service)
+}
+
+/// `HealthCheckResponse { ServingStatus status = 1; }` as a length-prefixed gRPC message
+pub fn encode_response(status: ServingStatus) -> Bytes {
+    let mut message = BytesMut::new();
+    if status != ServingStatus::Unknown {
+        message.put_u8(1 << 3);
+        put_varint(&mut message, status as u64);
+    }
+    frame(&message)
+}
+
+/// `HealthCheckResponse { ServingStatus status = 1; }` from a length-prefixed gRPC message
+pub fn decode_response(body: &[u8]) -> Result<ServingStatus, BoxError> {
+    let mut message = unframe(body)?;
+    let mut status = ServingStatus::Unknown;
+    while !message.is_empty() {
+        let key = get_varint(&mut message)?;
+        match (key >> 3, key & 7) {
+            (1, 0) => status = ServingStatus::from_u64(get_varint(&mut message)?),
+            (_, wire_type) => skip_field(&mut message, wire_type)?,
+        }
+    }
+    Ok(status)
+}
+
+fn frame(message: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(5 + message.len());
+    buf.put_u8(0);
+    buf.put_u32(message.len() as u32);
+    buf.put_slice(message);
+    buf.freeze()
+}
+
+/// The single uncompressed message of a unary body
+fn unframe(body: &[u8]) -> Result<&[u8], BoxError> {
+    if body.len() < 5 {
+        return Err("Truncated gRPC message".into());
+    }
+    if body[0] != 0 {
+        return Err("Compressed health messages are not supported".into());
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    body.get(5..5 + len)
+        .ok_or_else(|| "Truncated gRPC message".into())
+}
+
+fn put_varint(buf: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        buf.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.put_u8(value as u8);
+}
+
+fn get_varint(buf: &mut &[u8]) -> Result<u64, BoxError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or("Truncated varint")?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+    }
+    Err("Varint overflow".into())
+}
+
+fn skip_field(buf: &mut &[u8], wire_type: u64) -> Result<(), BoxError> {
+    let len = match wire_type {
+        0 => return get_varint(buf).map(|_| ()),
+        1 => 8,
+        2 => get_varint(buf)? as usize,
+        5 => 4,
+        _ => return Err(format!("Unsupported wire type {}", wire_type).into()),
+    };
+    *buf = buf.get(len..).ok_or("Truncated field")?;
+    Ok(())
+}
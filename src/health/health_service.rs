@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use bytes::Bytes;
+use futures_core::Stream;
+use http::{Request, Response, header::CONTENT_TYPE};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use tower::BoxError;
+
+use crate::{
+    context::ProxyContext,
+    core::{
+        grpc_kind::GrpcKind,
+        proxy_error::ProxyError,
+        status::{Code, Status},
+        stream_response::{DynStream, StreamResponse},
+    },
+    health::health_message::{
+        CHECK_PATH, ServingStatus, WATCH_PATH, decode_request, encode_response,
+    },
+};
+
+/// Calls of `grpc.health.v1.Health` answered by the proxy about itself
+/// rather than forwarded
+pub fn handles(path: &str) -> bool {
+    path == CHECK_PATH || path == WATCH_PATH
+}
+
+pub async fn serve<B>(
+    kind: GrpcKind,
+    parts: http::request::Parts,
+    req_body: B,
+    ctx: Arc<ProxyContext>,
+) -> Result<StreamResponse, ProxyError>
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let watch = parts.uri.path() == WATCH_PATH;
+    let req = kind.decode_request(Request::from_parts(parts, req_body));
+    let body = req
+        .into_body()
+        .collect()
+        .await
+        .map_err(ProxyError::Protocol)?
+        .to_bytes();
+    let service = decode_request(&body).map_err(ProxyError::Protocol)?;
+
+    if !watch {
+        let Some(status) = ctx.health.status(&ctx.state().routes, &service) else {
+            let status = Status::new(Code::NotFound, format!("Unknown service {:?}", service));
+            return Ok(kind.status_response(&status));
+        };
+        let stream = try_stream! {
+            yield kind.message_frame(encode_response(status));
+            yield kind.status_frame(&Status::new(Code::Ok, ""));
+        };
+        return Ok(response(kind, stream));
+    }
+
+    // a message every time the status changes, until the client goes away
+    let mut changed = ctx.health.subscribe();
+    let stream = try_stream! {
+        let mut last = None;
+        loop {
+            let status = ctx
+                .health
+                .status(&ctx.state().routes, &service)
+                .unwrap_or(ServingStatus::ServiceUnknown);
+            if last != Some(status) {
+                last = Some(status);
+                yield kind.message_frame(encode_response(status));
+            }
+            if changed.changed().await.is_err() {
+                break;
+            }
+        }
+        yield kind.status_frame(&Status::new(Code::Ok, ""));
+    };
+    Ok(response(kind, stream))
+}
+
+fn response<S>(kind: GrpcKind, stream: S) -> StreamResponse
+where
+    S: Stream<Item = Result<Frame<Bytes>, hyper::Error>> + Send + 'static,
+{
+    let boxed: DynStream = Box::pin(stream);
+    let mut res = Response::new(StreamBody::new(boxed));
+    res.headers_mut().insert(CONTENT_TYPE, kind.content_type());
+    res
+}
@@ -0,0 +1,4 @@
+pub mod health_checker;
+pub mod health_message;
+pub mod health_service;
+pub mod proxy_health;
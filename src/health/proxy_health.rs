@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bytes::Bytes;
+use http::StatusCode;
+use http_body_util::Full;
+use tokio::sync::watch;
+
+use crate::{
+    core::stream_response::StreamResponse, health::health_message::ServingStatus,
+    routing::route_table::RouteTable, telemetry::metrics::from_full_bytes,
+};
+
+/// Health of the proxy itself, as reported to its own probes
+pub struct ProxyHealth {
+    listening: AtomicBool,
+    draining: AtomicBool,
+    changed: watch::Sender<()>,
+}
+
+impl ProxyHealth {
+    pub fn new() -> Self {
+        Self {
+            listening: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            changed: watch::Sender::new(()),
+        }
+    }
+
+    pub fn set_listening(&self, listening: bool) {
+        self.listening.store(listening, Ordering::Relaxed);
+        self.notify();
+    }
+
+    /// New calls should go to another instance, the ones in flight are finishing
+    pub fn set_draining(&self, draining: bool) {
+        self.draining.store(draining, Ordering::Relaxed);
+        self.notify();
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
+
+    /// Wakes up the watchers, the status may have changed
+    pub fn notify(&self) {
+        self.changed.send_replace(());
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    /// The status of the proxy for the empty service name, or of the cluster
+    /// named `service`. `None` when there is no such cluster.
+    pub fn status(&self, routes: &RouteTable, service: &str) -> Option<ServingStatus> {
+        let clusters = routes.clusters();
+        let healthy = if service.is_empty() {
+            clusters
+                .iter()
+                .any(|cluster| cluster.endpoints.iter().any(|e| e.is_healthy()))
+        } else {
+            let cluster = clusters.iter().find(|cluster| cluster.name == service)?;
+            cluster.endpoints.iter().any(|e| e.is_healthy())
+        };
+        let serving = healthy && self.is_listening() && !self.draining.load(Ordering::Relaxed);
+        Some(match serving {
+            true => ServingStatus::Serving,
+            false => ServingStatus::NotServing,
+        })
+    }
+
+    /// Plain HTTP probe, `/healthz` only fails once the listener is down
+    /// while `/readyz` also fails when draining or without healthy backends
+    pub fn probe_response(&self, routes: &RouteTable, readiness: bool) -> StreamResponse {
+        let status = self.status(routes, "").unwrap_or(ServingStatus::NotServing);
+        let ok = match readiness {
+            true => status == ServingStatus::Serving,
+            false => self.is_listening(),
+        };
+        let mut res = from_full_bytes(Full::new(Bytes::from(status.as_str())));
+        if !ok {
+            *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        }
+        res
+    }
+}
+
+impl Default for ProxyHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
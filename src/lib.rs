@@ -1,50 +1,75 @@
 use bytes::Bytes;
-use futures_core::Stream;
-use http::{Request, Response, Uri, header::CONTENT_TYPE, uri::Authority};
-use http_body::Frame;
-use http_body_util::StreamBody;
-use hyper::client::conn::http2;
-use hyper_util::server::conn::auto::Builder as AutoBuilder;
-use hyper_util::{
-    rt::{TokioExecutor, TokioIo},
-    service::TowerToHyperService,
-};
+use http::{Request, header::CONTENT_TYPE};
+use hyper_util::{rt::TokioIo, service::TowerToHyperService};
 use scopeguard::defer;
-use std::str::FromStr;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
 use tokio::time::Instant;
 use tower::BoxError;
 
+use crate::admission::resizable_semaphore::{ResizablePermit, ResizableSemaphore};
+use crate::compression::encoding::{Encoding, GRPC_ACCEPT_ENCODING};
+use crate::config::compression_config::CompressionConfig;
+use crate::config::config_reloader::ConfigReloader;
+use crate::config::proxy_config::ProxyConfig;
+use crate::context::{ProxyContext, ProxyState};
 use crate::core::grpc_kind::GrpcKind;
-use crate::telemetry::metrics::Metrics;
+use crate::core::grpc_kind_connect::{Codec, GrpcKindConnect};
+use crate::core::grpc_kind_json::GrpcKindJson;
+use crate::core::grpc_kind_plain::GrpcKindPlain;
+use crate::core::grpc_kind_web::GrpcKindWeb;
+use crate::core::grpc_kind_web_json::{GrpcKindWebJson, is_web_json};
+use crate::core::grpc_timeout::{GRPC_TIMEOUT, encode_grpc_timeout, grpc_deadline};
+use crate::core::peer_addr::PeerAddr;
+use crate::core::proxy_error::ProxyError;
+use crate::core::stream_response::{StreamResponse, UpstreamBody, hold_until_end, on_end};
+use crate::cors::cors_policy::CorsPolicy;
+use crate::health::health_service;
+use crate::server::{
+    connection_activity::{ActiveCall, ConnectionActivity},
+    server_builder::ServerSettings,
+};
+use crate::tls::server_tls::ServerTls;
+use crate::transcoding::message_converter::{json_to_proto_request, proto_to_json_response};
+use crate::upstream::upstream_call;
+use crate::websocket::{upgraded_calls::UpgradedCalls, websocket_transport};
 
 #[cfg(any(test, feature = "test-support"))]
 pub mod test_support;
 
+pub mod admission;
+pub mod balancing;
 pub mod command;
+pub mod compression;
+pub mod config;
+pub mod context;
 pub mod core;
+pub mod cors;
+pub mod health;
+pub mod rate_limit;
+pub mod retry;
+pub mod routing;
+pub mod server;
 pub mod telemetry;
+pub mod tls;
 pub mod trailers;
+pub mod transcoding;
+pub mod upstream;
+pub mod websocket;
 
-pub async fn forward<B>(
-    req: Request<B>,
-    authority: Authority,
-    metrics: Arc<Metrics>,
-) -> Result<
-    Response<StreamBody<impl Stream<Item = Result<Frame<Bytes>, hyper::Error>> + Send>>,
-    BoxError,
->
+pub async fn forward<B>(req: Request<B>, ctx: Arc<ProxyContext>) -> Result<StreamResponse, BoxError>
 where
     B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
     B::Error: Into<BoxError>,
 {
-    //[START] switch endpoint
-    let (mut parts, req_body) = req.into_parts();
-    parts
-        .headers
-        .insert(hyper::header::HOST, authority.as_str().parse()?);
+    let metrics = &ctx.metrics;
+    let (parts, req_body) = req.into_parts();
 
     let path = parts.uri.path().to_string();
 
@@ -52,6 +77,29 @@ where
     if path == "/metrics" {
         return Ok(metrics.render());
     }
+    // the configuration this request is handled with, even if it is reloaded meanwhile
+    let state = ctx.state();
+    if path == "/healthz" || path == "/readyz" {
+        return Ok(ctx.health.probe_response(&state.routes, path == "/readyz"));
+    }
+
+    // browsers also send Origin on same-origin POST requests, CORS does not apply to them
+    let origin = parts
+        .headers
+        .get(hyper::header::ORIGIN)
+        .filter(|origin| !CorsPolicy::is_same_origin(origin, &parts, ctx.scheme))
+        .cloned();
+    if let Some(origin) = &origin {
+        if !state.cors.is_allowed(origin) {
+            return Ok(CorsPolicy::forbidden_response());
+        }
+        if CorsPolicy::is_preflight(&parts.method, &parts.headers) {
+            return Ok(state.cors.preflight_response(origin));
+        }
+    }
+    if websocket_transport::is_upgrade(&parts) {
+        return Ok(forward_websocket(parts, ctx.clone()));
+    }
     let start = Instant::now();
     defer!({
         let elapsed = start.elapsed().as_secs_f64();
@@ -64,69 +112,353 @@ where
             .with_label_values(&[&"POST", &path.as_str()])
             .observe(elapsed);
     });
-    let url = format!("http://{}{}", authority.as_ref(), path);
 
-    parts.uri = url.parse::<Uri>()?;
+    let json_call = state
+        .transcoder
+        .as_ref()
+        .and_then(|transcoder| transcoder.find(&parts.method, &path));
+    let mut res = match json_call {
+        Some(call) => {
+            let kind = GrpcKindJson::new(call);
+            match forward_json(&kind, parts, req_body, &state, &ctx).await {
+                Ok(res) => res,
+                Err(err) => {
+                    eprintln!("Failed to forward {}: {}", path, err);
+                    kind.status_response(&err.status())
+                }
+            }
+        }
+        None if parts.headers.get(CONTENT_TYPE).is_some_and(is_web_json) => {
+            forward_web_json(parts, req_body, &state, &ctx).await
+        }
+        None => match GrpcKindConnect::from_request(&parts) {
+            Some(kind) if kind.codec() == Codec::Json => {
+                forward_connect_json(kind, parts, req_body, &state, &ctx).await
+            }
+            _ => forward_by_content_type(parts, req_body, &state, &ctx).await,
+        },
+    };
+    if let Some(origin) = &origin {
+        state.cors.apply(origin, res.headers_mut());
+    }
+    Ok(res)
+}
 
-    //[END] switch endpoint
+/// Forwards a gRPC, grpc-web, grpc-web-text or Connect call, as told by its
+/// content type
+async fn forward_by_content_type<B>(
+    parts: http::request::Parts,
+    req_body: B,
+    state: &ProxyState,
+    ctx: &Arc<ProxyContext>,
+) -> StreamResponse
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
+    B::Error: Into<BoxError>,
+{
+    let path = parts.uri.path().to_string();
+    let kind = match parts.headers.get(CONTENT_TYPE) {
+        Some(content_type) => GrpcKind::from_content_type(content_type)
+            .ok_or_else(|| ProxyError::UnsupportedContentType(content_type.clone())),
+        // Connect sends unary GET requests without a body
+        None => GrpcKindConnect::from_request(&parts)
+            .map(GrpcKind::Connect)
+            .ok_or(ProxyError::MissingContentType),
+    };
+    match kind {
+        Ok(kind) => {
+            let result = if health_service::handles(&path) {
+                health_service::serve(kind, parts, req_body, ctx.clone()).await
+            } else {
+                forward_grpc(&kind, parts, req_body, state, ctx).await
+            };
+            match result {
+                Ok(res) => res,
+                Err(err) => {
+                    eprintln!("Failed to forward {}: {}", path, err);
+                    let mut res = kind.status_response(&err.status());
+                    if let ProxyError::UnsupportedEncoding(_) = err {
+                        // tells the client which encodings to use instead
+                        res.headers_mut()
+                            .insert(GRPC_ACCEPT_ENCODING, Encoding::accept_header());
+                    }
+                    res
+                }
+            }
+        }
+        // without a known content type, the status can only be sent in headers
+        Err(err) => err.status().trailers_only_response(),
+    }
+}
 
-    let stream = TcpStream::connect(authority.to_string()).await?;
-    let io = TokioIo::new(stream);
+/// Forwards a grpc-web+json call, its messages converted with the
+/// descriptor of its method
+async fn forward_web_json<B>(
+    parts: http::request::Parts,
+    req_body: B,
+    state: &ProxyState,
+    ctx: &Arc<ProxyContext>,
+) -> StreamResponse
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
+    B::Error: Into<BoxError>,
+{
+    let path = parts.uri.path().to_string();
+    let method = state
+        .transcoder
+        .as_ref()
+        .and_then(|transcoder| transcoder.method(&path));
+    let result = match method {
+        Some(method) => {
+            let kind = GrpcKindWebJson::new(method.clone(), max_message_size(&parts, state));
+            let req = kind.encode_request(Request::from_parts(parts, req_body));
+            let (parts, body) = req.into_parts();
+            forward_grpc(&GrpcKind::Web(GrpcKindWeb), parts, body, state, ctx)
+                .await
+                .map(|res| kind.respond(res))
+        }
+        None => Err(ProxyError::UnknownMethod(path.clone())),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("Failed to forward {}: {}", path, err);
+        GrpcKindWebJson::status_response(&err.status())
+    })
+}
 
-    let exec = TokioExecutor::new();
-    let (sender, conn): (
-        http2::SendRequest<_>,
-        http2::Connection<TokioIo<TcpStream>, _, TokioExecutor>,
-    ) = http2::Builder::new(exec).handshake(io).await?;
+/// Largest message converted for the call, as set for the cluster it is routed to
+fn max_message_size(parts: &http::request::Parts, state: &ProxyState) -> usize {
+    match state.routes.find(parts.uri.path(), &parts.headers) {
+        Some(route) => route.cluster.compression.max_message_size(),
+        // the call fails with UNIMPLEMENTED anyway
+        None => CompressionConfig::default().max_message_bytes,
+    }
+}
 
-    // Spawn a task to poll the connection, driving the HTTP state
-    tokio::task::spawn(async move {
-        if let Err(err) = conn.await {
-            println!("Connection failed: {:?}", err);
+/// Forwards a Connect call with JSON messages, converted with the
+/// descriptor of its method
+async fn forward_connect_json<B>(
+    kind: GrpcKindConnect,
+    parts: http::request::Parts,
+    req_body: B,
+    state: &ProxyState,
+    ctx: &Arc<ProxyContext>,
+) -> StreamResponse
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
+    B::Error: Into<BoxError>,
+{
+    let path = parts.uri.path().to_string();
+    let kind = GrpcKind::Connect(kind);
+    let method = state
+        .transcoder
+        .as_ref()
+        .and_then(|transcoder| transcoder.method(&path));
+    let result = match method {
+        Some(method) => {
+            let max_message_size = max_message_size(&parts, state);
+            let req = kind.decode_request(Request::from_parts(parts, req_body));
+            let mut req = json_to_proto_request(req, method.input(), max_message_size);
+            // the response messages are converted, so they must arrive uncompressed
+            req.headers_mut().remove(GRPC_ACCEPT_ENCODING);
+            let (parts, body) = req.into_parts();
+            let plain = GrpcKind::Plain(GrpcKindPlain);
+            match forward_grpc(&plain, parts, body, state, ctx).await {
+                Ok(res) => {
+                    let res = proto_to_json_response(res, method.output(), plain, max_message_size);
+                    Ok(kind.respond(res, None).await)
+                }
+                Err(err) => Err(err),
+            }
         }
-    });
-    let content_type = parts
-        .headers
-        .get(CONTENT_TYPE)
-        .ok_or("Missing Content-Type header")?
-        .clone();
-    let req = Request::from_parts(parts, req_body);
-    GrpcKind::from_content_type(&content_type)
-        .ok_or("Unsupported Content-Type header")?
-        .forward(sender, req)
+        None => Err(ProxyError::UnknownMethod(path.clone())),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("Failed to forward {}: {}", path, err);
+        kind.status_response(&err.status())
+    })
+}
+
+/// Accepts a WebSocket carrying a grpc-web call, forwarded once the
+/// connection has been upgraded
+fn forward_websocket(mut parts: http::request::Parts, ctx: Arc<ProxyContext>) -> StreamResponse {
+    let path = parts.uri.path().to_string();
+    match websocket_transport::accept(&mut parts) {
+        Ok((res, upgrade)) => {
+            let upgraded_calls = parts.extensions.remove::<UpgradedCalls>();
+            let serve = Box::pin(async move {
+                let call = |req| forward_websocket_call(req, ctx);
+                if let Err(err) = websocket_transport::serve(upgrade, parts, call).await {
+                    eprintln!("WebSocket call to {} failed: {}", path, err);
+                }
+            });
+            match upgraded_calls {
+                Some(upgraded_calls) => {
+                    let _ = upgraded_calls.0.send(serve);
+                }
+                // a request not received by the proxy server
+                None => {
+                    tokio::spawn(serve);
+                }
+            }
+            res
+        }
+        Err(err) => {
+            eprintln!("Failed to accept WebSocket for {}: {}", path, err);
+            websocket_transport::rejected_response()
+        }
+    }
+}
+
+async fn forward_websocket_call(
+    req: Request<UpstreamBody>,
+    ctx: Arc<ProxyContext>,
+) -> StreamResponse {
+    let state = ctx.state();
+    let path = req.uri().path().to_string();
+    let kind = GrpcKind::Web(GrpcKindWeb);
+    let (parts, body) = req.into_parts();
+    forward_grpc(&kind, parts, body, &state, &ctx)
         .await
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to forward {}: {}", path, err);
+            kind.status_response(&err.status())
+        })
+}
+
+/// Forwards a REST request as the unary call its HTTP rule maps it to
+async fn forward_json<B>(
+    kind: &GrpcKindJson<'_>,
+    parts: http::request::Parts,
+    req_body: B,
+    state: &ProxyState,
+    ctx: &Arc<ProxyContext>,
+) -> Result<StreamResponse, ProxyError>
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
+    B::Error: Into<BoxError>,
+{
+    let req = kind
+        .decode_request(Request::from_parts(parts, req_body))
+        .await?;
+    let (parts, body) = req.into_parts();
+    let res = forward_grpc(&GrpcKind::Plain(GrpcKindPlain), parts, body, state, ctx).await?;
+    Ok(kind.respond(res).await)
+}
+
+async fn forward_grpc<B>(
+    kind: &GrpcKind,
+    parts: http::request::Parts,
+    req_body: B,
+    state: &ProxyState,
+    ctx: &Arc<ProxyContext>,
+) -> Result<StreamResponse, ProxyError>
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static + Unpin,
+    B::Error: Into<BoxError>,
+{
+    // the call is routed and timed as the plain gRPC call it becomes
+    let req = kind.decode_request(Request::from_parts(parts, req_body));
+    let (mut parts, body) = req.into_parts();
+    let route = state
+        .routes
+        .find(parts.uri.path(), &parts.headers)
+        .ok_or_else(|| ProxyError::NoRoute(parts.uri.path().to_string()))?;
+    let peer = parts.extensions.get::<PeerAddr>().map(|peer| peer.0.ip());
+    if !route.admit(&parts.headers, peer, &ctx.metrics) {
+        return Err(ProxyError::RateLimited);
+    }
+    route.apply_timeout(&mut parts.headers);
+    // every attempt shares the deadline of the call, counted from its arrival
+    let deadline = grpc_deadline(&parts.headers);
+    let permit = ctx.in_flight.acquire(deadline).await?;
+    if let Some(deadline) = deadline.filter(|_| permit.was_queued()) {
+        parts.headers.insert(
+            GRPC_TIMEOUT,
+            encode_grpc_timeout(deadline.saturating_duration_since(Instant::now())),
+        );
+    }
+    let req = Request::from_parts(parts, body);
+    let compression = &route.cluster.compression;
+    let (req, client_accepts) = compression.transcode_request(req)?;
+    let (res, (pooled, in_flight, breaker_slot, outcome)) =
+        match (&route.retry_policy, &route.hedging_policy) {
+            (Some(policy), _) => {
+                upstream_call::send_with_retries(ctx, &route.cluster, req, policy, deadline).await?
+            }
+            (None, Some(policy)) => {
+                upstream_call::send_hedged(ctx, &route.cluster, req, policy, deadline).await?
+            }
+            (None, None) => upstream_call::send(ctx, &route.cluster, req, deadline).await?,
+        };
+    // the stream slot is released and the call stops counting as in flight
+    // once the response has been streamed
+    let mut res = compression.transcode_response(res, &client_accepts);
+    if let Some(outcome) = outcome {
+        res = on_end(res, move |trailers| outcome.report(trailers));
+    }
+    let res = kind.respond(res, deadline).await;
+    Ok(hold_until_end(
+        res,
+        (pooled, in_flight, breaker_slot, permit),
+    ))
 }
 
 pub async fn start_proxy(
     listener: TcpListener,
-    forward_authority: String,
+    config: ProxyConfig,
+    shutdown_rx: watch::Receiver<bool>,
+) -> Result<(), BoxError> {
+    start_proxy_with_reloader(listener, config, shutdown_rx, None).await
+}
+
+/// Like [`start_proxy`], with the routes, clusters, CORS policy, limits and
+/// connection settings replaced whenever `reloader` loads a new valid configuration
+pub async fn start_proxy_with_reloader(
+    listener: TcpListener,
+    config: ProxyConfig,
     mut shutdown_rx: watch::Receiver<bool>,
+    reloader: Option<ConfigReloader>,
 ) -> Result<(), BoxError> {
-    let forward_authority = Authority::from_str(&forward_authority)?;
-    let metrics = Arc::new(Metrics::new());
+    let ctx = Arc::new(ProxyContext::new(&config)?);
+    let tls = config.tls.as_ref().map(ServerTls::new).transpose()?;
+    let cert_watcher = tls
+        .as_ref()
+        .map(|tls| tokio::spawn(tls.reloader.clone().watch(tls.reload_interval)));
+    let config_watcher = reloader.map(|reloader| tokio::spawn(reloader.watch(ctx.clone())));
+    ctx.start_health_checks();
+    ctx.health.set_listening(true);
+    defer!({
+        if let Some(cert_watcher) = &cert_watcher {
+            cert_watcher.abort();
+        }
+        if let Some(config_watcher) = &config_watcher {
+            config_watcher.abort();
+        }
+        ctx.stop_health_checks();
+    });
+    let (drain_tx, drain_rx) = watch::channel(false);
+    let mut connections = JoinSet::new();
     loop {
         tokio::select! {
-            accept_result = listener.accept() => {
+            accept_result = accept(&listener, &ctx.connection_slots) => {
                 match accept_result {
-                    Ok((stream, _)) => {
-                        let io = TokioIo::new(stream);
-                        let metrics = metrics.clone();
-                        let forward_authority = forward_authority.clone();
-                        tokio::task::spawn(async move {
-
-                            let forward_authority = forward_authority.clone();
-                            let metrics = metrics.clone();
-                            let svc = tower::service_fn(move |req| {
-                                let forward_authority = forward_authority.clone();
-                                let metrics = metrics.clone();
-                                forward(req, forward_authority, metrics)
-                            });
-                            let svc = TowerToHyperService::new(svc);
-                            if let Err(err) = AutoBuilder::new(TokioExecutor::new())
-                                .serve_connection(io, svc)
-                                .await
-                            {
-                                eprintln!("Error serving connection: {:?}", err);
+                    Ok((stream, peer, slot)) => {
+                        let ctx = ctx.clone();
+                        let settings = ctx.server();
+                        let drain = drain_rx.clone();
+                        let acceptor = tls.as_ref().map(|tls| tls.acceptor.clone());
+                        connections.spawn(async move {
+                            // the connection counts against max_connections until it is closed
+                            let _slot = slot;
+                            let open = ctx.metrics.downstream_connections.clone();
+                            open.inc();
+                            defer!(open.dec());
+                            match acceptor {
+                                Some(acceptor) => match acceptor.accept(stream).await {
+                                    Ok(stream) => serve_connection(TokioIo::new(stream), peer, ctx, settings, drain).await,
+                                    Err(err) => eprintln!("TLS handshake failed: {:?}", err),
+                                },
+                                None => serve_connection(TokioIo::new(stream), peer, ctx, settings, drain).await,
                             }
                         });
                     }
@@ -136,13 +468,119 @@ pub async fn start_proxy(
                 }
             }
 
-             _ = shutdown_rx.changed() => {
-                if *shutdown_rx.borrow() {
+            // reap finished connection tasks
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+
+            changed = shutdown_rx.changed() => {
+                // a dropped sender can no longer stop the proxy, so it stops now
+                if changed.is_err() || *shutdown_rx.borrow() {
                     println!("Proxy shutdown signal received");
+                    ctx.health.set_draining(true);
                     break;
                 }
             }
         }
     }
+    // refuse new connections while the open ones drain
+    drop(listener);
+    ctx.health.set_listening(false);
+
+    println!("Draining {} connections", connections.len());
+    let drain_timeout = Duration::from_millis(config.drain_timeout_ms);
+    let drained = tokio::time::timeout(drain_timeout, async {
+        // GOAWAY on HTTP/2, Connection: close on HTTP/1,
+        // resolved once every call in flight has finished
+        let _ = drain_tx.send(true);
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!(
+            "Drain deadline passed, closing {} connections",
+            connections.len()
+        );
+        connections.shutdown().await;
+    }
     Ok(())
 }
+
+/// Accepts a connection once fewer than `max_connections` are open
+async fn accept(
+    listener: &TcpListener,
+    slots: &ResizableSemaphore,
+) -> io::Result<(TcpStream, SocketAddr, ResizablePermit)> {
+    loop {
+        let mut resized = pin!(slots.resized());
+        resized.as_mut().enable();
+        // the semaphore is never closed
+        let slot = slots.acquire().await.unwrap();
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, peer) = accepted?;
+                return Ok((stream, peer, slot));
+            }
+            // the slot taken may be over a lowered limit
+            _ = resized => {}
+        }
+    }
+}
+
+/// Forwards a call, keeping `call` alive until its response has been streamed
+async fn forward_call(
+    req: Request<hyper::body::Incoming>,
+    ctx: Arc<ProxyContext>,
+    call: ActiveCall,
+) -> Result<StreamResponse, BoxError> {
+    let res = forward(req, ctx).await?;
+    Ok(hold_until_end(res, call))
+}
+
+async fn serve_connection<I>(
+    io: I,
+    peer: SocketAddr,
+    ctx: Arc<ProxyContext>,
+    settings: Arc<ServerSettings>,
+    drain: watch::Receiver<bool>,
+) where
+    I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+    let activity = ConnectionActivity::new();
+    let (upgraded_tx, mut upgraded_rx) = mpsc::unbounded_channel();
+    let svc = {
+        let activity = activity.clone();
+        tower::service_fn(move |mut req: Request<hyper::body::Incoming>| {
+            req.extensions_mut().insert(PeerAddr(peer));
+            req.extensions_mut()
+                .insert(UpgradedCalls(upgraded_tx.clone()));
+            // the connection is busy until the response has been streamed
+            let call = activity.start_call();
+            let ctx = ctx.clone();
+            forward_call(req, ctx, call)
+        })
+    };
+    let svc = TowerToHyperService::new(svc);
+    // WebSockets take over the connections they upgrade
+    let mut conn = pin!(settings.builder.serve_connection_with_upgrades(io, svc));
+    let result = tokio::select! {
+        result = conn.as_mut() => result,
+        grace = settings.lifetime.closing(&activity, drain) => {
+            // GOAWAY on HTTP/2, Connection: close on HTTP/1
+            conn.as_mut().graceful_shutdown();
+            match grace {
+                Some(grace) => tokio::time::timeout(grace, conn).await.unwrap_or_else(|_| {
+                    eprintln!("Closing connection past its max age with calls in flight");
+                    Ok(())
+                }),
+                None => conn.await,
+            }
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("Error serving connection: {:?}", err);
+    }
+    // a WebSocket is served by the task of the connection it upgrades, which
+    // keeps its max_connections slot and is drained on shutdown
+    while let Ok(call) = upgraded_rx.try_recv() {
+        call.await;
+    }
+}
@@ -1,16 +1,82 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
 use clap::Parser;
-use griffin::{command::args::Args, start_proxy};
+use griffin::{
+    command::{
+        args::{Args, Command},
+        check_config::{check_config, validate},
+        reload_signal::reload_signal,
+        shutdown_signal::shutdown_signal,
+    },
+    config::config_reloader::ConfigReloader,
+    start_proxy_with_reloader,
+};
 use tokio::net::TcpListener;
-use tower::BoxError;
 
 #[tokio::main]
-async fn main() -> Result<(), BoxError> {
+async fn main() -> ExitCode {
     let args = Args::parse();
-    let proxy_address = format!("{}:{}", args.proxy_host, args.proxy_port);
 
-    let forward_authority = format!("{}:{}", args.forward_host, args.forward_port);
-    let (_, shutdown_rx) = tokio::sync::watch::channel(false);
-    let listener = TcpListener::bind(proxy_address).await?;
+    if let Some(Command::CheckConfig) = args.command {
+        let Some(path) = &args.config else {
+            eprintln!("check-config requires --config or GRIFFIN_CONFIG");
+            return ExitCode::FAILURE;
+        };
+        let errors = check_config(path);
+        for err in &errors {
+            eprintln!("{}", err);
+        }
+        if !errors.is_empty() {
+            return ExitCode::FAILURE;
+        }
+        println!("{}: configuration is valid", path.display());
+        return ExitCode::SUCCESS;
+    }
+
+    let config = match args.clone().into_config() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    // the file and the flags together, as check-config only sees the file
+    let errors = validate(&config);
+    for (field, err) in &errors {
+        eprintln!("{}: {}", field, err);
+    }
+    if !errors.is_empty() {
+        return ExitCode::FAILURE;
+    }
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let listener = match TcpListener::bind(&config.listen).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Failed to listen on {}: {}", config.listen, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_tx.send(true);
+    });
+
+    // reloads read the file again and apply the same flags and environment
+    let reloader = args.config.clone().map(|path| {
+        let (reload_tx, reload_rx) = tokio::sync::watch::channel(());
+        tokio::spawn(reload_signal(reload_tx));
+        ConfigReloader::new(path, Duration::from_secs(2), reload_rx, move || {
+            Ok(args.clone().into_config()?)
+        })
+    });
 
-    start_proxy(listener, forward_authority, shutdown_rx).await
+    match start_proxy_with_reloader(listener, config, shutdown_rx, reloader).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
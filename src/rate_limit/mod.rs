@@ -0,0 +1,2 @@
+pub mod rate_limiter;
+pub mod token_bucket;
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use http::{HeaderMap, HeaderName};
+use tokio::time::Instant;
+use tower::BoxError;
+
+use crate::{
+    config::rate_limit_config::{RateLimitConfig, RateLimitKeyConfig},
+    rate_limit::token_bucket::TokenBucket,
+    telemetry::metrics::Metrics,
+};
+
+/// Keys tracked at most, the calls of other keys sharing one bucket
+const MAX_KEYS: usize = 10_000;
+
+enum RateLimitKey {
+    Route,
+    PeerIp,
+    Header(HeaderName),
+}
+
+/// A token bucket per key of the calls of a route
+pub struct RateLimiter {
+    key: RateLimitKey,
+    rate: f64,
+    burst: f64,
+    /// `route` and `limiter` labels of the metrics
+    labels: [String; 2],
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    by_key: HashMap<Vec<u8>, TokenBucket>,
+    /// Tracked keys, the next one to check for a full bucket to forget first
+    order: VecDeque<Vec<u8>>,
+    /// Shared by the keys arriving while no tracked bucket can be forgotten
+    overflow: TokenBucket,
+}
+
+impl Buckets {
+    /// The bucket of `key`, tracking it when there is room for it or a full
+    /// bucket to forget. A single bucket is checked per call, a bucket in use
+    /// being checked again once every other one has been.
+    fn get(&mut self, key: Vec<u8>, rate: f64, burst: f64, now: Instant) -> &mut TokenBucket {
+        if !self.by_key.contains_key(&key) && self.by_key.len() >= MAX_KEYS {
+            let oldest = self.order.pop_front().unwrap();
+            if !self.by_key[&oldest].is_full(rate, burst, now) {
+                self.order.push_back(oldest);
+                return &mut self.overflow;
+            }
+            self.by_key.remove(&oldest);
+        }
+        self.by_key.entry(key).or_insert_with_key(|key| {
+            self.order.push_back(key.clone());
+            TokenBucket::full(burst, now)
+        })
+    }
+}
+
+impl RateLimiter {
+    pub fn from_config(route: &str, config: &RateLimitConfig) -> Result<Self, BoxError> {
+        if !(config.requests_per_second > 0.0 && config.requests_per_second.is_finite()) {
+            return Err("requests_per_second must be greater than 0".into());
+        }
+        if config.burst == 0 {
+            return Err("burst must be greater than 0".into());
+        }
+        let (key, limiter) = match &config.key {
+            RateLimitKeyConfig::Route => (RateLimitKey::Route, "route".to_string()),
+            RateLimitKeyConfig::PeerIp => (RateLimitKey::PeerIp, "peer_ip".to_string()),
+            RateLimitKeyConfig::Header(name) => {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| format!("Invalid header name {:?}: {}", name, e))?;
+                let limiter = format!("header:{}", name);
+                (RateLimitKey::Header(name), limiter)
+            }
+        };
+        Ok(Self {
+            key,
+            rate: config.requests_per_second,
+            burst: config.burst.into(),
+            labels: [route.to_string(), limiter],
+            buckets: Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                order: VecDeque::new(),
+                overflow: TokenBucket::full(config.burst.into(), Instant::now()),
+            }),
+        })
+    }
+
+    /// Takes a token from the bucket of the call, `false` when it is over the limit
+    pub fn check(&self, headers: &HeaderMap, peer: Option<IpAddr>, metrics: &Metrics) -> bool {
+        let key = match &self.key {
+            RateLimitKey::Route => Vec::new(),
+            RateLimitKey::PeerIp => peer.map_or_else(Vec::new, |ip| ip.to_string().into_bytes()),
+            RateLimitKey::Header(name) => headers
+                .get(name)
+                .map_or_else(Vec::new, |value| value.as_bytes().to_vec()),
+        };
+        let now = Instant::now();
+        let allowed = self
+            .buckets
+            .lock()
+            .unwrap()
+            .get(key, self.rate, self.burst, now)
+            .try_take(self.rate, self.burst, now);
+        let decision = if allowed { "allowed" } else { "limited" };
+        metrics
+            .rate_limit_decisions
+            .with_label_values(&[&self.labels[0], &self.labels[1], decision])
+            .inc();
+        allowed
+    }
+}
@@ -0,0 +1,41 @@
+use tokio::time::Instant;
+
+/// Tokens refilled continuously at `rate` per second up to `burst`,
+/// each call taking one
+pub struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub fn full(burst: f64, now: Instant) -> Self {
+        Self {
+            tokens: burst,
+            updated: now,
+        }
+    }
+
+    /// Takes a token, `false` when the bucket is empty
+    pub fn try_take(&mut self, rate: f64, burst: f64, now: Instant) -> bool {
+        self.refill(rate, burst, now);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// Whether the bucket would be full by `now`, and so can be forgotten
+    pub fn is_full(&self, rate: f64, burst: f64, now: Instant) -> bool {
+        self.tokens + self.elapsed(now) * rate >= burst
+    }
+
+    fn refill(&mut self, rate: f64, burst: f64, now: Instant) {
+        self.tokens = (self.tokens + self.elapsed(now) * rate).min(burst);
+        self.updated = now;
+    }
+
+    fn elapsed(&self, now: Instant) -> f64 {
+        now.saturating_duration_since(self.updated).as_secs_f64()
+    }
+}
@@ -0,0 +1,39 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use tower::BoxError;
+
+use crate::{config::hedging_policy_config::HedgingPolicyConfig, core::status::Code};
+
+/// Attempts allowed at most, whatever the configuration says
+const MAX_ATTEMPTS: u32 = 5;
+
+pub struct HedgingPolicy {
+    pub max_attempts: u32,
+    pub hedging_delay: Duration,
+    non_fatal_status_codes: Vec<Code>,
+    pub buffer_limit: usize,
+}
+
+impl HedgingPolicy {
+    pub fn from_config(config: &HedgingPolicyConfig) -> Result<Self, BoxError> {
+        if config.max_attempts < 2 {
+            return Err("max_attempts must be at least 2".into());
+        }
+        let non_fatal_status_codes = config
+            .non_fatal_status_codes
+            .iter()
+            .map(|name| Code::from_str(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            max_attempts: config.max_attempts.min(MAX_ATTEMPTS),
+            hedging_delay: Duration::from_millis(config.hedging_delay_ms),
+            non_fatal_status_codes,
+            buffer_limit: config.buffer_limit_bytes,
+        })
+    }
+
+    pub fn is_non_fatal(&self, code: Code) -> bool {
+        self.non_fatal_status_codes.contains(&code)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod hedging_policy;
+pub mod replay_body;
+pub mod retry_policy;
@@ -0,0 +1,148 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::{Body, Frame};
+use tower::BoxError;
+
+use crate::core::stream_response::UpstreamBody;
+
+/// Request body shared by the attempts of a call.
+///
+/// Every attempt first replays the frames already read from the client,
+/// then reads further ones, which are recorded for the next attempts.
+/// Once more than `limit` bytes have been read the frames are dropped
+/// and no new attempt can be made.
+#[derive(Clone)]
+pub struct ReplayBuffer {
+    shared: Arc<Mutex<Shared>>,
+}
+
+struct Shared {
+    source: UpstreamBody,
+    frames: Vec<Recorded>,
+    /// Frames read from the source, recorded or not
+    pulled: usize,
+    buffered: usize,
+    limit: usize,
+    overflowed: bool,
+    /// How the source ended, the error as a message for the other attempts
+    end: Option<Result<(), String>>,
+    /// Attempts waiting for the attempt polling the source
+    waiting: Vec<Waker>,
+}
+
+enum Recorded {
+    Data(Bytes),
+    Trailers(HeaderMap),
+}
+
+impl Shared {
+    fn wake_waiting(&mut self) {
+        for waker in self.waiting.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl ReplayBuffer {
+    pub fn new(source: UpstreamBody, limit: usize) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                source,
+                frames: Vec::new(),
+                pulled: 0,
+                buffered: 0,
+                limit,
+                overflowed: false,
+                end: None,
+                waiting: Vec::new(),
+            })),
+        }
+    }
+
+    /// The body of a new attempt, `None` once the limit has been exceeded
+    pub fn attempt(&self) -> Option<ReplayBody> {
+        if self.shared.lock().unwrap().overflowed {
+            return None;
+        }
+        Some(ReplayBody {
+            shared: self.shared.clone(),
+            position: 0,
+        })
+    }
+}
+
+/// The request body of one attempt
+pub struct ReplayBody {
+    shared: Arc<Mutex<Shared>>,
+    position: usize,
+}
+
+impl Body for ReplayBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let mut shared = this.shared.lock().unwrap();
+
+        if let Some(recorded) = shared.frames.get(this.position) {
+            this.position += 1;
+            let frame = match recorded {
+                Recorded::Data(data) => Frame::data(data.clone()),
+                Recorded::Trailers(trailers) => Frame::trailers(trailers.clone()),
+            };
+            return Poll::Ready(Some(Ok(frame)));
+        }
+        if this.position < shared.pulled {
+            return Poll::Ready(Some(Err("Request body too large to be replayed".into())));
+        }
+        match &shared.end {
+            Some(Ok(())) => return Poll::Ready(None),
+            Some(Err(err)) => return Poll::Ready(Some(Err(err.clone().into()))),
+            None => {}
+        }
+
+        let polled = Pin::new(&mut shared.source).poll_frame(cx);
+        match polled {
+            Poll::Pending => {
+                if !shared.waiting.iter().any(|w| w.will_wake(cx.waker())) {
+                    shared.waiting.push(cx.waker().clone());
+                }
+                return Poll::Pending;
+            }
+            Poll::Ready(None) => shared.end = Some(Ok(())),
+            Poll::Ready(Some(Err(ref err))) => shared.end = Some(Err(err.to_string())),
+            Poll::Ready(Some(Ok(ref frame))) => {
+                shared.pulled += 1;
+                this.position = shared.pulled;
+                let size = frame.data_ref().map_or(0, Bytes::len);
+                if shared.overflowed || shared.buffered + size > shared.limit {
+                    shared.overflowed = true;
+                    shared.frames = Vec::new();
+                } else if let Some(data) = frame.data_ref() {
+                    shared.buffered += size;
+                    shared.frames.push(Recorded::Data(data.clone()));
+                } else if let Some(trailers) = frame.trailers_ref() {
+                    shared.frames.push(Recorded::Trailers(trailers.clone()));
+                }
+            }
+        }
+        // the other attempts read what was just recorded
+        shared.wake_waiting();
+        polled
+    }
+}
+
+impl Drop for ReplayBody {
+    fn drop(&mut self) {
+        // another attempt takes over reading from the client
+        self.shared.lock().unwrap().wake_waiting();
+    }
+}
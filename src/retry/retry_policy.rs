@@ -0,0 +1,87 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use http::HeaderMap;
+use tower::BoxError;
+
+use crate::{config::retry_policy_config::RetryPolicyConfig, core::status::Code};
+
+/// Attempts allowed at most, whatever the configuration says
+const MAX_ATTEMPTS: u32 = 5;
+
+pub const GRPC_PREVIOUS_RPC_ATTEMPTS: &str = "grpc-previous-rpc-attempts";
+pub const GRPC_RETRY_PUSHBACK_MS: &str = "grpc-retry-pushback-ms";
+
+/// How a server answered about retrying its response
+pub enum Pushback {
+    /// No `grpc-retry-pushback-ms`, the policy backoff applies
+    None,
+    /// Retry after the given delay
+    After(Duration),
+    /// A negative or invalid value, the call must not be retried
+    Stop,
+}
+
+impl Pushback {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        match headers.get(GRPC_RETRY_PUSHBACK_MS) {
+            None => Pushback::None,
+            Some(value) => match value.to_str().ok().and_then(|ms| ms.parse().ok()) {
+                Some(ms) => Pushback::After(Duration::from_millis(ms)),
+                None => Pushback::Stop,
+            },
+        }
+    }
+}
+
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_status_codes: Vec<Code>,
+    pub buffer_limit: usize,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &RetryPolicyConfig) -> Result<Self, BoxError> {
+        if config.max_attempts < 2 {
+            return Err("max_attempts must be at least 2".into());
+        }
+        if config.initial_backoff_ms == 0 || config.max_backoff_ms == 0 {
+            return Err("Backoffs must be greater than 0".into());
+        }
+        if !(config.backoff_multiplier > 0.0 && config.backoff_multiplier.is_finite()) {
+            return Err("backoff_multiplier must be greater than 0".into());
+        }
+        let retryable_status_codes = config
+            .retryable_status_codes
+            .iter()
+            .map(|name| Code::from_str(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        if retryable_status_codes.is_empty() {
+            return Err("retryable_status_codes must not be empty".into());
+        }
+        Ok(Self {
+            max_attempts: config.max_attempts.min(MAX_ATTEMPTS),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            backoff_multiplier: config.backoff_multiplier,
+            retryable_status_codes,
+            buffer_limit: config.buffer_limit_bytes,
+        })
+    }
+
+    pub fn is_retryable(&self, code: Code) -> bool {
+        self.retryable_status_codes.contains(&code)
+    }
+
+    /// Random delay before retry number `retry`, starting at 1, up to
+    /// `initial_backoff * backoff_multiplier^(retry - 1)` capped by `max_backoff`
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = (self.initial_backoff.as_secs_f64()
+            * self.backoff_multiplier.powi(retry as i32 - 1))
+        .min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(ceiling * fastrand::f64())
+    }
+}
@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use http::{HeaderMap, HeaderName};
+use regex::Regex;
+use tower::BoxError;
+
+use crate::config::route_config::HeaderMatchConfig;
+
+const REGEX_PREFIX: &str = "regex:";
+
+#[derive(Debug, Clone)]
+pub enum HeaderMatch {
+    Present(HeaderName),
+    Exact(HeaderName, String),
+    Regex(HeaderName, Regex),
+}
+
+impl HeaderMatch {
+    pub fn from_config(config: &HeaderMatchConfig) -> Result<Self, BoxError> {
+        let name = HeaderName::from_str(&config.name)
+            .map_err(|e| format!("Invalid route header name {:?}: {}", config.name, e))?;
+        let Some(value) = &config.value else {
+            return Ok(HeaderMatch::Present(name));
+        };
+        match value.strip_prefix(REGEX_PREFIX) {
+            Some(pattern) => {
+                let regex = Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| format!("Invalid route header regex {:?}: {}", pattern, e))?;
+                Ok(HeaderMatch::Regex(name, regex))
+            }
+            None => Ok(HeaderMatch::Exact(name, value.clone())),
+        }
+    }
+
+    /// Any value of a repeated header may match
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        match self {
+            HeaderMatch::Present(name) => headers.contains_key(name),
+            HeaderMatch::Exact(name, exact) => headers
+                .get_all(name)
+                .iter()
+                .any(|value| value.as_bytes() == exact.as_bytes()),
+            HeaderMatch::Regex(name, regex) => headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| regex.is_match(value)),
+        }
+    }
+}
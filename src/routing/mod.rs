@@ -0,0 +1,3 @@
+pub mod header_match;
+pub mod path_match;
+pub mod route_table;
@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use tower::BoxError;
+
+/// Matches the `/package.Service/Method` path of a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathMatch {
+    Any,
+    Exact(String),
+    /// `/package.Service/*` matches every method of a service,
+    /// `/package.*` every service of a package
+    Prefix(String),
+}
+
+impl PathMatch {
+    pub fn matches(&self, path: &str) -> bool {
+        match self {
+            PathMatch::Any => true,
+            PathMatch::Exact(exact) => path == exact,
+            PathMatch::Prefix(prefix) => path.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+impl FromStr for PathMatch {
+    type Err = BoxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(PathMatch::Any);
+        }
+        if !s.starts_with('/') {
+            return Err(format!("Route path {:?} must start with '/' or be '*'", s).into());
+        }
+        match s.strip_suffix('*') {
+            Some(prefix) if !prefix.contains('*') => Ok(PathMatch::Prefix(prefix.to_string())),
+            Some(_) => Err(format!("Route path {:?} may only end with '*'", s).into()),
+            None if s.contains('*') => {
+                Err(format!("Route path {:?} may only end with '*'", s).into())
+            }
+            None => Ok(PathMatch::Exact(s.to_string())),
+        }
+    }
+}
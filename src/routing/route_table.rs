@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use std::net::IpAddr;
+
+use http::HeaderMap;
+use tower::BoxError;
+
+use crate::{
+    config::{cluster_config::ClusterConfig, route_config::RouteConfig},
+    core::grpc_timeout::{GRPC_TIMEOUT, encode_grpc_timeout, parse_grpc_timeout},
+    rate_limit::rate_limiter::RateLimiter,
+    retry::{hedging_policy::HedgingPolicy, retry_policy::RetryPolicy},
+    routing::{header_match::HeaderMatch, path_match::PathMatch},
+    telemetry::metrics::Metrics,
+    upstream::cluster::Cluster,
+};
+
+pub struct Route {
+    pub path: PathMatch,
+    pub headers: Vec<HeaderMatch>,
+    pub cluster: Arc<Cluster>,
+    pub default_timeout: Option<Duration>,
+    pub max_timeout: Option<Duration>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub hedging_policy: Option<HedgingPolicy>,
+    pub rate_limiters: Vec<RateLimiter>,
+}
+
+impl Route {
+    pub fn matches(&self, path: &str, headers: &HeaderMap) -> bool {
+        self.path.matches(path) && self.headers.iter().all(|header| header.matches(headers))
+    }
+
+    /// Caps the `grpc-timeout` of a call, or sets it when missing or invalid
+    pub fn apply_timeout(&self, headers: &mut HeaderMap) {
+        let timeout = match headers.get(GRPC_TIMEOUT).and_then(parse_grpc_timeout) {
+            Some(timeout) => match self.max_timeout {
+                Some(max_timeout) if timeout > max_timeout => max_timeout,
+                _ => return,
+            },
+            None => match self.default_timeout.or(self.max_timeout) {
+                Some(timeout) => timeout,
+                None => return,
+            },
+        };
+        headers.insert(GRPC_TIMEOUT, encode_grpc_timeout(timeout));
+    }
+
+    /// Whether the call is within every rate limit of the route,
+    /// the limiters after the first one rejecting it are not charged
+    pub fn admit(&self, headers: &HeaderMap, peer: Option<IpAddr>, metrics: &Metrics) -> bool {
+        self.rate_limiters
+            .iter()
+            .all(|limiter| limiter.check(headers, peer, metrics))
+    }
+}
+
+/// Selects the cluster of a call from its path and headers
+pub struct RouteTable {
+    clusters: Vec<Arc<Cluster>>,
+    routes: Vec<Route>,
+}
+
+impl RouteTable {
+    pub fn new(clusters: &[ClusterConfig], routes: &[RouteConfig]) -> Result<Self, BoxError> {
+        let clusters = clusters
+            .iter()
+            .map(|config| Cluster::from_config(config).map(Arc::new))
+            .collect::<Result<Vec<_>, BoxError>>()?;
+        let mut by_name = HashMap::new();
+        for cluster in &clusters {
+            if by_name
+                .insert(cluster.name.clone(), cluster.clone())
+                .is_some()
+            {
+                return Err(format!("Duplicate cluster {:?}", cluster.name).into());
+            }
+        }
+
+        let routes = routes
+            .iter()
+            .map(|config| {
+                if config.retry_policy.is_some() && config.hedging_policy.is_some() {
+                    return Err(format!(
+                        "Route {:?} has both a retry and a hedging policy",
+                        config.path
+                    )
+                    .into());
+                }
+                let cluster = by_name.get(&config.cluster).ok_or_else(|| {
+                    format!(
+                        "Route {:?} refers to unknown cluster {:?}",
+                        config.path, config.cluster
+                    )
+                })?;
+                Ok(Route {
+                    path: PathMatch::from_str(&config.path)?,
+                    headers: config
+                        .headers
+                        .iter()
+                        .map(HeaderMatch::from_config)
+                        .collect::<Result<_, BoxError>>()?,
+                    cluster: cluster.clone(),
+                    default_timeout: config.default_timeout_ms.map(Duration::from_millis),
+                    max_timeout: config.max_timeout_ms.map(Duration::from_millis),
+                    retry_policy: config
+                        .retry_policy
+                        .as_ref()
+                        .map(RetryPolicy::from_config)
+                        .transpose()?,
+                    hedging_policy: config
+                        .hedging_policy
+                        .as_ref()
+                        .map(HedgingPolicy::from_config)
+                        .transpose()?,
+                    rate_limiters: config
+                        .rate_limits
+                        .iter()
+                        .map(|limit| RateLimiter::from_config(&config.path, limit))
+                        .collect::<Result<_, BoxError>>()?,
+                })
+            })
+            .collect::<Result<_, BoxError>>()?;
+
+        Ok(Self { clusters, routes })
+    }
+
+    /// Every configured cluster, including those no route refers to
+    pub fn clusters(&self) -> &[Arc<Cluster>] {
+        &self.clusters
+    }
+
+    /// The first route matching the call, in configuration order
+    pub fn find(&self, path: &str, headers: &HeaderMap) -> Option<&Route> {
+        self.routes
+            .iter()
+            .find(|route| route.matches(path, headers))
+    }
+}
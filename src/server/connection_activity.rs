@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Calls in progress on a client connection, telling when it became idle
+#[derive(Clone)]
+pub struct ConnectionActivity(Arc<Mutex<Activity>>);
+
+struct Activity {
+    active: usize,
+    idle_since: Instant,
+}
+
+/// A call counted as active until dropped
+pub struct ActiveCall(ConnectionActivity);
+
+impl Drop for ActiveCall {
+    fn drop(&mut self) {
+        let mut activity = self.0.0.lock().unwrap();
+        activity.active -= 1;
+        if activity.active == 0 {
+            activity.idle_since = Instant::now();
+        }
+    }
+}
+
+impl ConnectionActivity {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Activity {
+            active: 0,
+            idle_since: Instant::now(),
+        })))
+    }
+
+    pub fn start_call(&self) -> ActiveCall {
+        self.0.lock().unwrap().active += 1;
+        ActiveCall(self.clone())
+    }
+
+    /// When the connection will have been idle for `max_idle`,
+    /// assuming no call starts meanwhile
+    pub fn idle_deadline(&self, max_idle: Duration) -> Instant {
+        let activity = self.0.lock().unwrap();
+        if activity.active > 0 {
+            Instant::now() + max_idle
+        } else {
+            activity.idle_since + max_idle
+        }
+    }
+}
+
+impl Default for ConnectionActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
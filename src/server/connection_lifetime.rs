@@ -0,0 +1,64 @@
+use std::future::pending;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::time::{Instant, sleep_until};
+
+use crate::{config::server_config::ServerConfig, server::connection_activity::ConnectionActivity};
+
+/// When a client connection is asked to go away: past its max age,
+/// once idle for too long, or when the proxy drains
+pub struct ConnectionLifetime {
+    max_age: Option<Duration>,
+    max_age_grace: Option<Duration>,
+    max_idle: Option<Duration>,
+}
+
+impl ConnectionLifetime {
+    pub fn new(config: &ServerConfig) -> Self {
+        Self {
+            max_age: config.max_connection_age_ms.map(Duration::from_millis),
+            max_age_grace: config
+                .max_connection_age_grace_ms
+                .map(Duration::from_millis),
+            max_idle: config.max_connection_idle_ms.map(Duration::from_millis),
+        }
+    }
+
+    /// Resolves once a connection opened now should be shut down gracefully,
+    /// with the time left to its calls to complete, unlimited when `None`
+    pub async fn closing(
+        &self,
+        activity: &ConnectionActivity,
+        mut drain: watch::Receiver<bool>,
+    ) -> Option<Duration> {
+        let max_age = self.max_age.map(|max_age| Instant::now() + max_age);
+        loop {
+            let idle_deadline = self
+                .max_idle
+                .map(|max_idle| activity.idle_deadline(max_idle));
+            tokio::select! {
+                // a dropped sender drains the connection as well
+                _ = drain.wait_for(|draining| *draining) => return None,
+                _ = at(max_age) => return self.max_age_grace,
+                _ = at(idle_deadline) => {
+                    // a call may have started meanwhile
+                    let now = Instant::now();
+                    if self
+                        .max_idle
+                        .is_some_and(|max_idle| activity.idle_deadline(max_idle) <= now)
+                    {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn at(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => sleep_until(deadline).await,
+        None => pending().await,
+    }
+}
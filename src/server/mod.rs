@@ -0,0 +1,3 @@
+pub mod connection_activity;
+pub mod connection_lifetime;
+pub mod server_builder;
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use hyper_util::rt::{TokioExecutor, TokioTimer};
+use hyper_util::server::conn::auto::Builder as AutoBuilder;
+
+use tower::BoxError;
+
+use crate::config::{http2_config::Http2Config, proxy_config::ProxyConfig};
+use crate::server::connection_lifetime::ConnectionLifetime;
+
+/// Largest window size allowed by HTTP/2
+const MAX_WINDOW_SIZE: u32 = (1 << 31) - 1;
+/// Smallest and largest SETTINGS_MAX_FRAME_SIZE allowed by HTTP/2
+const MIN_FRAME_SIZE: u32 = 1 << 14;
+const MAX_FRAME_SIZE: u32 = (1 << 24) - 1;
+
+/// How the client connections accepted from now on are served
+pub struct ServerSettings {
+    pub builder: AutoBuilder<TokioExecutor>,
+    pub lifetime: ConnectionLifetime,
+}
+
+impl ServerSettings {
+    pub fn new(config: &ProxyConfig) -> Result<Self, BoxError> {
+        Ok(Self {
+            builder: server_builder(config)?,
+            lifetime: ConnectionLifetime::new(&config.server),
+        })
+    }
+}
+
+/// HTTP/1 and HTTP/2 settings of the client connections
+pub fn server_builder(config: &ProxyConfig) -> Result<AutoBuilder<TokioExecutor>, BoxError> {
+    check_http2(&config.server.http2)?;
+    let mut builder = AutoBuilder::new(TokioExecutor::new());
+    let http1 = &config.server.http1;
+    let mut http1_builder = builder.http1();
+    http1_builder
+        .timer(TokioTimer::new())
+        .keep_alive(http1.keep_alive)
+        .header_read_timeout(Duration::from_millis(http1.header_read_timeout_ms));
+    if let Some(max) = http1.max_buf_size {
+        http1_builder.max_buf_size(max);
+    }
+
+    let http2 = &config.server.http2;
+    let mut http2_builder = builder.http2();
+    http2_builder
+        .timer(TokioTimer::new())
+        .initial_stream_window_size(http2.initial_stream_window_size)
+        .initial_connection_window_size(http2.initial_connection_window_size)
+        .adaptive_window(http2.adaptive_window)
+        .max_frame_size(http2.max_frame_size)
+        .keep_alive_interval(http2.keep_alive_interval_ms.map(Duration::from_millis))
+        .keep_alive_timeout(Duration::from_millis(http2.keep_alive_timeout_ms));
+    if let Some(max) = http2.max_header_list_size {
+        http2_builder.max_header_list_size(max);
+    }
+    // hyper allows 200 streams by default
+    if let Some(max) = config.limits.max_concurrent_streams_per_connection {
+        http2_builder.max_concurrent_streams(max);
+    }
+    Ok(builder)
+}
+
+/// Rejects the HTTP/2 settings out of the ranges allowed by the protocol,
+/// which hyper would panic on
+pub fn check_http2(config: &Http2Config) -> Result<(), BoxError> {
+    for (name, size) in [
+        (
+            "initial_stream_window_size",
+            config.initial_stream_window_size,
+        ),
+        (
+            "initial_connection_window_size",
+            config.initial_connection_window_size,
+        ),
+    ] {
+        if size.is_some_and(|size| size > MAX_WINDOW_SIZE) {
+            return Err(format!("{} must be at most {}", name, MAX_WINDOW_SIZE).into());
+        }
+    }
+    if config
+        .max_frame_size
+        .is_some_and(|size| !(MIN_FRAME_SIZE..=MAX_FRAME_SIZE).contains(&size))
+    {
+        return Err(format!(
+            "max_frame_size must be between {} and {}",
+            MIN_FRAME_SIZE, MAX_FRAME_SIZE
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -4,7 +4,9 @@ use async_stream::try_stream;
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full, StreamBody};
 use prometheus::{
-    CounterVec, Encoder, HistogramVec, TextEncoder, register_counter_vec, register_histogram_vec,
+    CounterVec, Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    TextEncoder, register_counter_vec, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
 };
 
 use crate::core::stream_response::StreamResponse;
@@ -12,6 +14,25 @@ use crate::core::stream_response::StreamResponse;
 pub struct Metrics {
     pub requests_total: CounterVec,
     pub request_duration: HistogramVec,
+    pub upstream_connections: IntGaugeVec,
+    pub upstream_streams_in_use: IntGaugeVec,
+    pub lb_selections: IntCounterVec,
+    pub endpoint_requests: IntCounterVec,
+    pub endpoint_in_flight: IntGaugeVec,
+    pub endpoint_healthy: IntGaugeVec,
+    pub retries: IntCounterVec,
+    pub hedges: IntCounterVec,
+    pub hedge_wins: IntCounterVec,
+    pub circuit_breaker_overflows: IntCounterVec,
+    pub outlier_ejections: IntCounterVec,
+    pub endpoint_ejected: IntGaugeVec,
+    pub rate_limit_decisions: IntCounterVec,
+    pub downstream_connections: IntGauge,
+    pub downstream_in_flight: IntGauge,
+    pub downstream_queued: IntGauge,
+    pub downstream_shed: IntCounter,
+    pub config_reloads: IntCounter,
+    pub config_reload_failures: IntCounter,
 }
 
 impl Metrics {
@@ -29,6 +50,114 @@ impl Metrics {
                 &["method", "path"]
             )
             .unwrap(),
+            upstream_connections: register_int_gauge_vec!(
+                "upstream_pool_connections",
+                "Open HTTP/2 connections to the upstream server",
+                &["authority"]
+            )
+            .unwrap(),
+            upstream_streams_in_use: register_int_gauge_vec!(
+                "upstream_pool_streams_in_use",
+                "Streams currently in use on pooled upstream connections",
+                &["authority"]
+            )
+            .unwrap(),
+            lb_selections: register_int_counter_vec!(
+                "upstream_lb_selections_total",
+                "Endpoints picked by the load balancer of a cluster",
+                &["cluster", "policy", "endpoint"]
+            )
+            .unwrap(),
+            endpoint_requests: register_int_counter_vec!(
+                "upstream_endpoint_requests_total",
+                "Calls sent to an upstream endpoint",
+                &["cluster", "endpoint"]
+            )
+            .unwrap(),
+            endpoint_in_flight: register_int_gauge_vec!(
+                "upstream_endpoint_requests_in_flight",
+                "Calls to an upstream endpoint awaiting their response",
+                &["cluster", "endpoint"]
+            )
+            .unwrap(),
+            endpoint_healthy: register_int_gauge_vec!(
+                "upstream_endpoint_healthy",
+                "1 when the endpoint passes its health checks, 0 otherwise",
+                &["cluster", "endpoint"]
+            )
+            .unwrap(),
+            retries: register_int_counter_vec!(
+                "upstream_retries_total",
+                "Calls sent again after an attempt failed with a retryable status",
+                &["cluster", "code"]
+            )
+            .unwrap(),
+            hedges: register_int_counter_vec!(
+                "upstream_hedges_total",
+                "Hedged attempts sent while the previous attempts of a call were pending",
+                &["cluster"]
+            )
+            .unwrap(),
+            hedge_wins: register_int_counter_vec!(
+                "upstream_hedge_wins_total",
+                "Calls answered by a hedged attempt rather than the first one",
+                &["cluster"]
+            )
+            .unwrap(),
+            circuit_breaker_overflows: register_int_counter_vec!(
+                "upstream_circuit_breaker_overflows_total",
+                "Calls rejected because a circuit breaker limit of the cluster was reached",
+                &["cluster", "limit"]
+            )
+            .unwrap(),
+            outlier_ejections: register_int_counter_vec!(
+                "upstream_outlier_ejections_total",
+                "Endpoints ejected by outlier detection",
+                &["cluster", "endpoint"]
+            )
+            .unwrap(),
+            endpoint_ejected: register_int_gauge_vec!(
+                "upstream_endpoint_ejected",
+                "1 while outlier detection keeps the endpoint out of rotation, 0 otherwise",
+                &["cluster", "endpoint"]
+            )
+            .unwrap(),
+            rate_limit_decisions: register_int_counter_vec!(
+                "rate_limit_decisions_total",
+                "Calls allowed or limited by a rate limiter of a route",
+                &["route", "limiter", "decision"]
+            )
+            .unwrap(),
+            downstream_connections: register_int_gauge!(
+                "downstream_connections",
+                "Client connections open"
+            )
+            .unwrap(),
+            downstream_in_flight: register_int_gauge!(
+                "downstream_requests_in_flight",
+                "Calls being handled"
+            )
+            .unwrap(),
+            downstream_queued: register_int_gauge!(
+                "downstream_requests_queued",
+                "Calls waiting for others to complete, over max_in_flight"
+            )
+            .unwrap(),
+            downstream_shed: register_int_counter!(
+                "downstream_requests_shed_total",
+                "Calls failed with UNAVAILABLE as too many were in flight"
+            )
+            .unwrap(),
+            config_reloads: register_int_counter!(
+                "config_reloads_total",
+                "Configurations reloaded and applied"
+            )
+            .unwrap(),
+            config_reload_failures: register_int_counter!(
+                "config_reload_failures_total",
+                "Configurations rejected on reload, the previous one staying in use"
+            )
+            .unwrap(),
         }
     }
 
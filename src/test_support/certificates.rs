@@ -0,0 +1,37 @@
+use std::path::{Path, PathBuf};
+
+use rustls_pki_types::CertificateDer;
+
+pub struct TestCertificate {
+    pub cert_pem: String,
+    pub key_pem: String,
+    pub cert_der: CertificateDer<'static>,
+}
+
+// self-signed certificate generated at test time
+pub fn self_signed(names: &[&str]) -> TestCertificate {
+    let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    let certified = rcgen::generate_simple_self_signed(names).unwrap();
+    TestCertificate {
+        cert_pem: certified.cert.pem(),
+        key_pem: certified.signing_key.serialize_pem(),
+        cert_der: certified.cert.der().clone(),
+    }
+}
+
+// empty directory unique to the test process
+pub fn temp_dir(label: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("griffin-{}-{}", label, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// write the certificate and key as `<name>.crt` and `<name>.key`
+pub fn write_pem(dir: &Path, name: &str, certificate: &TestCertificate) -> (PathBuf, PathBuf) {
+    let cert_path = dir.join(format!("{}.crt", name));
+    let key_path = dir.join(format!("{}.key", name));
+    std::fs::write(&cert_path, &certificate.cert_pem).unwrap();
+    std::fs::write(&key_path, &certificate.key_pem).unwrap();
+    (cert_path, key_path)
+}
@@ -2,12 +2,15 @@ use futures_util::StreamExt;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::metadata::MetadataMap;
 use tonic::{Code, Streaming};
-use tonic::{Request, Response, Status, transport::Server};
+use tonic::{Request, Response, Status};
 // use tonic_reflection::server::Builder as ReflectionBuilder;
 
-use hello_world::greeter_server::{Greeter, GreeterServer};
+use hello_world::greeter_server::Greeter;
 use hello_world::{HelloReply, HelloRequest};
 // pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("helloworld_descriptor");
+/// The descriptor set of helloworld.proto and its imports, with the
+/// `google.api.http` rules of the Greeter service
+pub const DESCRIPTOR_SET_PATH: &str = concat!(env!("OUT_DIR"), "/helloworld_descriptor.bin");
 pub mod hello_world {
     tonic::include_proto!("helloworld");
 }
@@ -55,7 +58,7 @@ impl Greeter for MyGreeter {
         request: Request<HelloRequest>,
     ) -> Result<Response<Self::SayHelloStreamStream>, Status> {
         // let name = request.into_inner().message().await?.unwrap().name;
-        let name = request.into_inner().name;
+        let _name = request.into_inner().name;
 
         // Create a channel to send streaming replies
         let (tx, rx) = tokio::sync::mpsc::channel(4);
@@ -117,30 +120,25 @@ impl Greeter for MyGreeter {
             while let Some(result) = inbound.next().await {
                 if let Ok(req) = result {
                     println!("Got a request: {:?}", req);
-                    if req.name == "client request 1" {
-                        if tx
+                    if req.name == "client request 1"
+                        && tx
                             .send(Ok(HelloReply {
                                 message: "first ok".into(),
                             }))
                             .await
                             .is_err()
-                        {
-                            return;
-                        }
+                    {
+                        return;
                     }
-                    match req.name == "client request 2" {
-                        true => {
-                            if tx
-                                .send(Ok(HelloReply {
-                                    message: "second ok".into(),
-                                }))
-                                .await
-                                .is_err()
-                            {
-                                return;
-                            }
-                        }
-                        false => (),
+                    if req.name == "client request 2"
+                        && tx
+                            .send(Ok(HelloReply {
+                                message: "second ok".into(),
+                            }))
+                            .await
+                            .is_err()
+                    {
+                        return;
                     }
                 }
                 // // Send one message first
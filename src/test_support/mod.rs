@@ -1,4 +1,6 @@
+pub mod certificates;
 pub mod greeter;
 pub mod preparation;
 pub mod proto_message;
+pub mod scripted_backend;
 pub mod utils;
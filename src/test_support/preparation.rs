@@ -1,15 +1,10 @@
-use futures_util::StreamExt;
-use tokio::{
-    sync::{
-        oneshot::{self, Sender},
-        watch,
-    },
-    task::JoinHandle,
-};
-use tonic::Request;
+use tokio::{sync::oneshot, task::JoinHandle};
+use tonic::transport::ServerTlsConfig;
+use tonic_health::server::HealthReporter;
 use tower::BoxError;
 
 use crate::{
+    config::proxy_config::ProxyConfig,
     start_proxy,
     test_support::greeter::{MyGreeter, hello_world::greeter_server::GreeterServer},
 };
@@ -19,40 +14,111 @@ where
     F: Fn(String) -> Fut,
     Fut: Future<Output = Result<(), BoxError>>,
 {
-    let mock = MyGreeter {};
+    run_intergration_with_config(|_| {}, call).await
+}
 
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-    let forward_address = listener.local_addr().unwrap().to_string();
-    let (backend_shutdown_tx, backend_shutdown_rx) = oneshot::channel::<()>();
-    // start mock server
-    let backend_task = tokio::spawn(async move {
-        tonic::transport::Server::builder()
-            .add_service(GreeterServer::new(mock))
-            .serve_with_incoming_shutdown(
-                tokio_stream::wrappers::TcpListenerStream::new(listener),
-                async {
-                    backend_shutdown_rx.await.ok();
-                },
-            )
-            .await
-            .unwrap();
-    });
+// same as run_intergration, with the proxy config adjusted by `configure`
+pub async fn run_intergration_with_config<C, F, Fut>(configure: C, call: F) -> Result<(), BoxError>
+where
+    C: FnOnce(&mut ProxyConfig),
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), BoxError>>,
+{
+    run_intergration_with_backend(None, configure, call).await
+}
+
+// same as run_intergration_with_config, with a mock server serving TLS
+pub async fn run_intergration_with_backend_tls<C, F, Fut>(
+    tls: ServerTlsConfig,
+    configure: C,
+    call: F,
+) -> Result<(), BoxError>
+where
+    C: FnOnce(&mut ProxyConfig),
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), BoxError>>,
+{
+    run_intergration_with_backend(Some(tls), configure, call).await
+}
+
+async fn run_intergration_with_backend<C, F, Fut>(
+    tls: Option<ServerTlsConfig>,
+    configure: C,
+    call: F,
+) -> Result<(), BoxError>
+where
+    C: FnOnce(&mut ProxyConfig),
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), BoxError>>,
+{
+    let backend = MockBackend::start(tls).await;
 
     let (proxy_shutdown_tx, proxy_shutdown_rx) = tokio::sync::watch::channel(false);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let proxy_address = listener.local_addr().unwrap().to_string();
-    let proxy_task = tokio::spawn(start_proxy(listener, forward_address, proxy_shutdown_rx));
+    let mut config = ProxyConfig::new(backend.address.clone());
+    configure(&mut config);
+    let proxy_task = tokio::spawn(start_proxy(listener, config, proxy_shutdown_rx));
 
     call(proxy_address).await.unwrap();
 
     proxy_shutdown_tx.send(true).unwrap();
-    backend_shutdown_tx.send(()).unwrap();
 
     // wait until both tasks are finished
     // with shutdown server
     let _ = proxy_task.await.unwrap();
-    backend_task.await.unwrap();
+    backend.stop().await;
 
     Ok(())
 }
+
+// mock greeter server listening on a random port,
+// with a grpc.health.v1 service reporting helloworld.Greeter as serving
+pub struct MockBackend {
+    pub address: String,
+    pub health: HealthReporter,
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl MockBackend {
+    pub async fn start(tls: Option<ServerTlsConfig>) -> MockBackend {
+        let mock = MyGreeter {};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (health, health_service) = tonic_health::server::health_reporter();
+        health.set_serving::<GreeterServer<MyGreeter>>().await;
+        let mut server = tonic::transport::Server::builder();
+        if let Some(tls) = tls {
+            server = server.tls_config(tls).unwrap();
+        }
+        // start mock server
+        let task = tokio::spawn(async move {
+            server
+                .add_service(GreeterServer::new(mock))
+                .add_service(health_service)
+                .serve_with_incoming_shutdown(
+                    tokio_stream::wrappers::TcpListenerStream::new(listener),
+                    async {
+                        shutdown_rx.await.ok();
+                    },
+                )
+                .await
+                .unwrap();
+        });
+        MockBackend {
+            address,
+            health,
+            shutdown_tx,
+            task,
+        }
+    }
+
+    pub async fn stop(self) {
+        self.shutdown_tx.send(()).unwrap();
+        self.task.await.unwrap();
+    }
+}
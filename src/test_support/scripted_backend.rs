@@ -0,0 +1,165 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, Request, Response};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::{body::Incoming, server::conn::http2};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use prost::Message;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{
+    compression::encoding::{Encoding, GRPC_ENCODING},
+    test_support::{
+        greeter::hello_world::{HelloReply, HelloRequest},
+        utils::encoded_message_to_frame,
+    },
+};
+
+/// What the scripted backend answers to a call
+pub enum Reply {
+    /// A `HelloReply` with this message, then an OK status
+    Message(String),
+    /// A Trailers-Only response with this status code
+    Status(i32),
+    /// Response headers, then trailers with this status code
+    Trailers(i32),
+    /// The reply after a delay
+    Delayed(Duration, Box<Reply>),
+    /// The reply with its message compressed in this encoding
+    Compressed(Encoding, Box<Reply>),
+}
+
+/// Decides the reply from the index of the call, its headers and request
+pub type Script = dyn Fn(usize, &HeaderMap, &HelloRequest) -> Reply + Send + Sync;
+
+/// A raw HTTP/2 gRPC server answering unary calls as scripted,
+/// recording the headers of every call it receives.
+pub struct ScriptedBackend {
+    pub address: String,
+    calls: Arc<Mutex<Vec<HeaderMap>>>,
+    task: JoinHandle<()>,
+}
+
+impl ScriptedBackend {
+    pub async fn start(
+        script: impl Fn(usize, &HeaderMap, &HelloRequest) -> Reply + Send + Sync + 'static,
+    ) -> ScriptedBackend {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let script: Arc<Script> = Arc::new(script);
+
+        let task = {
+            let calls = calls.clone();
+            tokio::spawn(async move {
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else {
+                        return;
+                    };
+                    let calls = calls.clone();
+                    let script = script.clone();
+                    tokio::spawn(async move {
+                        let svc = hyper::service::service_fn(move |req| {
+                            handle(req, calls.clone(), script.clone())
+                        });
+                        let _ = http2::Builder::new(TokioExecutor::new())
+                            .serve_connection(TokioIo::new(stream), svc)
+                            .await;
+                    });
+                }
+            })
+        };
+        ScriptedBackend {
+            address,
+            calls,
+            task,
+        }
+    }
+
+    /// Headers of the calls received so far
+    pub fn calls(&self) -> Vec<HeaderMap> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+type ReplyBody =
+    StreamBody<futures_util::stream::Iter<std::vec::IntoIter<Result<Frame<Bytes>, Infallible>>>>;
+
+async fn handle(
+    req: Request<Incoming>,
+    calls: Arc<Mutex<Vec<HeaderMap>>>,
+    script: Arc<Script>,
+) -> Result<Response<ReplyBody>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = body.collect().await.map(|body| body.to_bytes());
+    let encoding = Encoding::from_headers(&parts.headers).unwrap_or(Encoding::Identity);
+    let request = body
+        .ok()
+        .filter(|body| body.len() >= 5)
+        .and_then(|body| match body[0] {
+            0 => Some(body.slice(5..)),
+            _ => encoding
+                .decompress(&body[5..], 4 << 20)
+                .ok()
+                .map(Bytes::from),
+        })
+        .and_then(|message| HelloRequest::decode(message).ok())
+        .unwrap_or_default();
+    let index = {
+        let mut calls = calls.lock().unwrap();
+        calls.push(parts.headers.clone());
+        calls.len() - 1
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", HeaderValue::from_static("application/grpc"));
+    let mut encoding = Encoding::Identity;
+    let mut reply = script(index, &parts.headers, &request);
+    let reply = loop {
+        reply = match reply {
+            Reply::Delayed(delay, next) => {
+                tokio::time::sleep(delay).await;
+                *next
+            }
+            Reply::Compressed(compression, next) => {
+                encoding = compression;
+                headers.insert(GRPC_ENCODING, HeaderValue::from_static(encoding.name()));
+                *next
+            }
+            reply => break reply,
+        };
+    };
+    let frames = match reply {
+        Reply::Message(message) => {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("grpc-status", HeaderValue::from(0));
+            vec![
+                Frame::data(encoded_message_to_frame(&HelloReply { message }, encoding).freeze()),
+                Frame::trailers(trailers),
+            ]
+        }
+        Reply::Status(code) => {
+            headers.insert("grpc-status", HeaderValue::from(code));
+            Vec::new()
+        }
+        Reply::Trailers(code) => {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("grpc-status", HeaderValue::from(code));
+            vec![Frame::trailers(trailers)]
+        }
+        Reply::Delayed(..) | Reply::Compressed(..) => unreachable!(),
+    };
+    let mut res = Response::new(StreamBody::new(futures_util::stream::iter(
+        frames.into_iter().map(Ok).collect::<Vec<_>>(),
+    )));
+    *res.headers_mut() = headers;
+    Ok(res)
+}
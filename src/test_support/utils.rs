@@ -5,12 +5,25 @@ use http_body_util::StreamBody;
 use prost::Message;
 
 use futures_util::StreamExt;
-use prost::DecodeError;
+use tower::BoxError;
+
+use crate::compression::encoding::Encoding;
 
 //collect protobuf messages from stream body
 pub async fn collect_messages<M>(
+    body: StreamBody<impl Stream<Item = Result<Frame<Bytes>, hyper::Error>> + Send + Unpin>,
+) -> Result<Vec<M>, BoxError>
+where
+    M: Message + Default,
+{
+    collect_encoded_messages(body, Encoding::Identity).await
+}
+
+// collect protobuf messages, decompressing those flagged as compressed
+pub async fn collect_encoded_messages<M>(
     mut body: StreamBody<impl Stream<Item = Result<Frame<Bytes>, hyper::Error>> + Send + Unpin>,
-) -> Result<Vec<M>, DecodeError>
+    encoding: Encoding,
+) -> Result<Vec<M>, BoxError>
 where
     M: Message + Default,
 {
@@ -28,8 +41,7 @@ where
                 let mut reader = &buf[..];
 
                 //https://datatracker.ietf.org/doc/html/rfc7540#section-4.1
-                //TODO: support compression
-                let _compressed_flag = reader.get_u8();
+                let compressed_flag = reader.get_u8();
                 // Length: 24-bit, type =8-bit
                 let msg_len = reader.get_u32() as usize;
 
@@ -42,7 +54,11 @@ where
                 // by ignoring the first 5 bytes
                 let msg_bytes = &buf[5..5 + msg_len];
                 // bytes to message
-                let msg = M::decode(msg_bytes)?;
+                let msg = if compressed_flag & 1 == 1 {
+                    M::decode(&encoding.decompress(msg_bytes, 64 << 20)?[..])?
+                } else {
+                    M::decode(msg_bytes)?
+                };
 
                 // add new message to list
                 messages.push(msg);
@@ -58,12 +74,19 @@ where
 
 // convert message to frame
 pub fn message_to_frame(message: &impl Message) -> BytesMut {
-    let mut buf = BytesMut::new();
-    message.encode(&mut buf).unwrap();
+    encoded_message_to_frame(message, Encoding::Identity)
+}
+
+// convert message to frame, compressed unless the encoding is identity
+pub fn encoded_message_to_frame(message: &impl Message, encoding: Encoding) -> BytesMut {
+    let mut buf = message.encode_to_vec();
+    let compressed = encoding != Encoding::Identity;
+    if compressed {
+        buf = encoding.compress(&buf).unwrap();
+    }
 
     let mut framed = BytesMut::new();
-    //TODO support compression
-    framed.put_u8(0); // 0: not compressed
+    framed.put_u8(compressed.into()); // 0: not compressed, 1: compressed
     framed.put_u32(buf.len() as u32);
     framed.put_slice(&buf);
     framed
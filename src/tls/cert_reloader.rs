@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use rustls::crypto::CryptoProvider;
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use tower::BoxError;
+
+use crate::config::tls_config::TlsConfig;
+
+/// Serves the certificate found on disk, and picks up new versions of the
+/// certificate and key files without restarting the listener.
+///
+/// Handshakes always use the latest certificate that was loaded successfully,
+/// connections already established keep the one they were opened with.
+#[derive(Debug)]
+pub struct CertReloader {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    provider: Arc<CryptoProvider>,
+    current: RwLock<Arc<CertifiedKey>>,
+    modified: Mutex<(SystemTime, SystemTime)>,
+}
+
+impl CertReloader {
+    pub fn new(config: &TlsConfig, provider: Arc<CryptoProvider>) -> Result<Self, BoxError> {
+        let modified = (
+            modified_time(&config.cert_path)?,
+            modified_time(&config.key_path)?,
+        );
+        let current = load_certified_key(&config.cert_path, &config.key_path, &provider)?;
+        Ok(Self {
+            cert_path: config.cert_path.clone(),
+            key_path: config.key_path.clone(),
+            provider,
+            current: RwLock::new(Arc::new(current)),
+            modified: Mutex::new(modified),
+        })
+    }
+
+    /// Reloads the certificate when one of the files has been modified.
+    ///
+    /// On failure the previous certificate stays in use,
+    /// and loading is attempted again on the next call.
+    pub fn reload_if_changed(&self) -> Result<bool, BoxError> {
+        let modified = (
+            modified_time(&self.cert_path)?,
+            modified_time(&self.key_path)?,
+        );
+        if *self.modified.lock().unwrap() == modified {
+            return Ok(false);
+        }
+
+        let certified_key = load_certified_key(&self.cert_path, &self.key_path, &self.provider)?;
+        *self.current.write().unwrap() = Arc::new(certified_key);
+        *self.modified.lock().unwrap() = modified;
+        Ok(true)
+    }
+
+    /// Polls the certificate files every `interval`
+    pub async fn watch(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match self.reload_if_changed() {
+                Ok(true) => println!("TLS certificate reloaded from {:?}", self.cert_path),
+                Ok(false) => {}
+                Err(err) => eprintln!("Failed to reload TLS certificate: {}", err),
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for CertReloader {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+fn modified_time(path: &Path) -> Result<SystemTime, BoxError> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("Cannot read {:?}: {}", path, e).into())
+}
+
+pub fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, BoxError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Invalid certificate file {:?}: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificate found in {:?}", path).into());
+    }
+    Ok(certs)
+}
+
+pub fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, BoxError> {
+    PrivateKeyDer::from_pem_file(path)
+        .map_err(|e| format!("Invalid private key file {:?}: {}", path, e).into())
+}
+
+fn load_certified_key(
+    cert_path: &Path,
+    key_path: &Path,
+    provider: &CryptoProvider,
+) -> Result<CertifiedKey, BoxError> {
+    let certs = load_certs(cert_path)?;
+    let key = load_private_key(key_path)?;
+    // also rejects a key that does not match the certificate,
+    // which happens while the files are being replaced one after the other
+    Ok(CertifiedKey::from_der(certs, key, provider)?)
+}
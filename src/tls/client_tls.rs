@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use http::uri::Authority;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsConnector, client::TlsStream};
+use tower::BoxError;
+
+use crate::{
+    config::upstream_tls_config::UpstreamTlsConfig,
+    tls::cert_reloader::{load_certs, load_private_key},
+};
+
+/// TLS client settings for one upstream, negotiating `h2` with ALPN.
+pub struct ClientTls {
+    connector: TlsConnector,
+    server_name: Option<ServerName<'static>>,
+}
+
+impl ClientTls {
+    pub fn new(config: &UpstreamTlsConfig) -> Result<Self, BoxError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?;
+
+        let builder = if config.insecure_skip_verify {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SkipServerVerification(provider)))
+        } else {
+            builder.with_root_certificates(root_store(config)?)
+        };
+
+        let mut client_config = match (&config.client_cert_path, &config.client_key_path) {
+            (Some(cert_path), Some(key_path)) => builder
+                .with_client_auth_cert(load_certs(cert_path)?, load_private_key(key_path)?)?,
+            (None, None) => builder.with_no_client_auth(),
+            _ => return Err("Upstream client certificate and key must be set together".into()),
+        };
+        client_config.alpn_protocols = vec![b"h2".to_vec()];
+
+        let server_name = config
+            .server_name
+            .as_ref()
+            .map(|name| ServerName::try_from(name.clone()))
+            .transpose()?;
+
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(client_config)),
+            server_name,
+        })
+    }
+
+    pub async fn connect(
+        &self,
+        authority: &Authority,
+        stream: TcpStream,
+    ) -> Result<TlsStream<TcpStream>, BoxError> {
+        let server_name = match &self.server_name {
+            Some(server_name) => server_name.clone(),
+            None => ServerName::try_from(authority.host().to_string())?,
+        };
+        let stream = self.connector.connect(server_name, stream).await?;
+
+        let (_, session) = stream.get_ref();
+        if session.alpn_protocol() != Some(b"h2") {
+            return Err(format!("Upstream {} did not negotiate h2 with ALPN", authority).into());
+        }
+        Ok(stream)
+    }
+}
+
+fn root_store(config: &UpstreamTlsConfig) -> Result<RootCertStore, BoxError> {
+    let mut roots = RootCertStore::empty();
+    match &config.ca_path {
+        Some(ca_path) => {
+            for cert in load_certs(ca_path)? {
+                roots.add(cert)?;
+            }
+        }
+        None => {
+            let native = rustls_native_certs::load_native_certs();
+            for err in &native.errors {
+                eprintln!("Failed to load a system root certificate: {}", err);
+            }
+            roots.add_parsable_certificates(native.certs);
+        }
+    }
+    Ok(roots)
+}
+
+/// Accepts any server certificate while still checking handshake signatures
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -0,0 +1,3 @@
+pub mod cert_reloader;
+pub mod client_tls;
+pub mod server_tls;
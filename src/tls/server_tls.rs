@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use tower::BoxError;
+
+use crate::{config::tls_config::TlsConfig, tls::cert_reloader::CertReloader};
+
+/// TLS termination for the proxy listener.
+///
+/// ALPN advertises both `h2` and `http/1.1`,
+/// leaving the choice of protocol to the client.
+pub struct ServerTls {
+    pub acceptor: TlsAcceptor,
+    pub reloader: Arc<CertReloader>,
+    pub reload_interval: Duration,
+}
+
+impl ServerTls {
+    pub fn new(config: &TlsConfig) -> Result<Self, BoxError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let reloader = Arc::new(CertReloader::new(config, provider.clone())?);
+
+        let mut server_config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_cert_resolver(reloader.clone());
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            reloader,
+            reload_interval: Duration::from_millis(config.reload_interval_ms),
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use http::Method;
+use prost_reflect::{DynamicMessage, ExtensionDescriptor, MessageDescriptor, MethodDescriptor};
+use tower::BoxError;
+
+use crate::transcoding::path_template::PathTemplate;
+
+/// The full name of the method option holding the HTTP rule
+pub const HTTP_EXTENSION: &str = "google.api.http";
+
+/// One HTTP binding of a method, from its `google.api.http` option
+#[derive(Debug, Clone)]
+pub struct HttpRule {
+    /// Any method when unset, for a `custom` pattern of kind `*`
+    pub method: Option<Method>,
+    pub template: PathTemplate,
+    /// Request field set from the JSON body, `*` for the whole message
+    pub body: Option<String>,
+    /// Response field sent as the JSON body instead of the whole message
+    pub response_body: Option<String>,
+}
+
+impl HttpRule {
+    /// The bindings of `method`, its additional bindings included,
+    /// none when it has no `google.api.http` option
+    pub fn for_method(
+        method: &MethodDescriptor,
+        extension: &ExtensionDescriptor,
+    ) -> Result<Vec<HttpRule>, BoxError> {
+        let options = method.options();
+        if !options.has_extension(extension) {
+            return Ok(Vec::new());
+        }
+        let Some(rule) = options.get_extension(extension).as_message().cloned() else {
+            return Ok(Vec::new());
+        };
+        let mut rules = vec![HttpRule::parse(&rule, &method.input())?];
+        if let Some(additional) = rule
+            .get_field_by_name("additional_bindings")
+            .and_then(|bindings| bindings.as_list().map(<[_]>::to_vec))
+        {
+            for binding in additional.iter().filter_map(|binding| binding.as_message()) {
+                rules.push(HttpRule::parse(binding, &method.input())?);
+            }
+        }
+        Ok(rules)
+    }
+
+    fn parse(rule: &DynamicMessage, input: &MessageDescriptor) -> Result<Self, BoxError> {
+        let string = |name: &str| {
+            rule.get_field_by_name(name)
+                .and_then(|value| value.as_str().map(str::to_string))
+                .filter(|value| !value.is_empty())
+        };
+        let pattern = [
+            ("get", Method::GET),
+            ("put", Method::PUT),
+            ("post", Method::POST),
+            ("delete", Method::DELETE),
+            ("patch", Method::PATCH),
+        ]
+        .into_iter()
+        .find_map(|(name, method)| string(name).map(|path| (Some(method), path)));
+        let (method, path) = match pattern {
+            Some(pattern) => pattern,
+            None => {
+                let custom = rule
+                    .get_field_by_name("custom")
+                    .and_then(|custom| custom.as_message().cloned())
+                    .ok_or("HTTP rule without pattern")?;
+                let field = |name| {
+                    custom
+                        .get_field_by_name(name)
+                        .and_then(|value| value.as_str().map(str::to_string))
+                        .unwrap_or_default()
+                };
+                let method = match field("kind").as_str() {
+                    "*" => None,
+                    kind => Some(Method::from_str(kind)?),
+                };
+                (method, field("path"))
+            }
+        };
+
+        let rule = HttpRule {
+            method,
+            template: PathTemplate::from_str(&path)?,
+            body: string("body"),
+            response_body: string("response_body"),
+        };
+        for field_path in rule.template.field_paths() {
+            check_field_path(input, field_path)?;
+        }
+        if let Some(body) = rule.body.as_deref().filter(|body| *body != "*") {
+            check_field_path(input, body)?;
+        }
+        Ok(rule)
+    }
+}
+
+/// Fails unless `field_path` names a field of `message`, through message fields
+fn check_field_path(message: &MessageDescriptor, field_path: &str) -> Result<(), BoxError> {
+    let mut message = message.clone();
+    let mut fields = field_path.split('.').peekable();
+    while let Some(name) = fields.next() {
+        let field = message
+            .get_field_by_name(name)
+            .ok_or_else(|| format!("No field {:?} in {}", field_path, message.full_name()))?;
+        if fields.peek().is_some() {
+            message = field
+                .kind()
+                .as_message()
+                .cloned()
+                .ok_or_else(|| format!("Field {:?} is not a message", name))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use http::Method;
+use percent_encoding::percent_decode_str;
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, MethodDescriptor,
+};
+use serde_json::{Map, Value};
+use tower::BoxError;
+
+use crate::{
+    config::transcoding_config::TranscodingConfig,
+    transcoding::http_rule::{HTTP_EXTENSION, HttpRule},
+};
+
+/// Maps REST requests onto the unary methods annotated with `google.api.http`,
+/// and knows the messages of every method for grpc-web+json
+pub struct JsonTranscoder {
+    bindings: Vec<(HttpRule, MethodDescriptor)>,
+    /// Methods by `/package.Service/Method` path
+    methods: HashMap<String, MethodDescriptor>,
+}
+
+/// A REST request matching the HTTP rule of a method
+pub struct JsonCall<'a> {
+    rule: &'a HttpRule,
+    method: &'a MethodDescriptor,
+    /// Field paths and values captured by the path template
+    variables: Vec<(String, String)>,
+}
+
+impl JsonTranscoder {
+    pub fn from_config(config: &TranscodingConfig) -> Result<Self, BoxError> {
+        let path = &config.descriptor_set_path;
+        let bytes = std::fs::read(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        let pool = DescriptorPool::decode(bytes.as_slice())
+            .map_err(|err| format!("Invalid descriptor set {}: {}", path.display(), err))?;
+        let extension = pool
+            .get_extension_by_name(HTTP_EXTENSION)
+            .ok_or_else(|| format!("{} does not define {}", path.display(), HTTP_EXTENSION))?;
+        for service in &config.services {
+            if pool.get_service_by_name(service).is_none() {
+                return Err(format!("Unknown service {:?}", service).into());
+            }
+        }
+
+        let mut bindings = Vec::new();
+        let mut methods = HashMap::new();
+        let services = pool.services().filter(|service| {
+            config.services.is_empty() || config.services.iter().any(|s| s == service.full_name())
+        });
+        for service in services {
+            for method in service.methods() {
+                methods.insert(grpc_path(&method), method.clone());
+                // a REST response carries a single message
+                if method.is_client_streaming() || method.is_server_streaming() {
+                    continue;
+                }
+                let rules = HttpRule::for_method(&method, &extension)
+                    .map_err(|err| format!("{}: {}", method.full_name(), err))?;
+                bindings.extend(rules.into_iter().map(|rule| (rule, method.clone())));
+            }
+        }
+        Ok(Self { bindings, methods })
+    }
+
+    /// The method of a gRPC call
+    pub fn method(&self, grpc_path: &str) -> Option<&MethodDescriptor> {
+        self.methods.get(grpc_path)
+    }
+
+    /// The HTTP methods of the rules, a rule matching any method having none
+    pub fn http_methods(&self) -> impl Iterator<Item = &Method> {
+        self.bindings
+            .iter()
+            .filter_map(|(rule, _)| rule.method.as_ref())
+    }
+
+    /// The method called by a request, the first rule matching in the
+    /// order of the descriptor set
+    pub fn find(&self, method: &Method, path: &str) -> Option<JsonCall<'_>> {
+        self.bindings.iter().find_map(|(rule, descriptor)| {
+            if rule.method.as_ref().is_some_and(|m| m != method) {
+                return None;
+            }
+            let variables = rule.template.matches(path)?;
+            Some(JsonCall {
+                rule,
+                method: descriptor,
+                variables,
+            })
+        })
+    }
+}
+
+impl JsonCall<'_> {
+    /// The `/package.Service/Method` path of the gRPC call
+    pub fn grpc_path(&self) -> String {
+        grpc_path(self.method)
+    }
+
+    /// The encoded request message, from the path variables, the query
+    /// parameters and the JSON body
+    pub fn request_message(&self, query: Option<&str>, body: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let input = self.method.input();
+        let mut json = match self.rule.body.as_deref() {
+            // a body is ignored when the rule maps none
+            None => Map::new(),
+            Some("*") => parse_body(body)?
+                .as_object()
+                .cloned()
+                .ok_or("The JSON body must be an object")?,
+            Some(field) => {
+                let mut json = Map::new();
+                set_field(&input, &mut json, field, parse_body(body)?)?;
+                json
+            }
+        };
+        for (field_path, value) in &self.variables {
+            let value = scalar(&input, field_path, value);
+            set_field(&input, &mut json, field_path, value)?;
+        }
+        // the query sets the fields the path and the body do not
+        if self.rule.body.as_deref() != Some("*") {
+            for (name, value) in query.into_iter().flat_map(query_pairs) {
+                if !self
+                    .variables
+                    .iter()
+                    .any(|(field_path, _)| *field_path == name)
+                    && Some(name.as_str()) != self.rule.body.as_deref()
+                {
+                    let value = scalar(&input, &name, &value);
+                    // unknown query parameters are ignored
+                    let _ = set_field(&input, &mut json, &name, value);
+                }
+            }
+        }
+        let message = DynamicMessage::deserialize(input, Value::Object(json))?;
+        Ok(message.encode_to_vec())
+    }
+
+    /// The response message as JSON, or its `response_body` field
+    pub fn response_json(&self, message: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let message = DynamicMessage::decode(self.method.output(), message)?;
+        let mut json = serde_json::to_value(&message)?;
+        if let Some(field) = &self.rule.response_body {
+            let json_name = self
+                .method
+                .output()
+                .get_field_by_name(field)
+                .map(|field| field.json_name().to_string())
+                .unwrap_or_default();
+            json = json.get(&json_name).cloned().unwrap_or(Value::Null);
+        }
+        Ok(serde_json::to_vec(&json)?)
+    }
+}
+
+fn grpc_path(method: &MethodDescriptor) -> String {
+    format!("/{}/{}", method.parent_service().full_name(), method.name())
+}
+
+fn parse_body(body: &[u8]) -> Result<Value, BoxError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Object(Map::new()));
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+/// The `name=value` pairs of a query string, decoded
+fn query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(move |pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+}
+
+/// A path or query value as JSON, strings being accepted for every
+/// scalar but booleans by the JSON mapping of protobuf
+fn scalar(message: &MessageDescriptor, field_path: &str, value: &str) -> Value {
+    let is_bool =
+        find_field(message, field_path).is_some_and(|field| matches!(field.kind(), Kind::Bool));
+    match value {
+        "true" if is_bool => Value::Bool(true),
+        "false" if is_bool => Value::Bool(false),
+        value => Value::String(value.to_string()),
+    }
+}
+
+fn find_field(message: &MessageDescriptor, field_path: &str) -> Option<FieldDescriptor> {
+    let mut names = field_path.split('.');
+    let mut field = message.get_field_by_name(names.next()?)?;
+    for name in names {
+        field = field.kind().as_message()?.get_field_by_name(name)?;
+    }
+    Some(field)
+}
+
+/// Sets the field at `field_path` in the JSON object of `message`,
+/// appending to repeated fields
+fn set_field(
+    message: &MessageDescriptor,
+    json: &mut Map<String, Value>,
+    field_path: &str,
+    value: Value,
+) -> Result<(), BoxError> {
+    let unknown = || format!("No field {:?} in {}", field_path, message.full_name());
+    let (parents, name) = match field_path.rsplit_once('.') {
+        Some((parents, name)) => (Some(parents), name),
+        None => (None, field_path),
+    };
+    let mut message = message.clone();
+    let mut json = json;
+    for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        let field = message.get_field_by_name(parent).ok_or_else(unknown)?;
+        message = field.kind().as_message().cloned().ok_or_else(unknown)?;
+        json = json
+            .entry(field.name())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or_else(unknown)?;
+    }
+    let field = message.get_field_by_name(name).ok_or_else(unknown)?;
+    if field.is_list() && !value.is_array() {
+        let list = json
+            .entry(field.name())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(list) = list {
+            list.push(value);
+        }
+    } else {
+        json.insert(field.name().to_string(), value);
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use async_stream::try_stream;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_core::Stream;
+use http::{Request, Response};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+use tower::BoxError;
+
+use crate::core::{
+    grpc_kind::GrpcKind,
+    status::{Code, Status},
+    stream_response::{DynStream, StreamResponse, UpstreamBody},
+};
+
+/// Compressed-Flag, then Message-Length as a big-endian u32
+const PREFIX_LEN: usize = 5;
+
+/// Which way the messages are converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conversion {
+    JsonToProto,
+    ProtoToJson,
+}
+
+/// Converts the length-prefixed messages of a body, received in data frames
+/// of any size, between JSON and protobuf. The trailers frames of grpc-web
+/// are passed through.
+pub struct MessageConverter {
+    descriptor: MessageDescriptor,
+    conversion: Conversion,
+    max_message_size: usize,
+    buf: BytesMut,
+}
+
+impl MessageConverter {
+    /// Messages over `max_message_size` bytes fail the conversion
+    pub fn new(
+        descriptor: MessageDescriptor,
+        conversion: Conversion,
+        max_message_size: usize,
+    ) -> Self {
+        Self {
+            descriptor,
+            conversion,
+            max_message_size,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// The messages completed by `data`, converted
+    pub fn push(&mut self, data: &[u8]) -> Result<Bytes, BoxError> {
+        self.buf.extend_from_slice(data);
+        let mut out = BytesMut::new();
+        while self.buf.len() >= PREFIX_LEN {
+            let flags = self.buf[0];
+            let len = u32::from_be_bytes(self.buf[1..PREFIX_LEN].try_into().unwrap()) as usize;
+            if len > self.max_message_size {
+                return Err(format!("Message exceeds {} bytes", self.max_message_size).into());
+            }
+            if self.buf.len() < PREFIX_LEN + len {
+                break;
+            }
+            let frame = self.buf.split_to(PREFIX_LEN + len);
+            if flags & 0x80 != 0 {
+                out.extend_from_slice(&frame);
+                continue;
+            }
+            if flags & 1 != 0 {
+                return Err("Compressed messages cannot be converted".into());
+            }
+            let message = self.convert(&frame[PREFIX_LEN..])?;
+            out.put_u8(0);
+            out.put_u32(message.len() as u32);
+            out.put_slice(&message);
+        }
+        Ok(out.freeze())
+    }
+
+    /// Fails if the body ended in the middle of a message
+    pub fn finish(&self) -> Result<(), BoxError> {
+        if self.buf.has_remaining() {
+            Err("Truncated message".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn convert(&self, message: &[u8]) -> Result<Vec<u8>, BoxError> {
+        match self.conversion {
+            Conversion::JsonToProto => {
+                let mut deserializer = serde_json::Deserializer::from_slice(message);
+                let message =
+                    DynamicMessage::deserialize(self.descriptor.clone(), &mut deserializer)?;
+                deserializer.end()?;
+                Ok(message.encode_to_vec())
+            }
+            Conversion::ProtoToJson => {
+                let message = DynamicMessage::decode(self.descriptor.clone(), message)?;
+                Ok(serde_json::to_vec(&message)?)
+            }
+        }
+    }
+}
+
+/// The request with its JSON messages converted to `descriptor` as they arrive
+pub fn json_to_proto_request<B>(
+    req: Request<B>,
+    descriptor: MessageDescriptor,
+    max_message_size: usize,
+) -> Request<UpstreamBody>
+where
+    B: hyper::body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let converter = MessageConverter::new(descriptor, Conversion::JsonToProto, max_message_size);
+    req.map(|body| {
+        let body = body.map_err(Into::into).boxed_unsync();
+        StreamBody::new(convert_body(body, converter)).boxed_unsync()
+    })
+}
+
+/// The response with its `descriptor` messages converted to JSON, ending the
+/// call in the framing of `kind` with INTERNAL if one does not fit
+pub fn proto_to_json_response(
+    res: StreamResponse,
+    descriptor: MessageDescriptor,
+    kind: GrpcKind,
+    max_message_size: usize,
+) -> StreamResponse {
+    let mut converter =
+        MessageConverter::new(descriptor, Conversion::ProtoToJson, max_message_size);
+    let (parts, mut body) = res.into_parts();
+    let stream = try_stream! {
+        while let Some(frame) = body.frame().await {
+            let frame = frame?;
+            let Some(data) = frame.data_ref() else {
+                yield frame;
+                continue;
+            };
+            match converter.push(data) {
+                Ok(messages) => {
+                    if !messages.is_empty() {
+                        yield Frame::data(messages);
+                    }
+                }
+                // the upstream stream is reset when the body is dropped
+                Err(err) => {
+                    let status = Status::new(
+                        Code::Internal,
+                        format!("Failed to convert the response to JSON: {}", err),
+                    );
+                    yield kind.status_frame(&status);
+                    break;
+                }
+            }
+        }
+    };
+    let boxed: DynStream = Box::pin(stream);
+    Response::from_parts(parts, StreamBody::new(boxed))
+}
+
+/// Converts the messages of a request body as they arrive
+fn convert_body(
+    mut body: UpstreamBody,
+    mut converter: MessageConverter,
+) -> impl Stream<Item = Result<Frame<Bytes>, BoxError>> {
+    try_stream! {
+        while let Some(frame) = body.frame().await {
+            match frame?.into_data() {
+                Ok(data) => {
+                    let messages = converter.push(&data)?;
+                    if !messages.is_empty() {
+                        yield Frame::data(messages);
+                    }
+                }
+                Err(frame) => yield frame,
+            }
+        }
+        converter.finish()?;
+    }
+}
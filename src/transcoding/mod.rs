@@ -0,0 +1,4 @@
+pub mod http_rule;
+pub mod json_transcoder;
+pub mod message_converter;
+pub mod path_template;
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+use percent_encoding::percent_decode_str;
+use tower::BoxError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// `*`, exactly one segment
+    Wildcard,
+    /// `**`, the remaining segments
+    DoubleWildcard,
+}
+
+/// A field path bound to the segments `start..end` of the template
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Variable {
+    field_path: String,
+    start: usize,
+    end: usize,
+}
+
+/// The path of a `google.api.http` rule, such as `/v1/{name=shelves/*}:publish`
+///
+/// <https://github.com/googleapis/googleapis/blob/master/google/api/http.proto>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate {
+    segments: Vec<Segment>,
+    variables: Vec<Variable>,
+    verb: Option<String>,
+}
+
+impl PathTemplate {
+    /// Field paths bound by the template, such as `shelf.name`
+    pub fn field_paths(&self) -> impl Iterator<Item = &str> {
+        self.variables
+            .iter()
+            .map(|variable| variable.field_path.as_str())
+    }
+
+    /// The value of every variable when `path` matches the template
+    pub fn matches(&self, path: &str) -> Option<Vec<(String, String)>> {
+        let path = path.strip_prefix('/')?;
+        let path = match &self.verb {
+            Some(verb) => path.strip_suffix(verb.as_str())?.strip_suffix(':')?,
+            None => path,
+        };
+        let parts: Vec<&str> = path.split('/').collect();
+
+        // where each segment of the template starts in the path
+        let mut starts = Vec::with_capacity(self.segments.len() + 1);
+        let mut i = 0;
+        for segment in &self.segments {
+            starts.push(i);
+            match segment {
+                Segment::Literal(literal) if parts.get(i) == Some(&literal.as_str()) => i += 1,
+                Segment::Wildcard if parts.get(i).is_some_and(|part| !part.is_empty()) => i += 1,
+                Segment::DoubleWildcard => i = parts.len(),
+                _ => return None,
+            }
+        }
+        if i != parts.len() {
+            return None;
+        }
+        starts.push(i);
+
+        let variables = self
+            .variables
+            .iter()
+            .map(|variable| {
+                let value = parts[starts[variable.start]..starts[variable.end]]
+                    .iter()
+                    .map(|part| percent_decode_str(part).decode_utf8_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (variable.field_path.clone(), value)
+            })
+            .collect();
+        Some(variables)
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = BoxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("Invalid path template {:?}: {}", s, reason);
+        let path = s
+            .strip_prefix('/')
+            .ok_or_else(|| invalid("must start with '/'"))?;
+
+        // the verb follows the last ':' outside of a variable
+        let mut depth = 0;
+        let mut verb_at = None;
+        for (i, c) in path.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ':' if depth == 0 => verb_at = Some(i),
+                _ => {}
+            }
+        }
+        let (path, verb) = match verb_at {
+            Some(i) => (&path[..i], Some(path[i + 1..].to_string())),
+            None => (path, None),
+        };
+
+        let mut template = PathTemplate {
+            segments: Vec::new(),
+            variables: Vec::new(),
+            verb,
+        };
+        for part in split_segments(path) {
+            match part.strip_prefix('{') {
+                Some(variable) => {
+                    let variable = variable
+                        .strip_suffix('}')
+                        .ok_or_else(|| invalid("unclosed variable"))?;
+                    let (field_path, segments) =
+                        variable.split_once('=').unwrap_or((variable, "*"));
+                    if field_path.is_empty() || segments.contains(['{', '}']) {
+                        return Err(invalid("invalid variable").into());
+                    }
+                    let start = template.segments.len();
+                    for segment in segments.split('/') {
+                        template
+                            .segments
+                            .push(parse_segment(segment).map_err(invalid)?);
+                    }
+                    template.variables.push(Variable {
+                        field_path: field_path.to_string(),
+                        start,
+                        end: template.segments.len(),
+                    });
+                }
+                None => template
+                    .segments
+                    .push(parse_segment(part).map_err(invalid)?),
+            }
+        }
+        let last = template.segments.len().saturating_sub(1);
+        if template.segments[..last].contains(&Segment::DoubleWildcard) {
+            return Err(invalid("'**' must be the last segment").into());
+        }
+        Ok(template)
+    }
+}
+
+/// Splits on the '/' outside of variables
+fn split_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in path.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '/' if depth == 0 => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+fn parse_segment(segment: &str) -> Result<Segment, &'static str> {
+    match segment {
+        "" => Err("empty segment"),
+        "*" => Ok(Segment::Wildcard),
+        "**" => Ok(Segment::DoubleWildcard),
+        literal if literal.contains(['*', '{', '}']) => Err("invalid segment"),
+        literal => Ok(Segment::Literal(literal.to_string())),
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    config::circuit_breaker_config::CircuitBreakerConfig,
+    upstream::{connection_pool::ConnectionLimit, endpoint::Endpoint},
+};
+
+/// Counts the calls of a cluster against the limits of its circuit breaker
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    streams: Arc<AtomicUsize>,
+    pending: Arc<AtomicUsize>,
+}
+
+/// A call counted against a limit until dropped
+pub struct BreakerSlot(Arc<AtomicUsize>);
+
+impl Drop for BreakerSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            streams: Arc::new(AtomicUsize::new(0)),
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// A slot for a call in flight, or the name of the limit reached
+    pub fn start_stream(&self) -> Result<BreakerSlot, &'static str> {
+        acquire(&self.streams, self.config.max_concurrent_streams).ok_or("max_concurrent_streams")
+    }
+
+    /// A slot for a call waiting for a connection, or the name of the limit reached
+    pub fn start_pending(&self) -> Result<BreakerSlot, &'static str> {
+        acquire(&self.pending, self.config.max_pending_requests).ok_or("max_pending_requests")
+    }
+
+    /// The connections the pool may open to `endpoints`, those of the cluster
+    pub fn connection_limit<'a>(&self, endpoints: &'a [Endpoint]) -> Option<ConnectionLimit<'a>> {
+        self.config
+            .max_connections
+            .map(|max_connections| ConnectionLimit {
+                endpoints,
+                max_connections,
+            })
+    }
+}
+
+fn acquire(counter: &Arc<AtomicUsize>, max: Option<usize>) -> Option<BreakerSlot> {
+    counter
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+            max.is_none_or(|max| count < max).then_some(count + 1)
+        })
+        .ok()?;
+    Some(BreakerSlot(counter.clone()))
+}
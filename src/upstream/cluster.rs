@@ -0,0 +1,113 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use http::{HeaderMap, uri::Authority};
+use tower::BoxError;
+
+use crate::{
+    balancing::load_balancer::{self, LoadBalancer},
+    compression::compression_policy::CompressionPolicy,
+    config::{cluster_config::ClusterConfig, health_check_config::HealthCheckConfig},
+    telemetry::metrics::Metrics,
+    tls::client_tls::ClientTls,
+    upstream::{
+        circuit_breaker::CircuitBreaker, endpoint::Endpoint, outlier_detector::OutlierDetector,
+    },
+};
+
+/// A named group of upstream gRPC servers
+pub struct Cluster {
+    pub name: String,
+    pub endpoints: Vec<Endpoint>,
+    pub health_check: Option<HealthCheckConfig>,
+    pub circuit_breaker: CircuitBreaker,
+    pub outlier_detector: Option<OutlierDetector>,
+    pub compression: CompressionPolicy,
+    balancer: Box<dyn LoadBalancer>,
+}
+
+impl Cluster {
+    pub fn from_config(config: &ClusterConfig) -> Result<Self, BoxError> {
+        if config.endpoints.is_empty() {
+            return Err(format!("Cluster {:?} has no endpoint", config.name).into());
+        }
+        // endpoints of a cluster share the same TLS settings
+        let tls = config
+            .tls
+            .as_ref()
+            .map(ClientTls::new)
+            .transpose()?
+            .map(Arc::new);
+        let endpoints = config
+            .endpoints
+            .iter()
+            .map(|authority| {
+                let authority = Authority::from_str(authority).map_err(|e| {
+                    format!(
+                        "Invalid authority {:?} of cluster {:?}: {}",
+                        authority, config.name, e
+                    )
+                })?;
+                Ok(Endpoint::new(authority, tls.clone()))
+            })
+            .collect::<Result<Vec<_>, BoxError>>()?;
+        Ok(Self {
+            name: config.name.clone(),
+            balancer: load_balancer::from_config(&config.lb_policy, &endpoints)?,
+            endpoints,
+            health_check: config.health_check.clone(),
+            circuit_breaker: CircuitBreaker::new(config.circuit_breaker.clone()),
+            outlier_detector: config.outlier_detection.clone().map(OutlierDetector::new),
+            compression: CompressionPolicy::from_config(&config.compression)?,
+        })
+    }
+
+    /// Name of the load balancing policy, as reported in metrics
+    pub fn policy(&self) -> &'static str {
+        self.balancer.policy()
+    }
+
+    /// The endpoint the call with `headers` is sent to, among the healthy ones
+    /// not ejected by outlier detection. When none is available every
+    /// endpoint is a candidate again, failing calls being preferable to
+    /// rejecting all of them.
+    pub fn pick(&self, headers: &HeaderMap) -> Endpoint {
+        if self.endpoints.iter().all(Endpoint::is_available) {
+            return self.endpoints[self.balancer.pick(&self.endpoints, headers)].clone();
+        }
+        let available: Vec<_> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_available())
+            .cloned()
+            .collect();
+        let candidates = if available.is_empty() {
+            &self.endpoints
+        } else {
+            &available
+        };
+        candidates[self.balancer.pick(candidates, headers)].clone()
+    }
+
+    /// Like [`Cluster::pick`], avoiding the endpoints in `exclude`
+    /// unless every available endpoint is excluded
+    pub fn pick_excluding(&self, headers: &HeaderMap, exclude: &[Authority]) -> Endpoint {
+        let candidates: Vec<_> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_available() && !exclude.contains(&endpoint.authority))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return self.pick(headers);
+        }
+        candidates[self.balancer.pick(&candidates, headers)].clone()
+    }
+
+    /// Feeds the outcome of a call to `endpoint` to outlier detection
+    pub fn report(&self, endpoint: &Endpoint, failed: bool, metrics: &Metrics) {
+        if let Some(detector) = &self.outlier_detector {
+            detector.report(&self.name, &self.endpoints, endpoint, failed, metrics);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{
+    Arc, Mutex, RwLock, Weak,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+use http::uri::Authority;
+use hyper::client::conn::http2;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    sync::{Notify, futures::OwnedNotified},
+};
+use tower::BoxError;
+
+use crate::{
+    config::pool_config::PoolConfig,
+    core::stream_response::UpstreamBody,
+    telemetry::metrics::Metrics,
+    tls::client_tls::ClientTls,
+    upstream::{
+        endpoint::{Endpoint, OutlierState},
+        peer_settings::{PeerMaxStreams, SettingsReader},
+    },
+};
+
+/// Upstream HTTP/2 connections shared by every downstream request.
+///
+/// Streams are multiplexed over at most `max_connections_per_authority`
+/// connections per upstream authority and TLS settings. A new connection is only opened when
+/// every existing one carries `max_concurrent_streams` streams, or the lower
+/// limit advertised by the upstream server, calls waiting
+/// for the connection being opened when no other one may be. Connections
+/// are evicted once they are closed by a GOAWAY or a connection error, and
+/// the pools of endpoints removed by a reload once their streams have ended.
+pub struct ConnectionPool {
+    config: RwLock<PoolConfig>,
+    metrics: Metrics,
+    authorities: Mutex<HashMap<PoolKey, AuthorityPool>>,
+}
+
+/// Connections are only shared by endpoints reaching the same authority with
+/// the same TLS settings, `None` standing for cleartext
+#[derive(Clone)]
+struct PoolKey {
+    authority: Authority,
+    tls: Option<Arc<ClientTls>>,
+}
+
+impl PoolKey {
+    fn new(endpoint: &Endpoint) -> Self {
+        Self {
+            authority: endpoint.authority.clone(),
+            tls: endpoint.tls.clone(),
+        }
+    }
+}
+
+impl PartialEq for PoolKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.authority == other.authority
+            && match (&self.tls, &other.tls) {
+                (Some(tls), Some(other)) => Arc::ptr_eq(tls, other),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+impl Eq for PoolKey {}
+
+impl Hash for PoolKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.authority.hash(state);
+        self.tls.as_ref().map(Arc::as_ptr).hash(state);
+    }
+}
+
+#[derive(Default)]
+struct AuthorityPool {
+    connections: Vec<Arc<PooledConnection>>,
+    connecting: usize,
+    /// Notified when a dial ends, successful or not
+    dialed: Arc<Notify>,
+    /// The last endpoint using the pool, dropped once a reload removes it
+    endpoint: Weak<OutlierState>,
+}
+
+impl AuthorityPool {
+    /// Whether the pool can be dropped, closing its idle connections: no
+    /// stream or dial is in progress, and it has no connection or the
+    /// configuration no longer has its endpoint
+    fn is_unused(&self) -> bool {
+        self.connecting == 0
+            && self
+                .connections
+                .iter()
+                .all(|connection| connection.in_use() == 0)
+            && (self.connections.is_empty() || self.endpoint.strong_count() == 0)
+    }
+}
+
+/// What a call does with the pool of its authority
+enum Reuse {
+    Lease(PooledSender),
+    /// A dial slot was reserved
+    Dial,
+    /// No connection may be opened, this one is notified once a dial ends
+    Wait(OwnedNotified),
+    /// No connection is open and the connection limit of the cluster is reached
+    Exhausted,
+}
+
+/// The connections a cluster may open to all its endpoints together
+#[derive(Clone, Copy)]
+pub struct ConnectionLimit<'a> {
+    pub endpoints: &'a [Endpoint],
+    pub max_connections: usize,
+}
+
+struct PooledConnection {
+    sender: http2::SendRequest<UpstreamBody>,
+    in_use: AtomicUsize,
+    closed: AtomicBool,
+    peer_max_streams: PeerMaxStreams,
+}
+
+impl PooledConnection {
+    fn is_saturated(&self, config: &PoolConfig) -> bool {
+        self.in_use()
+            >= config
+                .max_concurrent_streams
+                .min(self.peer_max_streams.get())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire) || self.sender.is_closed()
+    }
+
+    fn in_use(&self) -> usize {
+        self.in_use.load(Ordering::Acquire)
+    }
+}
+
+/// A stream slot on a pooled connection, released on drop.
+///
+/// It must be kept alive until the response body has been streamed.
+pub struct PooledSender {
+    connection: Arc<PooledConnection>,
+    authority: Authority,
+    metrics: Metrics,
+}
+
+impl PooledSender {
+    pub fn sender(&self) -> http2::SendRequest<UpstreamBody> {
+        self.connection.sender.clone()
+    }
+}
+
+impl Drop for PooledSender {
+    fn drop(&mut self) {
+        self.connection.in_use.fetch_sub(1, Ordering::AcqRel);
+        self.metrics
+            .upstream_streams_in_use
+            .with_label_values(&[self.authority.as_str()])
+            .dec();
+    }
+}
+
+impl ConnectionPool {
+    pub fn new(config: PoolConfig, metrics: Metrics) -> Self {
+        Self {
+            config: RwLock::new(config),
+            metrics,
+            authorities: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Applies new limits, connections above them are kept until they close
+    pub fn reconfigure(&self, config: PoolConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    /// A stream slot on a connection to `endpoint`, dialing one when needed
+    pub async fn get(&self, endpoint: &Endpoint) -> Result<PooledSender, BoxError> {
+        self.get_within(endpoint, None)
+            .await?
+            .ok_or_else(|| "No connection may be opened".into())
+    }
+
+    /// Like [`ConnectionPool::get`], only dialing while `limit` is not
+    /// reached, `None` when it is and no connection to `endpoint` is open.
+    ///
+    /// The limit is checked and the dial reserved at once, so that concurrent
+    /// calls never open more connections than it allows.
+    pub async fn get_within(
+        &self,
+        endpoint: &Endpoint,
+        limit: Option<ConnectionLimit<'_>>,
+    ) -> Result<Option<PooledSender>, BoxError> {
+        let key = PoolKey::new(endpoint);
+        loop {
+            match self.try_reuse(endpoint, &key, limit) {
+                Reuse::Lease(sender) => return Ok(Some(sender)),
+                Reuse::Dial => break,
+                Reuse::Wait(dialed) => dialed.await,
+                Reuse::Exhausted => return Ok(None),
+            }
+        }
+
+        // release the dial slot even if the request is cancelled meanwhile,
+        // adding the connection before waking up the calls waiting for it
+        let mut dialed = scopeguard::guard(None, |connection| {
+            self.pool_mut(&key, |pool| {
+                pool.connecting -= 1;
+                pool.connections.extend(connection);
+                pool.dialed.notify_waiters();
+            })
+        });
+        let connect_timeout = Duration::from_millis(self.config.read().unwrap().connect_timeout_ms);
+        let connection = tokio::time::timeout(connect_timeout, self.connect(endpoint))
+            .await
+            .map_err(|_| format!("Connecting to {} timed out", endpoint.authority))??;
+        *dialed = Some(connection.clone());
+        drop(dialed);
+        Ok(Some(self.lease(&key.authority, connection)))
+    }
+
+    /// Picks the least loaded open connection, unless a new connection
+    /// should be opened, or waited for when no more may be dialed.
+    fn try_reuse(
+        &self,
+        endpoint: &Endpoint,
+        key: &PoolKey,
+        limit: Option<ConnectionLimit<'_>>,
+    ) -> Reuse {
+        let config = self.config.read().unwrap().clone();
+        let mut authorities = self.authorities.lock().unwrap();
+        self.evict_unused(&mut authorities);
+        let within_limit = limit.is_none_or(|limit| {
+            let open: usize = limit
+                .endpoints
+                .iter()
+                .filter_map(|endpoint| authorities.get(&PoolKey::new(endpoint)))
+                .map(|pool| pool.connections.len() + pool.connecting)
+                .sum();
+            open < limit.max_connections
+        });
+        let pool = authorities.entry(key.clone()).or_default();
+        pool.endpoint = Arc::downgrade(&endpoint.outlier);
+        // connections closed by a GOAWAY or an error are evicted here
+        pool.connections
+            .retain(|connection| !connection.is_closed());
+
+        let least_loaded = pool
+            .connections
+            .iter()
+            .min_by_key(|connection| connection.in_use())
+            .cloned();
+        let saturated = least_loaded
+            .as_ref()
+            .is_none_or(|connection| connection.is_saturated(&config));
+        let can_connect = within_limit
+            && pool.connections.len() + pool.connecting < config.max_connections_per_authority;
+
+        match least_loaded {
+            // when every connection is saturated and no more can be opened,
+            // hyper queues the stream until the upstream server accepts it
+            Some(connection) if !saturated || !can_connect => {
+                Reuse::Lease(self.lease(&key.authority, connection))
+            }
+            None if !can_connect && pool.connecting > 0 => {
+                Reuse::Wait(pool.dialed.clone().notified_owned())
+            }
+            None if !can_connect => Reuse::Exhausted,
+            _ => {
+                pool.connecting += 1;
+                Reuse::Dial
+            }
+        }
+    }
+
+    /// Drops the unused pools, and the metrics of the authorities left without any
+    // keys hash the TLS settings by pointer, never by their reloadable state
+    #[allow(clippy::mutable_key_type)]
+    fn evict_unused(&self, authorities: &mut HashMap<PoolKey, AuthorityPool>) {
+        let mut evicted = Vec::new();
+        authorities.retain(|key, pool| {
+            pool.connections
+                .retain(|connection| !connection.is_closed());
+            let unused = pool.is_unused();
+            if unused {
+                evicted.push(key.authority.clone());
+            }
+            !unused
+        });
+        for authority in evicted {
+            if !authorities.keys().any(|key| key.authority == authority) {
+                let labels = [authority.as_str()];
+                let _ = self
+                    .metrics
+                    .upstream_connections
+                    .remove_label_values(&labels);
+                let _ = self
+                    .metrics
+                    .upstream_streams_in_use
+                    .remove_label_values(&labels);
+            }
+        }
+    }
+
+    fn pool_mut<T>(&self, key: &PoolKey, f: impl FnOnce(&mut AuthorityPool) -> T) -> T {
+        let mut authorities = self.authorities.lock().unwrap();
+        f(authorities.entry(key.clone()).or_default())
+    }
+
+    fn lease(&self, authority: &Authority, connection: Arc<PooledConnection>) -> PooledSender {
+        connection.in_use.fetch_add(1, Ordering::AcqRel);
+        self.metrics
+            .upstream_streams_in_use
+            .with_label_values(&[authority.as_str()])
+            .inc();
+        PooledSender {
+            connection,
+            authority: authority.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    async fn connect(&self, endpoint: &Endpoint) -> Result<Arc<PooledConnection>, BoxError> {
+        let authority = &endpoint.authority;
+        let stream = TcpStream::connect(authority.as_str()).await?;
+        stream.set_nodelay(true)?;
+        match &endpoint.tls {
+            Some(tls) => {
+                let stream = tls.connect(authority, stream).await?;
+                self.handshake(authority, stream).await
+            }
+            None => self.handshake(authority, stream).await,
+        }
+    }
+
+    async fn handshake<S>(
+        &self,
+        authority: &Authority,
+        stream: S,
+    ) -> Result<Arc<PooledConnection>, BoxError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let http2 = self.config.read().unwrap().http2.clone();
+        let mut builder = http2::Builder::new(TokioExecutor::new());
+        builder
+            .timer(TokioTimer::new())
+            .initial_stream_window_size(http2.initial_stream_window_size)
+            .initial_connection_window_size(http2.initial_connection_window_size)
+            .adaptive_window(http2.adaptive_window)
+            .max_frame_size(http2.max_frame_size)
+            .keep_alive_interval(http2.keep_alive_interval_ms.map(Duration::from_millis))
+            .keep_alive_timeout(Duration::from_millis(http2.keep_alive_timeout_ms))
+            .keep_alive_while_idle(true);
+        if let Some(max) = http2.max_header_list_size {
+            builder.max_header_list_size(max);
+        }
+        let (io, peer_max_streams) = SettingsReader::new(stream);
+        let (sender, conn) = builder.handshake(TokioIo::new(io)).await?;
+        let connection = Arc::new(PooledConnection {
+            sender,
+            in_use: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            peer_max_streams,
+        });
+
+        let connections = self
+            .metrics
+            .upstream_connections
+            .with_label_values(&[authority.as_str()]);
+        connections.inc();
+
+        // Spawn a task to poll the connection, driving the HTTP state.
+        // The connection is evicted from the pool once this task ends.
+        let authority = authority.clone();
+        let pooled = Arc::downgrade(&connection);
+        tokio::task::spawn(async move {
+            if let Err(err) = conn.await {
+                eprintln!("Upstream connection to {} failed: {:?}", authority, err);
+            }
+            if let Some(connection) = pooled.upgrade() {
+                connection.closed.store(true, Ordering::Release);
+            }
+            connections.dec();
+        });
+        Ok(connection)
+    }
+}
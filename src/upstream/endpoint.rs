@@ -0,0 +1,102 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+};
+
+use http::{Uri, uri::Authority};
+use prometheus::IntGauge;
+use tokio::time::Instant;
+
+use crate::tls::client_tls::ClientTls;
+
+/// An upstream server and how to connect to it
+#[derive(Clone)]
+pub struct Endpoint {
+    pub authority: Authority,
+    pub tls: Option<Arc<ClientTls>>,
+    in_flight: Arc<AtomicUsize>,
+    healthy: Arc<AtomicBool>,
+    pub outlier: Arc<OutlierState>,
+}
+
+/// Recent outcomes of the calls to an endpoint, for outlier detection
+#[derive(Default)]
+pub struct OutlierState {
+    pub consecutive_failures: AtomicU32,
+    /// Times the endpoint was ejected
+    pub ejections: AtomicU32,
+    pub ejected: AtomicBool,
+    /// When the endpoint was last put back in rotation
+    pub restored_at: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    pub fn new(authority: Authority, tls: Option<Arc<ClientTls>>) -> Self {
+        Self {
+            authority,
+            tls,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            healthy: Arc::new(AtomicBool::new(true)),
+            outlier: Arc::new(OutlierState::default()),
+        }
+    }
+
+    /// The URI of `path` on this endpoint, with the scheme matching its transport
+    pub fn uri(&self, path: &str) -> Result<Uri, http::Error> {
+        let scheme = match self.tls {
+            Some(_) => "https",
+            None => "http",
+        };
+        Uri::builder()
+            .scheme(scheme)
+            .authority(self.authority.clone())
+            .path_and_query(path)
+            .build()
+    }
+
+    /// Calls currently sent to this endpoint
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Whether the endpoint is in load-balancing rotation
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    /// Whether outlier detection took the endpoint out of rotation
+    pub fn is_ejected(&self) -> bool {
+        self.outlier.ejected.load(Ordering::Acquire)
+    }
+
+    /// Healthy and not ejected
+    pub fn is_available(&self) -> bool {
+        self.is_healthy() && !self.is_ejected()
+    }
+
+    pub fn set_healthy(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::Relaxed);
+    }
+
+    /// Counts a call as in flight until the returned guard is dropped
+    pub fn start_request(&self, gauge: IntGauge) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        gauge.inc();
+        InFlight {
+            in_flight: self.in_flight.clone(),
+            gauge,
+        }
+    }
+}
+
+pub struct InFlight {
+    in_flight: Arc<AtomicUsize>,
+    gauge: IntGauge,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.gauge.dec();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod circuit_breaker;
+pub mod cluster;
+pub mod connection_pool;
+pub mod endpoint;
+pub mod outlier_detector;
+pub mod peer_settings;
+pub mod upstream_call;
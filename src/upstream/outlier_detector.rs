@@ -0,0 +1,105 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::{
+    config::outlier_detection_config::OutlierDetectionConfig,
+    telemetry::metrics::Metrics,
+    upstream::endpoint::{Endpoint, OutlierState},
+};
+
+/// Ejects the endpoints of a cluster failing too many calls in a row, for
+/// longer each time, and puts them back in rotation once the time is over.
+/// Endpoints staying in rotation are ejected for shorter again.
+pub struct OutlierDetector {
+    config: OutlierDetectionConfig,
+}
+
+impl OutlierDetector {
+    pub fn new(config: OutlierDetectionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Records the outcome of a call to `endpoint`, one of `endpoints`
+    pub fn report(
+        &self,
+        cluster: &str,
+        endpoints: &[Endpoint],
+        endpoint: &Endpoint,
+        failed: bool,
+        metrics: &Metrics,
+    ) {
+        let outlier = &endpoint.outlier;
+        if !failed {
+            outlier.consecutive_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+        let failures = outlier.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < self.config.consecutive_failures || endpoint.is_ejected() {
+            return;
+        }
+        // one endpoint can always be ejected, whatever the percentage
+        let ejected = endpoints.iter().filter(|e| e.is_ejected()).count();
+        let max_percent = self.config.max_ejection_percent as usize;
+        if ejected > 0 && (ejected + 1) * 100 > endpoints.len() * max_percent {
+            return;
+        }
+        if outlier
+            .ejected
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+        outlier.consecutive_failures.store(0, Ordering::Relaxed);
+        self.forget_ejections(outlier);
+        let ejections = outlier.ejections.fetch_add(1, Ordering::Relaxed) + 1;
+        let duration = Duration::from_millis(
+            self.config
+                .base_ejection_ms
+                .saturating_mul(ejections.into())
+                .min(self.config.max_ejection_ms),
+        );
+
+        let labels = [cluster, endpoint.authority.as_str()];
+        metrics.outlier_ejections.with_label_values(&labels).inc();
+        let gauge = metrics.endpoint_ejected.with_label_values(&labels);
+        gauge.set(1);
+        eprintln!(
+            "Ejecting {} of cluster {:?} for {:?} after {} consecutive failures",
+            endpoint.authority, cluster, duration, failures
+        );
+
+        let endpoint = endpoint.clone();
+        let cluster = cluster.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            *endpoint.outlier.restored_at.lock().unwrap() = Some(Instant::now());
+            endpoint.outlier.ejected.store(false, Ordering::Release);
+            gauge.set(0);
+            println!(
+                "Restoring {} of cluster {:?} to rotation",
+                endpoint.authority, cluster
+            );
+        });
+    }
+
+    /// Forgets one ejection for every base ejection time the endpoint spent
+    /// in rotation since it was restored, as if decremented at each interval
+    fn forget_ejections(&self, outlier: &OutlierState) {
+        let Some(restored_at) = outlier.restored_at.lock().unwrap().take() else {
+            return;
+        };
+        let in_rotation = restored_at.elapsed().as_millis() as u64;
+        let forgotten = in_rotation
+            .checked_div(self.config.base_ejection_ms)
+            .unwrap_or(0);
+        let forgotten = u32::try_from(forgotten).unwrap_or(u32::MAX);
+        let _ = outlier
+            .ejections
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |ejections| {
+                Some(ejections.saturating_sub(forgotten))
+            });
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::IoSlice;
+use std::pin::Pin;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+const FRAME_HEADER_LEN: usize = 9;
+const SETTINGS_FRAME: u8 = 0x4;
+const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+/// SETTINGS frames carry a handful of parameters, larger ones are not parsed
+const MAX_SETTINGS_LEN: usize = 1024;
+
+/// The SETTINGS_MAX_CONCURRENT_STREAMS of an upstream server, `usize::MAX`
+/// until its connection preface is read or when it sets no limit.
+#[derive(Clone)]
+pub struct PeerMaxStreams(Arc<AtomicUsize>);
+
+impl PeerMaxStreams {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Reads the SETTINGS frame opening the HTTP/2 connection of an upstream
+/// server as hyper reads it, hyper not exposing the settings of the peer.
+pub struct SettingsReader<S> {
+    inner: S,
+    preface: Option<Vec<u8>>,
+    max_streams: PeerMaxStreams,
+}
+
+impl<S> SettingsReader<S> {
+    pub fn new(inner: S) -> (Self, PeerMaxStreams) {
+        let max_streams = PeerMaxStreams(Arc::new(AtomicUsize::new(usize::MAX)));
+        let reader = Self {
+            inner,
+            preface: Some(Vec::with_capacity(FRAME_HEADER_LEN)),
+            max_streams: max_streams.clone(),
+        };
+        (reader, max_streams)
+    }
+
+    fn inspect(&mut self, read: &[u8]) {
+        let Some(preface) = &mut self.preface else {
+            return;
+        };
+        preface.extend_from_slice(read);
+        if preface.len() < FRAME_HEADER_LEN {
+            return;
+        }
+        let len = u32::from_be_bytes([0, preface[0], preface[1], preface[2]]) as usize;
+        if preface[3] != SETTINGS_FRAME || len > MAX_SETTINGS_LEN {
+            self.preface = None;
+            return;
+        }
+        if preface.len() < FRAME_HEADER_LEN + len {
+            return;
+        }
+        for setting in preface[FRAME_HEADER_LEN..FRAME_HEADER_LEN + len].chunks_exact(6) {
+            if u16::from_be_bytes([setting[0], setting[1]]) == SETTINGS_MAX_CONCURRENT_STREAMS {
+                let max = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+                self.max_streams.0.store(max as usize, Ordering::Release);
+            }
+        }
+        self.preface = None;
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for SettingsReader<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            self.inspect(&buf.filled()[filled..]);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for SettingsReader<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
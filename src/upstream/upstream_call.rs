@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use http::{HeaderMap, Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use tokio::{task::JoinSet, time::Instant};
+
+use crate::{
+    context::ProxyContext,
+    core::{
+        grpc_kind::GrpcKind,
+        grpc_timeout::{GRPC_TIMEOUT, encode_grpc_timeout},
+        proxy_error::ProxyError,
+        status::Code,
+        stream_response::UpstreamBody,
+    },
+    retry::{
+        hedging_policy::HedgingPolicy,
+        replay_body::ReplayBuffer,
+        retry_policy::{GRPC_PREVIOUS_RPC_ATTEMPTS, Pushback, RetryPolicy},
+    },
+    telemetry::metrics::Metrics,
+    upstream::{
+        circuit_breaker::BreakerSlot,
+        cluster::Cluster,
+        connection_pool::PooledSender,
+        endpoint::{Endpoint, InFlight},
+    },
+};
+
+/// The stream slot, in-flight count and circuit breaker slot of an attempt,
+/// to be kept until its response has been streamed, and its outcome when
+/// only its trailers tell it
+pub type AttemptGuard = (PooledSender, InFlight, BreakerSlot, Option<PendingOutcome>);
+
+/// The outcome of an attempt answered with response headers, reported to
+/// outlier detection once the trailers carrying its status are received
+pub struct PendingOutcome {
+    cluster: Arc<Cluster>,
+    endpoint: Endpoint,
+    metrics: Metrics,
+}
+
+impl PendingOutcome {
+    /// `trailers` is `None` when the body ended without any, or with an error
+    pub fn report(self, trailers: Option<&HeaderMap>) {
+        let failed =
+            trailers.is_none_or(|trailers| Code::from_headers(trailers) == Some(Code::Unavailable));
+        self.cluster.report(&self.endpoint, failed, &self.metrics);
+    }
+}
+
+/// Sends a plain gRPC request to an endpoint of `cluster`
+pub async fn send(
+    ctx: &ProxyContext,
+    cluster: &Arc<Cluster>,
+    req: Request<UpstreamBody>,
+    deadline: Option<Instant>,
+) -> Result<(Response<Incoming>, AttemptGuard), ProxyError> {
+    let endpoint = cluster.pick(req.headers());
+    send_to(ctx, cluster, &endpoint, req, deadline).await
+}
+
+async fn send_to(
+    ctx: &ProxyContext,
+    cluster: &Arc<Cluster>,
+    endpoint: &Endpoint,
+    req: Request<UpstreamBody>,
+    deadline: Option<Instant>,
+) -> Result<(Response<Incoming>, AttemptGuard), ProxyError> {
+    let (mut parts, body) = req.into_parts();
+    let labels = [cluster.name.as_str(), endpoint.authority.as_str()];
+    let overflow = |limit| {
+        ctx.metrics
+            .circuit_breaker_overflows
+            .with_label_values(&[labels[0], limit])
+            .inc();
+        ProxyError::CircuitOpen(limit)
+    };
+    let breaker = &cluster.circuit_breaker;
+    let stream = breaker.start_stream().map_err(overflow)?;
+    ctx.metrics
+        .lb_selections
+        .with_label_values(&[labels[0], cluster.policy(), labels[1]])
+        .inc();
+    let in_flight =
+        endpoint.start_request(ctx.metrics.endpoint_in_flight.with_label_values(&labels));
+
+    //[START] switch endpoint
+    parts.headers.insert(
+        hyper::header::HOST,
+        endpoint
+            .authority
+            .as_str()
+            .parse()
+            .map_err(|err| ProxyError::InvalidAuthority(Box::new(err)))?,
+    );
+    parts.uri = endpoint
+        .uri(parts.uri.path())
+        .map_err(|err| ProxyError::InvalidAuthority(Box::new(err)))?;
+
+    //[END] switch endpoint
+
+    let pooled = {
+        let _pending = breaker.start_pending().map_err(overflow)?;
+        let limit = breaker.connection_limit(&cluster.endpoints);
+        ctx.pool
+            .get_within(endpoint, limit)
+            .await
+            .map_err(|err| {
+                cluster.report(endpoint, true, &ctx.metrics);
+                ProxyError::Connect(err)
+            })?
+            .ok_or_else(|| overflow("max_connections"))?
+    };
+    ctx.metrics
+        .endpoint_requests
+        .with_label_values(&labels)
+        .inc();
+    let result = GrpcKind::send(pooled.sender(), Request::from_parts(parts, body), deadline).await;
+    let outcome = match &result {
+        // the status comes in the trailers
+        Ok(res) if Code::from_headers(res.headers()).is_none() => {
+            cluster.outlier_detector.as_ref().map(|_| PendingOutcome {
+                cluster: cluster.clone(),
+                endpoint: endpoint.clone(),
+                metrics: ctx.metrics.clone(),
+            })
+        }
+        _ => {
+            if let Some(failed) = is_failure(&result) {
+                cluster.report(endpoint, failed, &ctx.metrics);
+            }
+            None
+        }
+    };
+    Ok((result?, (pooled, in_flight, stream, outcome)))
+}
+
+/// Whether an attempt counts as a failure of its endpoint for outlier
+/// detection, `None` when its outcome says nothing about the endpoint
+fn is_failure(result: &Result<Response<Incoming>, ProxyError>) -> Option<bool> {
+    match result {
+        // a Trailers-Only response
+        Ok(res) => Some(Code::from_headers(res.headers()) == Some(Code::Unavailable)),
+        Err(ProxyError::Connect(_) | ProxyError::Upstream(_)) => Some(true),
+        Err(ProxyError::UpstreamHttpStatus(status)) => {
+            Some(*status >= StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => None,
+    }
+}
+
+/// Sends the request again, after a backoff, each time an attempt fails
+/// with a status the policy retries.
+///
+/// A response with headers is committed and never retried, and neither is
+/// a call whose request body has outgrown the replay buffer.
+pub async fn send_with_retries(
+    ctx: &ProxyContext,
+    cluster: &Arc<Cluster>,
+    req: Request<UpstreamBody>,
+    policy: &RetryPolicy,
+    deadline: Option<Instant>,
+) -> Result<(Response<Incoming>, AttemptGuard), ProxyError> {
+    let (parts, body) = req.into_parts();
+    let buffer = ReplayBuffer::new(body, policy.buffer_limit);
+    let mut body = buffer.attempt();
+    let mut attempts = 0;
+    loop {
+        let mut parts = parts.clone();
+        if attempts > 0 {
+            parts
+                .headers
+                .insert(GRPC_PREVIOUS_RPC_ATTEMPTS, attempts.into());
+            if let Some(deadline) = deadline {
+                parts.headers.insert(
+                    GRPC_TIMEOUT,
+                    encode_grpc_timeout(deadline.saturating_duration_since(Instant::now())),
+                );
+            }
+        }
+        // the first attempt always has a body
+        let req = Request::from_parts(parts, body.take().unwrap().boxed_unsync());
+        let result = send(ctx, cluster, req, deadline).await;
+        attempts += 1;
+
+        let (code, pushback) = match &result {
+            Ok((res, _)) => match Code::from_headers(res.headers()) {
+                // a Trailers-Only response
+                Some(code) => (code, Pushback::from_headers(res.headers())),
+                None => return result,
+            },
+            Err(err) => (err.code(), Pushback::None),
+        };
+        if attempts >= policy.max_attempts || !policy.is_retryable(code) {
+            return result;
+        }
+        let delay = match pushback {
+            Pushback::None => policy.backoff(attempts),
+            Pushback::After(delay) => delay,
+            Pushback::Stop => return result,
+        };
+        if deadline.is_some_and(|deadline| Instant::now() + delay >= deadline) {
+            return result;
+        }
+        body = buffer.attempt();
+        if body.is_none() {
+            return result;
+        }
+
+        drop(result);
+        ctx.metrics
+            .retries
+            .with_label_values(&[&cluster.name, code.name()])
+            .inc();
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Sends the request to an endpoint, then to other ones each time
+/// `hedging_delay` passes without an answer or an attempt fails with a
+/// non-fatal status.
+///
+/// The first response with headers or a fatal status is the answer of the
+/// call, the attempts still pending are cancelled.
+pub async fn send_hedged(
+    ctx: &Arc<ProxyContext>,
+    cluster: &Arc<Cluster>,
+    req: Request<UpstreamBody>,
+    policy: &HedgingPolicy,
+    deadline: Option<Instant>,
+) -> Result<(Response<Incoming>, AttemptGuard), ProxyError> {
+    let (parts, body) = req.into_parts();
+    let buffer = ReplayBuffer::new(body, policy.buffer_limit);
+    // dropping the set cancels the attempts still pending
+    let mut pending = JoinSet::new();
+    let mut tried = Vec::new();
+    let mut sent = 0;
+    let mut next_attempt = Instant::now();
+    let mut last_failure = None;
+    loop {
+        if sent < policy.max_attempts && Instant::now() >= next_attempt {
+            match buffer.attempt() {
+                Some(body) => {
+                    let endpoint = cluster.pick_excluding(&parts.headers, &tried);
+                    tried.push(endpoint.authority.clone());
+                    let mut parts = parts.clone();
+                    if sent > 0 {
+                        parts
+                            .headers
+                            .insert(GRPC_PREVIOUS_RPC_ATTEMPTS, sent.into());
+                        if let Some(deadline) = deadline {
+                            parts.headers.insert(
+                                GRPC_TIMEOUT,
+                                encode_grpc_timeout(
+                                    deadline.saturating_duration_since(Instant::now()),
+                                ),
+                            );
+                        }
+                        ctx.metrics.hedges.with_label_values(&[&cluster.name]).inc();
+                    }
+                    let req = Request::from_parts(parts, body.boxed_unsync());
+                    let (ctx, cluster, attempt) = (ctx.clone(), cluster.clone(), sent);
+                    pending.spawn(async move {
+                        let result = send_to(&ctx, &cluster, &endpoint, req, deadline).await;
+                        (attempt, result)
+                    });
+                    sent += 1;
+                    next_attempt = Instant::now() + policy.hedging_delay;
+                }
+                // the request body is too large to be sent again
+                None => sent = policy.max_attempts,
+            }
+        }
+        if pending.is_empty() && sent >= policy.max_attempts {
+            // every attempt failed with a non-fatal status
+            return last_failure.unwrap();
+        }
+
+        tokio::select! {
+            Some(joined) = pending.join_next() => {
+                let (attempt, result) = joined.map_err(|err| ProxyError::Upstream(err.into()))?;
+                let (code, pushback) = match &result {
+                    Ok((res, _)) => (
+                        Code::from_headers(res.headers()),
+                        Pushback::from_headers(res.headers()),
+                    ),
+                    Err(err) => (Some(err.code()), Pushback::None),
+                };
+                match code {
+                    Some(code) if policy.is_non_fatal(code) => {
+                        next_attempt = match pushback {
+                            Pushback::None => Instant::now(),
+                            Pushback::After(delay) => Instant::now() + delay,
+                            Pushback::Stop => {
+                                sent = policy.max_attempts;
+                                next_attempt
+                            }
+                        };
+                        last_failure = Some(result);
+                    }
+                    _ => {
+                        if attempt > 0 {
+                            ctx.metrics
+                                .hedge_wins
+                                .with_label_values(&[&cluster.name])
+                                .inc();
+                        }
+                        return result;
+                    }
+                }
+            }
+            _ = tokio::time::sleep_until(next_attempt), if sent < policy.max_attempts => {}
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod upgraded_calls;
+pub mod websocket_transport;
@@ -0,0 +1,10 @@
+use std::pin::Pin;
+
+use tokio::sync::mpsc;
+
+pub type UpgradedCall = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Where a call taking over its upgraded connection is sent to be served by
+/// the task of the connection, stored in the request extensions
+#[derive(Clone)]
+pub struct UpgradedCalls(pub mpsc::UnboundedSender<UpgradedCall>);
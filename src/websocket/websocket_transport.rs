@@ -0,0 +1,187 @@
+use async_stream::try_stream;
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::{SinkExt, StreamExt, stream::SplitStream};
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode,
+    header::{
+        CONNECTION, CONTENT_TYPE, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_PROTOCOL,
+        SEC_WEBSOCKET_VERSION, UPGRADE,
+    },
+    request::Parts,
+};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::upgrade::OnUpgrade;
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{Message, handshake::derive_accept_key, protocol::Role},
+};
+use tower::BoxError;
+
+use crate::{
+    core::stream_response::{StreamResponse, UpstreamBody},
+    telemetry::metrics::from_full_bytes,
+    trailers::Trailers,
+};
+
+/// The subprotocol of the grpc-web WebSocket transport of improbable-eng
+pub const PROTOCOL: &str = "grpc-websockets";
+
+/// First byte of a client message carrying a chunk of the request body
+const DATA_FLAG: u8 = 0;
+/// The whole client message half-closing the call
+const FINISH_SEND: u8 = 1;
+
+/// Whether the request opens a WebSocket carrying a grpc-web call
+pub fn is_upgrade(parts: &Parts) -> bool {
+    parts.method == Method::GET
+        && parts
+            .headers
+            .get(UPGRADE)
+            .is_some_and(|upgrade| upgrade.as_bytes().eq_ignore_ascii_case(b"websocket"))
+        && parts
+            .headers
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|protocol| protocol.trim() == PROTOCOL)
+}
+
+/// The response completing the WebSocket handshake, and the connection it
+/// upgrades once sent
+pub fn accept(parts: &mut Parts) -> Result<(StreamResponse, OnUpgrade), BoxError> {
+    if parts
+        .headers
+        .get(SEC_WEBSOCKET_VERSION)
+        .is_none_or(|version| version != "13")
+    {
+        return Err("Unsupported Sec-WebSocket-Version".into());
+    }
+    let key = parts
+        .headers
+        .get(SEC_WEBSOCKET_KEY)
+        .ok_or("Missing Sec-WebSocket-Key")?;
+    let accept_key = HeaderValue::from_str(&derive_accept_key(key.as_bytes()))?;
+    // only HTTP/1.1 connections can be upgraded
+    let upgrade = parts
+        .extensions
+        .remove::<OnUpgrade>()
+        .ok_or("The connection cannot be upgraded")?;
+
+    let mut res = from_full_bytes(Full::new(Bytes::new()));
+    *res.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = res.headers_mut();
+    headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(SEC_WEBSOCKET_ACCEPT, accept_key);
+    headers.insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(PROTOCOL));
+    Ok((res, upgrade))
+}
+
+/// The answer to a WebSocket handshake that cannot be accepted
+pub fn rejected_response() -> StreamResponse {
+    let mut res = from_full_bytes(Full::new(Bytes::new()));
+    *res.status_mut() = StatusCode::BAD_REQUEST;
+    res
+}
+
+/// Serves the call carried by an upgraded connection. Its first message holds
+/// the request headers, then `call` is given the grpc-web request streamed
+/// from the following messages, and its response is written back: the
+/// headers as a first trailers frame, then the body as it is received.
+pub async fn serve<F, Fut>(upgrade: OnUpgrade, parts: Parts, call: F) -> Result<(), BoxError>
+where
+    F: FnOnce(Request<UpstreamBody>) -> Fut,
+    Fut: Future<Output = StreamResponse>,
+{
+    let io = TokioIo::new(upgrade.await?);
+    let (mut sink, mut source) = WebSocketStream::from_raw_socket(io, Role::Server, None)
+        .await
+        .split();
+
+    let headers = loop {
+        match source.next().await {
+            Some(message) => match message? {
+                Message::Binary(data) => break parse_headers(&data)?,
+                Message::Text(text) => break parse_headers(text.as_bytes())?,
+                Message::Close(_) => return Ok(()),
+                _ => continue,
+            },
+            None => return Ok(()),
+        }
+    };
+
+    let mut req = Request::from_parts(parts, StreamBody::new(request_body(source)).boxed_unsync());
+    *req.method_mut() = Method::POST;
+    *req.uri_mut() = req.uri().path().parse()?;
+    *req.headers_mut() = headers;
+    req.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc-web+proto"),
+    );
+
+    let (parts, mut body) = call(req).await.into_parts();
+    // grpc-web clients read the first trailers frame as the response headers
+    let headers = Trailers::new(parts.headers).into_to_frame();
+    sink.send(Message::Binary(headers)).await?;
+    while let Some(frame) = body.frame().await {
+        match frame?.into_data() {
+            Ok(data) => sink.send(Message::Binary(data)).await?,
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    let trailers = Trailers::new(trailers).into_to_frame();
+                    sink.send(Message::Binary(trailers)).await?;
+                }
+            }
+        }
+    }
+    sink.close().await?;
+    Ok(())
+}
+
+/// The `name: value` lines of the first message of the client
+fn parse_headers(data: &[u8]) -> Result<HeaderMap, BoxError> {
+    let mut headers = HeaderMap::new();
+    for line in data.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let colon = line
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or("Invalid header line")?;
+        let name = HeaderName::from_bytes(&line[..colon])?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// The chunks of the request body, each sent after a 0 byte,
+/// until the client half-closes the call with a single 1 byte
+fn request_body<S>(
+    mut source: SplitStream<WebSocketStream<S>>,
+) -> impl Stream<Item = Result<Frame<Bytes>, BoxError>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    try_stream! {
+        while let Some(message) = source.next().await {
+            let data = match message? {
+                Message::Binary(data) => data,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            match data.first() {
+                Some(&DATA_FLAG) => yield Frame::data(data.slice(1..)),
+                Some(&FINISH_SEND) if data.len() == 1 => break,
+                _ => Err("Invalid WebSocket message")?,
+            }
+        }
+    }
+}
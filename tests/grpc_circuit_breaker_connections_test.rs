@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request};
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_grpc_circuit_breaker_connections() -> Result<(), BoxError> {
+    let script = |_: usize, _: &_, request: &HelloRequest| {
+        Reply::Delayed(
+            Duration::from_millis(200),
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    };
+    let first = ScriptedBackend::start(script).await;
+    let second = ScriptedBackend::start(script).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(first.address.clone());
+    config.clusters[0].endpoints.push(second.address.clone());
+    config.clusters[0].circuit_breaker.max_connections = Some(1);
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    // concurrent calls spread over both endpoints, racing to dial
+    let mut calls = Vec::new();
+    for i in 0..16 {
+        let proxy_address = proxy_address.clone();
+        calls.push(tokio::spawn(async move {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            client
+                .say_hello(Request::new(HelloRequest {
+                    name: format!("client {}", i),
+                }))
+                .await
+        }));
+    }
+    for call in calls {
+        if let Err(status) = call.await? {
+            assert_eq!(status.code(), Code::Unavailable);
+            assert!(status.message().contains("max_connections"));
+        }
+    }
+
+    // a single connection was ever opened to the cluster
+    let opened: usize = first.calls().len().min(1) + second.calls().len().min(1);
+    assert_eq!(opened, 1);
+    let metrics = metrics(&proxy_address).await;
+    let connections: i64 = metrics
+        .lines()
+        .filter(|line| line.starts_with("upstream_pool_connections{"))
+        .map(|line| line.rsplit(' ').next().unwrap().parse::<i64>().unwrap())
+        .sum();
+    assert_eq!(connections, 1);
+
+    first.stop();
+    second.stop();
+    Ok(())
+}
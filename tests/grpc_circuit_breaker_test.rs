@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request};
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_circuit_breaker() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Delayed(
+            Duration::from_millis(300),
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.clusters[0].circuit_breaker.max_concurrent_streams = Some(1);
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = |name: &str| Request::new(HelloRequest { name: name.into() });
+
+    // the second call overflows while the first one is in flight
+    let mut first_client = client.clone();
+    let first = tokio::spawn(async move { first_client.say_hello(hello("Alice")).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let status = client.clone().say_hello(hello("Bob")).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.message().contains("max_concurrent_streams"));
+    assert_eq!(first.await??.into_inner().message, "Hello Alice!");
+
+    // the slot is released once the call has completed
+    let res = client.clone().say_hello(hello("Carol")).await?;
+    assert_eq!(res.into_inner().message, "Hello Carol!");
+    assert_eq!(backend.calls().len(), 2);
+
+    assert!(metrics(&proxy_address).await.contains(
+        "upstream_circuit_breaker_overflows_total{cluster=\"default\",limit=\"max_concurrent_streams\"} 1"
+    ));
+
+    backend.stop();
+    Ok(())
+}
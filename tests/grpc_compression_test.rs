@@ -0,0 +1,121 @@
+use bytes::Bytes;
+use http::{HeaderMap, Request};
+use http_body_util::{BodyExt, Full};
+use hyper::client::conn::http2;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use prost::Message;
+use tokio::net::TcpStream;
+
+use griffin::{
+    compression::encoding::Encoding,
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloReply, HelloRequest},
+        scripted_backend::{Reply, ScriptedBackend},
+        utils::encoded_message_to_frame,
+    },
+};
+use tower::BoxError;
+
+/// The response headers, the reply if any and the grpc-status of a call
+/// whose request is compressed in `encoding`
+async fn say_hello(
+    sender: &mut http2::SendRequest<Full<Bytes>>,
+    proxy_address: &str,
+    encoding: &str,
+    accept_encoding: Option<&str>,
+    name: &str,
+) -> Result<(HeaderMap, Option<HelloReply>, String), BoxError> {
+    let request = HelloRequest { name: name.into() };
+    let frame = encoded_message_to_frame(&request, encoding.parse().unwrap_or(Encoding::Gzip));
+    let mut req = Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc")
+    .header("te", "trailers")
+    .header("grpc-encoding", encoding);
+    if let Some(accept_encoding) = accept_encoding {
+        req = req.header("grpc-accept-encoding", accept_encoding);
+    }
+    let res = sender
+        .send_request(req.body(Full::new(frame.freeze()))?)
+        .await?;
+    let headers = res.headers().clone();
+    let body = res.into_body().collect().await?;
+    let status = match body.trailers() {
+        Some(trailers) => trailers["grpc-status"].to_str()?.to_string(),
+        None => headers["grpc-status"].to_str()?.to_string(),
+    };
+    let data = body.to_bytes();
+    let reply = if data.len() < 5 {
+        None
+    } else if data[0] == 1 {
+        let encoding = Encoding::from_headers(&headers)?;
+        Some(HelloReply::decode(
+            &encoding.decompress(&data[5..], 4 << 20)?[..],
+        )?)
+    } else {
+        Some(HelloReply::decode(&data[5..])?)
+    };
+    Ok((headers, reply, status))
+}
+
+#[tokio::test]
+async fn test_grpc_compression() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Compressed(
+            Encoding::Zstd,
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.clusters[0].compression.accept_encodings = vec!["gzip".to_string()];
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let stream = TcpStream::connect(&proxy_address).await?;
+    let (mut sender, conn) = http2::handshake(TokioExecutor::new(), TokioIo::new(stream)).await?;
+    tokio::spawn(conn);
+
+    // a zstd request is re-encoded in the encoding the cluster accepts,
+    // the zstd reply in one the client accepts
+    let (headers, reply, status) =
+        say_hello(&mut sender, &proxy_address, "zstd", None, "Alice").await?;
+    assert_eq!(status, "0");
+    assert_eq!(reply.unwrap().message, "Hello Alice!");
+    assert!(headers.get("grpc-encoding").is_none());
+    let upstream = &backend.calls()[0];
+    assert_eq!(upstream["grpc-encoding"], "gzip");
+    assert_eq!(
+        upstream["grpc-accept-encoding"],
+        "identity,gzip,deflate,zstd"
+    );
+
+    // messages the client decodes are passed through
+    let (headers, reply, status) =
+        say_hello(&mut sender, &proxy_address, "gzip", Some("zstd"), "Bob").await?;
+    assert_eq!(status, "0");
+    assert_eq!(reply.unwrap().message, "Hello Bob!");
+    assert_eq!(headers["grpc-encoding"], "zstd");
+    assert_eq!(backend.calls()[1]["grpc-encoding"], "gzip");
+
+    // an unknown encoding is rejected with the ones to use instead
+    let (headers, reply, status) =
+        say_hello(&mut sender, &proxy_address, "snappy", None, "Carol").await?;
+    assert_eq!(status, "12");
+    assert!(reply.is_none());
+    assert_eq!(
+        headers["grpc-accept-encoding"],
+        "identity,gzip,deflate,zstd"
+    );
+    assert_eq!(backend.calls().len(), 2);
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use clap::Parser;
+use tonic::Request;
+
+use griffin::{
+    command::{args::Args, check_config::check_config},
+    config::config_file::ConfigFile,
+    test_support::{
+        certificates::temp_dir,
+        greeter::{
+            DESCRIPTOR_SET_PATH,
+            hello_world::{HelloRequest, greeter_client::GreeterClient},
+        },
+        preparation::run_intergration_with_config,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_config_file() -> Result<(), BoxError> {
+    let dir = temp_dir("config-file");
+    let path = dir.join("griffin.toml");
+    std::fs::copy(DESCRIPTOR_SET_PATH, dir.join("greeter.bin"))?;
+
+    run_intergration_with_config(
+        |config| {
+            std::fs::write(
+                &path,
+                format!(
+                    r#"
+listen = "127.0.0.1:18080"
+
+[[clusters]]
+name = "greeter"
+endpoints = ["{}"]
+lb_policy = "least_request"
+
+[[routes]]
+path = "/helloworld.Greeter/*"
+cluster = "greeter"
+
+[cors]
+allowed_origins = ["https://app.example.com"]
+
+[transcoding]
+descriptor_set_path = "greeter.bin"
+"#,
+                    config.clusters[0].endpoints[0]
+                ),
+            )
+            .unwrap();
+            assert!(check_config(&path).is_empty());
+            *config = ConfigFile::read(&path).unwrap().parse().unwrap();
+            let transcoding = config.transcoding.as_ref().unwrap();
+            assert_eq!(transcoding.descriptor_set_path, dir.join("greeter.bin"));
+        },
+        async |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            let res = client
+                .say_hello(Request::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap();
+            assert_eq!(res.into_inner().message, "Hello Alice!");
+            Ok(())
+        },
+    )
+    .await?;
+
+    // flags override the file
+    let args = Args::try_parse_from([
+        "griffin",
+        "--config",
+        path.to_str().unwrap(),
+        "--proxy-port",
+        "9090",
+        "--forward-port",
+        "4000",
+    ])?;
+    let config = args.into_config()?;
+    assert_eq!(config.listen, "127.0.0.1:9090");
+    assert_eq!(config.cors.allowed_origins, ["https://app.example.com"]);
+    // the forward flags add the default cluster next to the file's one
+    assert_eq!(config.clusters.len(), 2);
+    assert_eq!(config.clusters[1].endpoints, ["127.0.0.1:4000"]);
+
+    // every error is reported with its location, paths relative to the file
+    std::fs::write(
+        &path,
+        r#"[[clusters]]
+name = "greeter"
+endpoints = ["bad host"]
+
+[[routes]]
+path = "*"
+cluster = "missing"
+
+[tls]
+cert_path = "missing.crt"
+key_path = "missing.key"
+"#,
+    )?;
+    let errors: Vec<String> = check_config(&path).iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(
+        errors[0].contains("griffin.toml:3:14: clusters[0].endpoints[0]"),
+        "{:?}",
+        errors
+    );
+    assert!(errors[1].contains("griffin.toml:7:11: routes[0].cluster"));
+    assert!(errors[2].contains("griffin.toml:9:1: tls"));
+    assert!(errors[2].contains(dir.join("missing.crt").to_str().unwrap()));
+
+    // the certificate watcher needs a positive interval
+    std::fs::write(
+        &path,
+        r#"[tls]
+cert_path = "tls.crt"
+key_path = "tls.key"
+reload_interval_ms = 0
+"#,
+    )?;
+    let errors: Vec<String> = check_config(&path).iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(
+        errors[0].contains("griffin.toml:4:22") && errors[0].contains("reload_interval_ms"),
+        "{:?}",
+        errors
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Code, Request, transport::Channel};
+
+use griffin::{
+    config::{config_file::ConfigFile, config_reloader::ConfigReloader},
+    start_proxy_with_reloader,
+    test_support::{
+        certificates::temp_dir,
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::MockBackend,
+    },
+};
+use tower::BoxError;
+
+fn write_config(path: &Path, endpoint: &str) {
+    std::fs::write(
+        path,
+        format!(
+            r#"
+[[clusters]]
+name = "greeter"
+endpoints = ["{}"]
+
+[[routes]]
+path = "/helloworld.Greeter/*"
+cluster = "greeter"
+"#,
+            endpoint
+        ),
+    )
+    .unwrap();
+}
+
+async fn say_hello(client: &mut GreeterClient<Channel>) -> Result<String, Code> {
+    client
+        .say_hello(Request::new(HelloRequest {
+            name: "Alice".into(),
+        }))
+        .await
+        .map(|res| res.into_inner().message)
+        .map_err(|status| status.code())
+}
+
+async fn reload_failures(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find(|line| line.starts_with("config_reload_failures_total "))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_grpc_config_reload() -> Result<(), BoxError> {
+    let backend = MockBackend::start(None).await;
+    // a port nothing listens on
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let closed_address = closed.local_addr()?.to_string();
+    drop(closed);
+
+    let dir = temp_dir("config-reload");
+    let path = dir.join("griffin.toml");
+    write_config(&path, &backend.address);
+    let config = ConfigFile::read(&path)?.parse()?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let (reload_tx, reload_rx) = tokio::sync::watch::channel(());
+    let reloader = ConfigReloader::new(
+        path.clone(),
+        Duration::from_millis(50),
+        reload_rx,
+        move || Ok(ConfigFile::read(&path)?.parse()?),
+    );
+    tokio::spawn(start_proxy_with_reloader(
+        listener,
+        config,
+        shutdown_rx,
+        Some(reloader),
+    ));
+    let path = dir.join("griffin.toml");
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    assert_eq!(say_hello(&mut client).await.unwrap(), "Hello Alice!");
+
+    // a bidi stream opened with the first configuration
+    let (tx, rx) = mpsc::channel(4);
+    let hello = |name: &str| HelloRequest { name: name.into() };
+    tx.send(hello("client request 1")).await?;
+    let mut replies = client
+        .say_hello_bi_stream(ReceiverStream::new(rx))
+        .await?
+        .into_inner();
+    assert_eq!(replies.message().await?.unwrap().message, "first ok");
+
+    // the modified file sends new calls to the unreachable endpoint
+    write_config(&path, &closed_address);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while say_hello(&mut client).await.is_ok() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    assert_eq!(say_hello(&mut client).await, Err(Code::Unavailable));
+
+    // while the stream opened before keeps its upstream
+    tx.send(hello("client request 2")).await?;
+    assert_eq!(replies.message().await?.unwrap().message, "second ok");
+
+    // an invalid configuration is rejected, counted, and the current one kept
+    std::fs::write(&path, "[[routes]]\npath = \"*\"\ncluster = \"missing\"\n")?;
+    reload_tx.send(())?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while reload_failures(&proxy_address).await.is_empty()
+            || reload_failures(&proxy_address).await == "config_reload_failures_total 0"
+        {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    assert_eq!(say_hello(&mut client).await, Err(Code::Unavailable));
+
+    // a reload triggered by hand, as on SIGHUP
+    write_config(&path, &backend.address);
+    reload_tx.send(())?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while say_hello(&mut client).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+
+    drop(tx);
+    backend.stop().await;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
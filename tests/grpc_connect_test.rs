@@ -0,0 +1,156 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{Request, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use prost::Message;
+use serde_json::{Value, json};
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::{
+            DESCRIPTOR_SET_PATH,
+            hello_world::{HelloReply, HelloRequest},
+        },
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+/// The flags and payload of every envelope of a streaming Connect body
+fn envelopes(mut body: &[u8]) -> Vec<(u8, Bytes)> {
+    let mut envelopes = Vec::new();
+    while body.len() >= 5 {
+        let len = u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+        envelopes.push((body[0], Bytes::copy_from_slice(&body[5..5 + len])));
+        body = &body[5 + len..];
+    }
+    envelopes
+}
+
+fn hello(name: &str) -> Vec<u8> {
+    HelloRequest {
+        name: name.to_string(),
+    }
+    .encode_to_vec()
+}
+
+#[tokio::test]
+async fn test_grpc_connect() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| match request.name.as_str() {
+        "nobody" => Reply::Status(5),
+        name => Reply::Message(format!("Hello {}!", name)),
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let url = format!("http://{}/helloworld.Greeter/SayHello", proxy_address);
+    let call = |req: Request<Full<Bytes>>| {
+        let client = client.clone();
+        async move {
+            let res = client.request(req).await?;
+            let status = res.status();
+            let content_type = res.headers()["content-type"].clone();
+            let body = res.into_body().collect().await?.to_bytes();
+            Ok::<_, BoxError>((status, content_type, body))
+        }
+    };
+
+    // a unary call with a protobuf message, and a deadline
+    let req = Request::post(&url)
+        .header("content-type", "application/proto")
+        .header("connect-protocol-version", "1")
+        .header("connect-timeout-ms", "1500")
+        .body(Full::new(Bytes::from(hello("Alice"))))?;
+    let (status, content_type, body) = call(req).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/proto");
+    assert_eq!(HelloReply::decode(body)?.message, "Hello Alice!");
+    let calls = backend.calls();
+    assert_eq!(calls[0]["content-type"], "application/grpc");
+    assert_eq!(calls[0]["grpc-timeout"], "1500000u");
+
+    // a unary call with a JSON message
+    let req = Request::post(&url)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(r#"{"name":"Bob"}"#)))?;
+    let (status, content_type, body) = call(req).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    let reply: Value = serde_json::from_slice(&body)?;
+    assert_eq!(reply, json!({ "message": "Hello Bob!" }));
+
+    // the gRPC status as an HTTP status and a Connect error
+    let req = Request::post(&url)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(r#"{"name":"nobody"}"#)))?;
+    let (status, content_type, body) = call(req).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(content_type, "application/json");
+    let error: Value = serde_json::from_slice(&body)?;
+    assert_eq!(error["code"], "not_found");
+
+    // unary GET requests, the message in the query
+    let query = format!(
+        "connect=v1&encoding=proto&base64=1&message={}",
+        URL_SAFE_NO_PAD.encode(hello("Carol"))
+    );
+    let req = Request::get(format!("{}?{}", url, query)).body(Full::default())?;
+    let (status, _, body) = call(req).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(HelloReply::decode(body)?.message, "Hello Carol!");
+
+    let query = "connect=v1&encoding=json&message=%7B%22name%22%3A%22Dan%22%7D";
+    let req = Request::get(format!("{}?{}", url, query)).body(Full::default())?;
+    let (status, content_type, body) = call(req).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    let reply: Value = serde_json::from_slice(&body)?;
+    assert_eq!(reply, json!({ "message": "Hello Dan!" }));
+    assert_eq!(backend.calls().len(), 5);
+
+    // streaming calls end with an end-of-stream message
+    let streaming = |message: Vec<u8>| {
+        let mut framed = BytesMut::new();
+        framed.put_u8(0);
+        framed.put_u32(message.len() as u32);
+        framed.put_slice(&message);
+        Request::post(&url)
+            .header("content-type", "application/connect+proto")
+            .body(Full::new(framed.freeze()))
+    };
+    let (status, content_type, body) = call(streaming(hello("Erin"))?).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/connect+proto");
+    let received = envelopes(&body);
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].0, 0);
+    assert_eq!(
+        HelloReply::decode(received[0].1.clone())?.message,
+        "Hello Erin!"
+    );
+    assert_eq!(received[1].0, 2);
+    let end: Value = serde_json::from_slice(&received[1].1)?;
+    assert!(end.get("error").is_none());
+
+    // including Trailers-Only responses
+    let (status, _, body) = call(streaming(hello("nobody"))?).await?;
+    assert_eq!(status, StatusCode::OK);
+    let received = envelopes(&body);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].0, 2);
+    let end: Value = serde_json::from_slice(&received[0].1)?;
+    assert_eq!(end["error"]["code"], "not_found");
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use tonic::{Code, Request};
+
+use griffin::{
+    config::upstream_tls_config::UpstreamTlsConfig,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::run_intergration_with_config,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_connect_timeout() -> Result<(), BoxError> {
+    // accepts TCP connections, then never answers the TLS handshake
+    let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let silent_address = silent.local_addr()?.to_string();
+    tokio::spawn(async move {
+        let mut accepted = Vec::new();
+        while let Ok((stream, _)) = silent.accept().await {
+            accepted.push(stream);
+        }
+    });
+
+    run_intergration_with_config(
+        |config| {
+            config.pool.connect_timeout_ms = 200;
+            config.clusters[0].endpoints = vec![silent_address.clone()];
+            config.clusters[0].tls = Some(UpstreamTlsConfig {
+                ca_path: None,
+                client_cert_path: None,
+                client_key_path: None,
+                server_name: None,
+                insecure_skip_verify: true,
+            });
+        },
+        async |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+
+            // the dial gives up instead of holding the call
+            let started = Instant::now();
+            let status = client
+                .say_hello(Request::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unavailable);
+            assert!(started.elapsed() < Duration::from_secs(5));
+            Ok(())
+        },
+    )
+    .await
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper::client::conn::http2;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use tokio::{net::TcpStream, task::JoinHandle};
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::HelloRequest,
+        scripted_backend::{Reply, ScriptedBackend},
+        utils::message_to_frame,
+    },
+};
+use tower::BoxError;
+
+/// A raw HTTP/2 connection to the proxy, and the task ending when it closes
+async fn connect(
+    proxy_address: &str,
+) -> Result<(http2::SendRequest<Full<Bytes>>, JoinHandle<()>), BoxError> {
+    let stream = TcpStream::connect(proxy_address).await?;
+    let (sender, conn) = http2::handshake(TokioExecutor::new(), TokioIo::new(stream)).await?;
+    let closed = tokio::spawn(async move {
+        let _ = conn.await;
+    });
+    Ok((sender, closed))
+}
+
+async fn say_hello(
+    sender: &mut http2::SendRequest<Full<Bytes>>,
+    proxy_address: &str,
+    name: &str,
+) -> Result<String, BoxError> {
+    let frame = message_to_frame(&HelloRequest { name: name.into() });
+    let req = Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc")
+    .header("te", "trailers")
+    .body(Full::new(frame.freeze()))?;
+    let res = sender.send_request(req).await?;
+    let body = res.into_body().collect().await?;
+    let trailers = body.trailers().cloned().unwrap_or_default();
+    Ok(trailers["grpc-status"].to_str()?.to_string())
+}
+
+#[tokio::test]
+async fn test_grpc_connection_lifetime() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        let reply = Reply::Message(format!("Hello {}!", request.name));
+        match request.name.as_str() {
+            "slow" => Reply::Delayed(Duration::from_millis(1500), Box::new(reply)),
+            _ => reply,
+        }
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.server.max_connection_age_ms = Some(1000);
+    config.server.max_connection_idle_ms = Some(300);
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    // an idle connection goes away
+    let (mut idle, idle_closed) = connect(&proxy_address).await?;
+    assert_eq!(say_hello(&mut idle, &proxy_address, "Alice").await?, "0");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!idle_closed.is_finished());
+    tokio::time::timeout(Duration::from_secs(1), idle_closed).await??;
+
+    // a busy one past its max age is only closed once its calls complete
+    let started = Instant::now();
+    let (mut busy, busy_closed) = connect(&proxy_address).await?;
+    assert_eq!(say_hello(&mut busy, &proxy_address, "slow").await?, "0");
+    assert!(started.elapsed() >= Duration::from_millis(1500));
+    tokio::time::timeout(Duration::from_millis(500), busy_closed).await??;
+    assert!(busy.is_closed());
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use bytes::Bytes;
+use futures_util::future::try_join_all;
+use http::Request;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request as GrpcRequest;
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+    preparation::run_intergration_with_config,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_connection_pool_burst() -> Result<(), BoxError> {
+    run_intergration_with_config(
+        |config| config.pool.max_connections_per_authority = 1,
+        async move |proxy_address| {
+            let client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+
+            // concurrent calls before any upstream connection is open
+            let calls = (0..20).map(|i| {
+                let mut client = client.clone();
+                async move {
+                    let name = format!("Client {}", i);
+                    let res = client
+                        .say_hello(GrpcRequest::new(HelloRequest { name: name.clone() }))
+                        .await?;
+                    assert_eq!(res.into_inner().message, format!("Hello {}!", name));
+                    Ok::<_, BoxError>(())
+                }
+            });
+            try_join_all(calls).await?;
+
+            let client = Client::builder(TokioExecutor::new()).build_http();
+            let req = Request::get(format!("http://{}/metrics", proxy_address))
+                .body(Empty::<Bytes>::new())
+                .unwrap();
+            let res = client.request(req).await.unwrap();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let metrics = String::from_utf8(body.to_vec()).unwrap();
+
+            // they all waited for the single connection allowed
+            let connections = metrics
+                .lines()
+                .find(|line| line.starts_with("upstream_pool_connections{"))
+                .unwrap();
+            assert!(connections.ends_with(" 1"), "{}", connections);
+            Ok(())
+        },
+    )
+    .await
+}
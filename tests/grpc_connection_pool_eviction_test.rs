@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request;
+
+use griffin::{
+    config::{config_file::ConfigFile, config_reloader::ConfigReloader},
+    start_proxy_with_reloader,
+    test_support::{
+        certificates::temp_dir,
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::MockBackend,
+    },
+};
+use tower::BoxError;
+
+fn write_config(path: &Path, endpoint: &str) {
+    std::fs::write(
+        path,
+        format!(
+            r#"
+[[clusters]]
+name = "greeter"
+endpoints = ["{}"]
+
+[[routes]]
+path = "/helloworld.Greeter/*"
+cluster = "greeter"
+"#,
+            endpoint
+        ),
+    )
+    .unwrap();
+}
+
+async fn pool_metrics(proxy_address: &str) -> Vec<String> {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("upstream_pool_"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_grpc_connection_pool_eviction() -> Result<(), BoxError> {
+    let first = MockBackend::start(None).await;
+    let second = MockBackend::start(None).await;
+
+    let dir = temp_dir("connection-pool-eviction");
+    let path = dir.join("griffin.toml");
+    write_config(&path, &first.address);
+    let config = ConfigFile::read(&path)?.parse()?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let (reload_tx, reload_rx) = tokio::sync::watch::channel(());
+    let reloader = ConfigReloader::new(
+        path.clone(),
+        Duration::from_millis(50),
+        reload_rx,
+        move || Ok(ConfigFile::read(&path)?.parse()?),
+    );
+    tokio::spawn(start_proxy_with_reloader(
+        listener,
+        config,
+        shutdown_rx,
+        Some(reloader),
+    ));
+    let path = dir.join("griffin.toml");
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let mut say_hello = async move || {
+        client
+            .say_hello(Request::new(HelloRequest {
+                name: "Alice".into(),
+            }))
+            .await
+            .unwrap();
+    };
+    say_hello().await;
+    let first_label = format!("authority=\"{}\"", first.address);
+    let second_label = format!("authority=\"{}\"", second.address);
+    let metrics = pool_metrics(&proxy_address).await;
+    assert!(
+        metrics.iter().any(|line| line.contains(&first_label)),
+        "{:?}",
+        metrics
+    );
+
+    // the endpoint removed by a reload loses its pool and its metrics
+    write_config(&path, &second.address);
+    reload_tx.send(())?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            say_hello().await;
+            let metrics = pool_metrics(&proxy_address).await;
+            if metrics.iter().any(|line| line.contains(&second_label))
+                && !metrics.iter().any(|line| line.contains(&first_label))
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+
+    first.stop().await;
+    second.stop().await;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::Request;
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::greeter::{
+        MyGreeter,
+        hello_world::{HelloRequest, greeter_client::GreeterClient, greeter_server::GreeterServer},
+    },
+};
+use tower::BoxError;
+
+async fn pool_connections(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find(|line| line.starts_with("upstream_pool_connections{"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_grpc_connection_pool_peer_limit() -> Result<(), BoxError> {
+    // a backend accepting a single stream per connection
+    let backend = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let backend_address = backend.local_addr()?.to_string();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .max_concurrent_streams(1)
+            .add_service(GreeterServer::new(MyGreeter {}))
+            .serve_with_incoming(TcpListenerStream::new(backend)),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(
+        listener,
+        ProxyConfig::new(backend_address),
+        shutdown_rx,
+    ));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let (tx, rx) = mpsc::channel(4);
+    tx.send(HelloRequest {
+        name: "client request 1".into(),
+    })
+    .await?;
+    let mut replies = client
+        .say_hello_bi_stream(ReceiverStream::new(rx))
+        .await?
+        .into_inner();
+    assert_eq!(replies.message().await?.unwrap().message, "first ok");
+
+    // the stream left open saturates its connection, another one is opened
+    let res = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.say_hello(Request::new(HelloRequest {
+            name: "Alice".into(),
+        })),
+    )
+    .await??;
+    assert_eq!(res.into_inner().message, "Hello Alice!");
+    let connections = pool_connections(&proxy_address).await;
+    assert!(connections.ends_with(" 2"), "{}", connections);
+
+    drop(tx);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request as GrpcRequest;
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+    preparation::run_intergration,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_connection_pool() -> Result<(), BoxError> {
+    run_intergration(async move |proxy_address| {
+        for name in ["Alice", "Bob", "Carol"] {
+            // a new downstream connection for every call
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            let res = client
+                .say_hello(GrpcRequest::new(HelloRequest { name: name.into() }))
+                .await
+                .unwrap();
+            assert_eq!(res.into_inner().message, format!("Hello {}!", name));
+        }
+
+        let client = Client::builder(TokioExecutor::new()).build_http();
+        let req = Request::get(format!("http://{}/metrics", proxy_address))
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        let res = client.request(req).await.unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+
+        // every call was multiplexed on the same upstream connection
+        let connections = metrics
+            .lines()
+            .find(|line| line.starts_with("upstream_pool_connections{"))
+            .unwrap();
+        assert!(connections.ends_with(" 1"), "{}", connections);
+        let in_use = metrics
+            .lines()
+            .find(|line| line.starts_with("upstream_pool_streams_in_use{"))
+            .unwrap();
+        assert!(in_use.ends_with(" 0"), "{}", in_use);
+
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,59 @@
+use futures_util::StreamExt;
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{
+        cluster_config::ClusterConfig, proxy_config::DEFAULT_CLUSTER, route_config::RouteConfig,
+        upstream_tls_config::UpstreamTlsConfig,
+    },
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::run_intergration_with_config,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_connection_pool_tls() -> Result<(), BoxError> {
+    run_intergration_with_config(
+        |config| {
+            // the same cleartext backend, reached with TLS by another cluster
+            let mut tls = ClusterConfig::new("tls", config.clusters[0].endpoints[0].clone());
+            tls.tls = Some(UpstreamTlsConfig {
+                insecure_skip_verify: true,
+                ..UpstreamTlsConfig::default()
+            });
+            config.clusters.push(tls);
+            config.routes = vec![
+                RouteConfig::new("/helloworld.Greeter/SayHello", "tls"),
+                RouteConfig::new("*", DEFAULT_CLUSTER),
+            ];
+        },
+        async move |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+
+            // opens a cleartext connection to the backend
+            let res = client
+                .say_hello_stream(Request::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap();
+            let replies: Vec<_> = res.into_inner().collect().await;
+            assert_eq!(replies[0].as_ref().unwrap().message, "first ok");
+
+            // which is not reused by the TLS cluster, whose handshake fails
+            let status = client
+                .say_hello(Request::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unavailable);
+            Ok(())
+        },
+    )
+    .await
+}
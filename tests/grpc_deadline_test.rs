@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Code, Request};
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+    preparation::run_intergration_with_config,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_deadline() -> Result<(), BoxError> {
+    run_intergration_with_config(
+        |config| config.routes[0].max_timeout_ms = Some(500),
+        async |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            let hello = |name: &str| HelloRequest { name: name.into() };
+
+            // calls finishing in time are not affected
+            let res = client
+                .say_hello(Request::new(hello("Alice")))
+                .await
+                .unwrap();
+            assert_eq!(res.into_inner().message, "Hello Alice!");
+
+            // the backend answers the first message then waits for the client,
+            // the proxy ends the stream once the timeout of the call passes
+            for timeout in [Some("100m"), Some("150000u"), Some("1S"), None] {
+                let (tx, rx) = mpsc::channel(4);
+                tx.send(hello("client request 1")).await.unwrap();
+                let mut req = Request::new(ReceiverStream::new(rx));
+                if let Some(timeout) = timeout {
+                    req.metadata_mut()
+                        .insert("grpc-timeout", timeout.parse().unwrap());
+                }
+                let started = Instant::now();
+                let mut replies = client.say_hello_bi_stream(req).await.unwrap().into_inner();
+                assert_eq!(
+                    replies.message().await.unwrap().unwrap().message,
+                    "first ok"
+                );
+
+                let status = replies.message().await.unwrap_err();
+                assert_eq!(status.code(), Code::DeadlineExceeded, "{:?}", timeout);
+                let elapsed = started.elapsed();
+                let expected = match timeout {
+                    Some("100m") => Duration::from_millis(100),
+                    Some("150000u") => Duration::from_millis(150),
+                    // capped, or injected, by the route
+                    _ => Duration::from_millis(500),
+                };
+                assert!(elapsed >= expected, "{:?} {:?}", timeout, elapsed);
+                assert!(
+                    elapsed < expected + Duration::from_millis(400),
+                    "{:?} {:?}",
+                    timeout,
+                    elapsed
+                );
+                drop(tx);
+            }
+            Ok(())
+        },
+    )
+    .await
+}
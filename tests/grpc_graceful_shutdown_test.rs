@@ -0,0 +1,87 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::Channel;
+use tonic_health::pb::{
+    HealthCheckRequest, health_check_response::ServingStatus, health_client::HealthClient,
+};
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::MockBackend,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_graceful_shutdown() -> Result<(), BoxError> {
+    let backend = MockBackend::start(None).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.drain_timeout_ms = 500;
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let proxy_task = tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let channel = Channel::from_shared(format!("http://{}", proxy_address))?
+        .connect()
+        .await?;
+
+    // a bidi stream in flight when the shutdown starts
+    let mut client = GreeterClient::new(channel.clone());
+    let (tx, rx) = mpsc::channel(4);
+    let hello = |name: &str| HelloRequest { name: name.into() };
+    tx.send(hello("client request 1")).await?;
+    let mut replies = client
+        .say_hello_bi_stream(ReceiverStream::new(rx))
+        .await?
+        .into_inner();
+    assert_eq!(replies.message().await?.unwrap().message, "first ok");
+
+    // a health watch never ends on its own, it holds the drain until the deadline
+    let mut health = HealthClient::new(channel);
+    let mut watch = health
+        .watch(HealthCheckRequest {
+            service: String::new(),
+        })
+        .await?
+        .into_inner();
+    watch.message().await?.unwrap();
+
+    let shutdown_at = Instant::now();
+    shutdown_tx.send(true)?;
+    let draining = watch.message().await?.unwrap();
+    assert_eq!(draining.status(), ServingStatus::NotServing);
+
+    // new connections are refused while draining
+    let mut refused = false;
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(&proxy_address)
+            .await
+            .is_err()
+        {
+            refused = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(refused);
+
+    // the stream in flight still completes
+    tx.send(hello("client request 2")).await?;
+    assert_eq!(replies.message().await?.unwrap().message, "second ok");
+    drop(tx);
+    while replies.message().await?.is_some() {}
+
+    // the proxy returns once the deadline closed the remaining connection
+    tokio::time::timeout(Duration::from_secs(5), proxy_task).await???;
+    assert!(shutdown_at.elapsed() >= Duration::from_millis(500));
+    assert!(watch.message().await.is_err());
+
+    backend.stop().await;
+    Ok(())
+}
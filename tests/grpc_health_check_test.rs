@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request as GrpcRequest;
+
+use griffin::{
+    config::health_check_config::HealthCheckConfig,
+    test_support::{
+        greeter::{
+            MyGreeter,
+            hello_world::{
+                HelloRequest, greeter_client::GreeterClient, greeter_server::GreeterServer,
+            },
+        },
+        preparation::{MockBackend, run_intergration_with_config},
+    },
+};
+use tower::BoxError;
+
+async fn metric(proxy_address: &str, name: &str, endpoint: &str) -> Option<String> {
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let req = Request::get(format!("http://{}/metrics", proxy_address))
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let res = client.request(req).await.unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find(|line| {
+            line.starts_with(&format!("{}{{", name))
+                && line.contains(&format!("endpoint=\"{}\"", endpoint))
+        })
+        .map(|line| line.rsplit(' ').next().unwrap().to_string())
+}
+
+async fn wait_healthy(proxy_address: &str, endpoint: &str, healthy: &str) {
+    for _ in 0..100 {
+        let value = metric(proxy_address, "upstream_endpoint_healthy", endpoint).await;
+        if value.as_deref() == Some(healthy) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{} never became healthy={}", endpoint, healthy);
+}
+
+#[tokio::test]
+async fn test_grpc_health_check() -> Result<(), BoxError> {
+    let second = MockBackend::start(None).await;
+    let second_address = second.address.clone();
+    let health = second.health.clone();
+
+    run_intergration_with_config(
+        |config| {
+            config.clusters[0].endpoints.push(second_address.clone());
+            config.clusters[0].health_check = Some(HealthCheckConfig {
+                interval_ms: 20,
+                timeout_ms: 500,
+                service: "helloworld.Greeter".to_string(),
+                healthy_threshold: 1,
+                unhealthy_threshold: 1,
+            });
+        },
+        async |proxy_address| {
+            health.set_not_serving::<GreeterServer<MyGreeter>>().await;
+            wait_healthy(&proxy_address, &second_address, "0").await;
+
+            // every call goes to the endpoint left in rotation
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            for _ in 0..4 {
+                let res = client
+                    .say_hello(GrpcRequest::new(HelloRequest {
+                        name: "Alice".into(),
+                    }))
+                    .await
+                    .unwrap();
+                assert_eq!(res.into_inner().message, "Hello Alice!");
+            }
+            let second_requests = metric(
+                &proxy_address,
+                "upstream_endpoint_requests_total",
+                &second_address,
+            )
+            .await;
+            assert_eq!(second_requests, None);
+
+            // back in rotation once serving again
+            health.set_serving::<GreeterServer<MyGreeter>>().await;
+            wait_healthy(&proxy_address, &second_address, "1").await;
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    second.stop().await;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{hedging_policy_config::HedgingPolicyConfig, proxy_config::ProxyConfig},
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_hedging_buffer_limit() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, _| Reply::Status(14)).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.routes[0].hedging_policy = Some(HedgingPolicyConfig {
+        max_attempts: 3,
+        hedging_delay_ms: 1000,
+        non_fatal_status_codes: vec!["UNAVAILABLE".to_string()],
+        buffer_limit_bytes: 16,
+    });
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+
+    // the request outgrows the buffer, so the failure of the first attempt
+    // is the answer of the call
+    let mut req = Request::new(HelloRequest {
+        name: "a name longer than the buffer".into(),
+    });
+    req.set_timeout(Duration::from_secs(5));
+    let status = client.say_hello(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(backend.calls().len(), 1);
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{hedging_policy_config::HedgingPolicyConfig, proxy_config::ProxyConfig},
+    core::grpc_timeout::parse_grpc_timeout,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_hedging() -> Result<(), BoxError> {
+    let script = |server: &'static str, delay: Duration| {
+        move |_, _: &_, request: &HelloRequest| match request.name.as_str() {
+            "down" => Reply::Status(14),
+            "invalid" => Reply::Status(3),
+            name => Reply::Delayed(
+                delay,
+                Box::new(Reply::Message(format!("Hello {} from {}", name, server))),
+            ),
+        }
+    };
+    let slow = ScriptedBackend::start(script("slow", Duration::from_secs(1))).await;
+    let fast = ScriptedBackend::start(script("fast", Duration::ZERO)).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(slow.address.clone());
+    config.clusters[0].endpoints.push(fast.address.clone());
+    config.routes[0].hedging_policy = Some(HedgingPolicyConfig {
+        max_attempts: 2,
+        hedging_delay_ms: 200,
+        non_fatal_status_codes: vec!["UNAVAILABLE".to_string()],
+        ..HedgingPolicyConfig::default()
+    });
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = |name: &str| Request::new(HelloRequest { name: name.into() });
+
+    // round robin sends the first attempt to the slow server,
+    // the hedge sent after the delay answers first
+    // with the time left before the deadline of the call
+    let started = Instant::now();
+    let mut req = hello("Alice");
+    req.set_timeout(Duration::from_secs(5));
+    let res = client.say_hello(req).await?;
+    assert_eq!(res.into_inner().message, "Hello Alice from fast");
+    assert!(started.elapsed() < Duration::from_millis(800));
+    assert_eq!(fast.calls()[0]["grpc-previous-rpc-attempts"], "1");
+    let timeout = parse_grpc_timeout(&fast.calls()[0]["grpc-timeout"]).unwrap();
+    assert!(timeout <= Duration::from_millis(4800));
+
+    // any other status is the answer
+    let status = client.say_hello(hello("invalid")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(slow.calls().len(), 2);
+
+    // a fast answer needs no hedge
+    let res = client.say_hello(hello("Bob")).await?;
+    assert_eq!(res.into_inner().message, "Hello Bob from fast");
+    assert_eq!(fast.calls().len(), 2);
+    assert_eq!(slow.calls().len(), 2);
+
+    // a non-fatal status sends the next hedge at once
+    let started = Instant::now();
+    let status = client.say_hello(hello("down")).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(started.elapsed() < Duration::from_millis(200));
+    assert_eq!(slow.calls().len(), 3);
+    assert_eq!(fast.calls().len(), 3);
+
+    let metrics = metrics(&proxy_address).await;
+    assert!(metrics.contains("upstream_hedges_total{cluster=\"default\"} 2"));
+    assert!(metrics.contains("upstream_hedge_wins_total{cluster=\"default\"} 1"));
+
+    slow.stop();
+    fast.stop();
+    Ok(())
+}
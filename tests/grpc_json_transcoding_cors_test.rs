@@ -0,0 +1,64 @@
+use bytes::Bytes;
+use http::{Method, Request};
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::{Value, json};
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::DESCRIPTOR_SET_PATH,
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_json_transcoding_cors() -> Result<(), BoxError> {
+    let backend =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.cors.allowed_origins = vec!["https://app.example.com".to_string()];
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let url = format!("http://{}/v1/greeter:sayHello", proxy_address);
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    // the preflight allows the methods of the HTTP rules
+    let preflight = Request::builder()
+        .method(Method::OPTIONS)
+        .uri(&url)
+        .header("origin", "https://app.example.com")
+        .header("access-control-request-method", "PUT")
+        .body(Full::default())?;
+    let res = client.request(preflight).await?;
+    assert_eq!(res.status(), 204);
+    let methods = res.headers()["access-control-allow-methods"].to_str()?;
+    for method in ["GET", "POST", "PUT", "OPTIONS"] {
+        assert!(methods.split(", ").any(|m| m == method), "{}", methods);
+    }
+
+    let req = Request::put(&url)
+        .header("origin", "https://app.example.com")
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(r#"{"name":"Alice"}"#)))?;
+    let res = client.request(req).await?;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["access-control-allow-origin"],
+        "https://app.example.com"
+    );
+    let body = res.into_body().collect().await?.to_bytes();
+    let reply: Value = serde_json::from_slice(&body)?;
+    assert_eq!(reply, json!({ "message": "Hello Alice!" }));
+
+    backend.stop();
+    Ok(())
+}
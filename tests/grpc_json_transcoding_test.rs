@@ -0,0 +1,79 @@
+use bytes::Bytes;
+use http::{Method, Request, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::{Value, json};
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::DESCRIPTOR_SET_PATH,
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_json_transcoding() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| match request.name.as_str() {
+        "nobody" => Reply::Status(5),
+        name => Reply::Message(format!("Hello {}!", name)),
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let call = |method: Method, path: &str, body: &str| {
+        let req = Request::builder()
+            .method(method)
+            .uri(format!("http://{}{}", proxy_address, path))
+            .header("content-type", "application/json")
+            .body(Full::new(Bytes::from(body.to_string())))
+            .unwrap();
+        let client = client.clone();
+        async move {
+            let res = client.request(req).await?;
+            let status = res.status();
+            assert_eq!(res.headers()["content-type"], "application/json");
+            let body = res.into_body().collect().await?.to_bytes();
+            Ok::<_, BoxError>((status, serde_json::from_slice::<Value>(&body)?))
+        }
+    };
+
+    // a path variable
+    let (status, body) = call(Method::GET, "/v1/greeter/Alice%20B", "").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "message": "Hello Alice B!" }));
+    assert_eq!(backend.calls()[0]["content-type"], "application/grpc");
+
+    // query parameters
+    let (status, body) = call(Method::GET, "/v1/greeter?name=Bob", "").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "message": "Hello Bob!" }));
+
+    // the whole JSON body, with a custom verb
+    let (status, body) = call(Method::POST, "/v1/greeter:sayHello", r#"{"name":"Carol"}"#).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "message": "Hello Carol!" }));
+
+    // the gRPC status as an HTTP status and a JSON error
+    let (status, body) = call(Method::GET, "/v1/greeter/nobody", "").await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], 5);
+
+    // a body that does not fit the request message
+    let (status, body) = call(Method::POST, "/v1/greeter:sayHello", r#"{"nom":"Dan"}"#).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], 3);
+    assert_eq!(backend.calls().len(), 4);
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use tonic::{Code, Request, transport::Channel};
+
+use griffin::{
+    config::{config_file::ConfigFile, config_reloader::ConfigReloader},
+    start_proxy_with_reloader,
+    test_support::{
+        certificates::temp_dir,
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+fn write_config(path: &Path, endpoint: &str, limits: &str) {
+    std::fs::write(
+        path,
+        format!(
+            r#"
+[[clusters]]
+name = "greeter"
+endpoints = ["{}"]
+
+[[routes]]
+path = "/helloworld.Greeter/*"
+cluster = "greeter"
+
+[limits]
+queue_timeout_ms = 0
+{}
+"#,
+            endpoint, limits
+        ),
+    )
+    .unwrap();
+}
+
+type MetricsClient = Client<HttpConnector, Empty<Bytes>>;
+
+async fn reloads(client: &MetricsClient, proxy_address: &str) -> String {
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find(|line| line.starts_with("config_reloads_total "))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The outcome of two calls sent at once
+async fn concurrent_calls(client: &GreeterClient<Channel>) -> Vec<Result<(), Code>> {
+    let calls: Vec<_> = ["Alice", "Bob"]
+        .into_iter()
+        .map(|name| {
+            let mut client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .say_hello(Request::new(HelloRequest { name: name.into() }))
+                    .await
+                    .map(|_| ())
+                    .map_err(|status| status.code())
+            })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for call in calls {
+        results.push(call.await.unwrap());
+    }
+    results.sort_by_key(Result::is_err);
+    results
+}
+
+#[tokio::test]
+async fn test_grpc_limits_reload() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Delayed(
+            Duration::from_millis(300),
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let dir = temp_dir("limits-reload");
+    let path = dir.join("griffin.toml");
+    write_config(&path, &backend.address, "max_in_flight = 1");
+    let config = ConfigFile::read(&path)?.parse()?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let (reload_tx, reload_rx) = tokio::sync::watch::channel(());
+    let reloader = ConfigReloader::new(
+        path.clone(),
+        Duration::from_secs(3600),
+        reload_rx,
+        move || Ok(ConfigFile::read(&path)?.parse()?),
+    );
+    tokio::spawn(start_proxy_with_reloader(
+        listener,
+        config,
+        shutdown_rx,
+        Some(reloader),
+    ));
+    let path = dir.join("griffin.toml");
+    // a single connection to the proxy, kept open
+    let metrics = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let reload = async |limits: &str, count: &str| -> Result<(), BoxError> {
+        write_config(&path, &backend.address, limits);
+        reload_tx.send(())?;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while reloads(&metrics, &proxy_address).await
+                != format!("config_reloads_total {}", count)
+            {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await?;
+        Ok(())
+    };
+
+    let client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    assert_eq!(
+        concurrent_calls(&client).await,
+        vec![Ok(()), Err(Code::Unavailable)]
+    );
+
+    // a raised limit admits both calls
+    reload("max_in_flight = 2", "1").await?;
+    assert_eq!(concurrent_calls(&client).await, vec![Ok(()), Ok(())]);
+
+    // and a lowered one sheds the second call again
+    reload("max_in_flight = 1", "2").await?;
+    assert_eq!(
+        concurrent_calls(&client).await,
+        vec![Ok(()), Err(Code::Unavailable)]
+    );
+
+    // a connection limit lowered to the connections already open, those of
+    // the gRPC and metrics clients, keeps new ones waiting
+    reload("max_connections = 2", "3").await?;
+    let call = async {
+        let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+        client
+            .say_hello(Request::new(HelloRequest {
+                name: "Carol".into(),
+            }))
+            .await?;
+        Ok::<_, BoxError>(())
+    };
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), call)
+            .await
+            .is_err()
+    );
+    assert_eq!(concurrent_calls(&client).await, vec![Ok(()), Ok(())]);
+
+    backend.stop();
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
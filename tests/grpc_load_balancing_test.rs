@@ -0,0 +1,96 @@
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request as GrpcRequest;
+
+use griffin::{
+    config::{
+        lb_policy_config::LbPolicyConfig,
+        route_config::{HeaderMatchConfig, RouteConfig},
+    },
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::{MockBackend, run_intergration_with_config},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_load_balancing() -> Result<(), BoxError> {
+    let second = MockBackend::start(None).await;
+    let second_address = second.address.clone();
+
+    run_intergration_with_config(
+        |config| {
+            // both backends, round robin for the default cluster
+            config.clusters[0].endpoints.push(second_address.clone());
+            let mut sticky = config.clusters[0].clone();
+            sticky.name = "sticky".to_string();
+            sticky.lb_policy = LbPolicyConfig::ConsistentHash {
+                header: "x-session".to_string(),
+            };
+            config.clusters.push(sticky);
+            config.routes.insert(
+                0,
+                RouteConfig {
+                    headers: vec![HeaderMatchConfig {
+                        name: "x-session".to_string(),
+                        value: None,
+                    }],
+                    ..RouteConfig::new("*", "sticky")
+                },
+            );
+        },
+        async |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            for session in [None, None, None, None, Some("a"), Some("a"), Some("a")] {
+                let mut req = GrpcRequest::new(HelloRequest {
+                    name: "Alice".into(),
+                });
+                if let Some(session) = session {
+                    req.metadata_mut()
+                        .insert("x-session", session.parse().unwrap());
+                }
+                let res = client.say_hello(req).await.unwrap();
+                assert_eq!(res.into_inner().message, "Hello Alice!");
+            }
+
+            let client = Client::builder(TokioExecutor::new()).build_http();
+            let req = Request::get(format!("http://{}/metrics", proxy_address))
+                .body(Empty::<Bytes>::new())
+                .unwrap();
+            let res = client.request(req).await.unwrap();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let metrics = String::from_utf8(body.to_vec()).unwrap();
+            let requests = |cluster: &str| -> Vec<String> {
+                metrics
+                    .lines()
+                    .filter(|line| {
+                        line.starts_with("upstream_endpoint_requests_total{")
+                            && line.contains(&format!("cluster=\"{}\"", cluster))
+                    })
+                    .map(|line| line.rsplit(' ').next().unwrap().to_string())
+                    .collect()
+            };
+
+            // round robin alternates between the two endpoints
+            assert_eq!(requests("default"), ["2", "2"], "{}", metrics);
+            // calls of a session stick to one endpoint
+            assert_eq!(requests("sticky"), ["3"], "{}", metrics);
+            assert!(
+                metrics.contains("upstream_lb_selections_total{cluster=\"sticky\""),
+                "{}",
+                metrics
+            );
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    second.stop().await;
+    Ok(())
+}
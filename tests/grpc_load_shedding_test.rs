@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request};
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_load_shedding() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Delayed(
+            Duration::from_millis(600),
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.limits.max_in_flight = Some(1);
+    config.limits.queue_timeout_ms = 400;
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = |name: &str| Request::new(HelloRequest { name: name.into() });
+    let call = |name: &'static str| {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.say_hello(hello(name)).await })
+    };
+
+    let first = call("Alice");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let metrics_text = metrics(&proxy_address).await;
+    assert!(metrics_text.contains("downstream_requests_in_flight 1"));
+    assert!(metrics_text.contains("downstream_connections 2"));
+
+    // queued for longer than the queue timeout, then shed
+    let shed = call("Bob");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains("downstream_requests_queued 1")
+    );
+    let status = shed.await?.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    // queued until the first call completes
+    let queued = call("Carol");
+    assert_eq!(first.await??.into_inner().message, "Hello Alice!");
+    assert_eq!(queued.await??.into_inner().message, "Hello Carol!");
+    assert_eq!(backend.calls().len(), 2);
+
+    let metrics_text = metrics(&proxy_address).await;
+    assert!(metrics_text.contains("downstream_requests_shed_total 1"));
+    assert!(metrics_text.contains("downstream_requests_in_flight 0"));
+    assert!(metrics_text.contains("downstream_requests_queued 0"));
+
+    backend.stop();
+    Ok(())
+}
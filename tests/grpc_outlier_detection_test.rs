@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::Request;
+
+use griffin::{
+    config::{outlier_detection_config::OutlierDetectionConfig, proxy_config::ProxyConfig},
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_outlier_detection() -> Result<(), BoxError> {
+    let good =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+    let bad = ScriptedBackend::start(|_, _, _| Reply::Status(14)).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(good.address.clone());
+    config.clusters[0].endpoints.push(bad.address.clone());
+    config.clusters[0].outlier_detection = Some(OutlierDetectionConfig {
+        consecutive_failures: 2,
+        base_ejection_ms: 500,
+        ..OutlierDetectionConfig::default()
+    });
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = || {
+        Request::new(HelloRequest {
+            name: "Alice".into(),
+        })
+    };
+    let ejected = format!(
+        "upstream_endpoint_ejected{{cluster=\"default\",endpoint=\"{}\"}}",
+        bad.address
+    );
+
+    // round robin alternates until the bad endpoint fails twice in a row
+    for _ in 0..4 {
+        let _ = client.say_hello(hello()).await;
+    }
+    assert_eq!(bad.calls().len(), 2);
+    let metrics_text = metrics(&proxy_address).await;
+    assert!(metrics_text.contains(&format!("{} 1", ejected)));
+    assert!(metrics_text.contains(&format!(
+        "upstream_outlier_ejections_total{{cluster=\"default\",endpoint=\"{}\"}} 1",
+        bad.address
+    )));
+
+    // the ejected endpoint receives no call
+    for _ in 0..4 {
+        client.say_hello(hello()).await?;
+    }
+    assert_eq!(bad.calls().len(), 2);
+
+    // then is restored once the ejection time is over
+    tokio::time::sleep(Duration::from_millis(700)).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains(&format!("{} 0", ejected))
+    );
+    for _ in 0..2 {
+        let _ = client.say_hello(hello()).await;
+    }
+    assert_eq!(bad.calls().len(), 3);
+
+    good.stop();
+    bad.stop();
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request, transport::Channel};
+
+use griffin::{
+    config::{outlier_detection_config::OutlierDetectionConfig, proxy_config::ProxyConfig},
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+/// Calls until the bad endpoint has failed twice in a row
+async fn fail_twice(client: &mut GreeterClient<Channel>) {
+    let mut failures = 0;
+    while failures < 2 {
+        let res = client
+            .say_hello(Request::new(HelloRequest {
+                name: "Alice".into(),
+            }))
+            .await;
+        match res {
+            Err(status) if status.code() == Code::Unavailable => failures += 1,
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_grpc_outlier_detection_trailers() -> Result<(), BoxError> {
+    let good =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+    // UNAVAILABLE after the response headers
+    let bad = ScriptedBackend::start(|_, _, _| Reply::Trailers(14)).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(good.address.clone());
+    config.clusters[0].endpoints.push(bad.address.clone());
+    config.clusters[0].outlier_detection = Some(OutlierDetectionConfig {
+        consecutive_failures: 2,
+        base_ejection_ms: 300,
+        ..OutlierDetectionConfig::default()
+    });
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let ejected = format!(
+        "upstream_endpoint_ejected{{cluster=\"default\",endpoint=\"{}\"}}",
+        bad.address
+    );
+
+    // the statuses in the trailers eject the endpoint
+    fail_twice(&mut client).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains(&format!("{} 1", ejected))
+    );
+    tokio::time::sleep(Duration::from_millis(450)).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains(&format!("{} 0", ejected))
+    );
+
+    // after two base ejection times in rotation, the first ejection is
+    // forgotten and the next one lasts the base ejection time again
+    tokio::time::sleep(Duration::from_millis(700)).await;
+    fail_twice(&mut client).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains(&format!("{} 1", ejected))
+    );
+    tokio::time::sleep(Duration::from_millis(450)).await;
+    assert!(
+        metrics(&proxy_address)
+            .await
+            .contains(&format!("{} 0", ejected))
+    );
+
+    good.stop();
+    bad.stop();
+    Ok(())
+}
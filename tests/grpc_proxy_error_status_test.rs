@@ -0,0 +1,67 @@
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request as GrpcRequest};
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+    preparation::run_intergration_with_config,
+    utils::message_to_frame,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_proxy_error_status() -> Result<(), BoxError> {
+    // a port nothing listens on
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let closed_address = closed.local_addr()?.to_string();
+    drop(closed);
+
+    run_intergration_with_config(
+        |config| config.clusters[0].endpoints = vec![closed_address],
+        async move |proxy_address| {
+            // gRPC clients get UNAVAILABLE in the HTTP/2 trailers
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+            let status = client
+                .say_hello(GrpcRequest::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unavailable);
+
+            // grpc-web clients get UNAVAILABLE in a trailers frame
+            let url = format!("http://{}/helloworld.Greeter/SayHello", proxy_address);
+            let req_msg = HelloRequest {
+                name: "Alice".to_string(),
+            };
+            let req = Request::post(&url)
+                .header("content-type", "application/grpc-web+proto")
+                .body(Full::<Bytes>::from(message_to_frame(&req_msg).freeze()))
+                .unwrap();
+            let http_client = Client::builder(TokioExecutor::new()).build_http();
+            let res = http_client.request(req).await.unwrap();
+            assert_eq!(res.status(), 200);
+            assert_eq!(res.headers()["content-type"], "application/grpc-web+proto");
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body[0], 0x80);
+            let trailers = String::from_utf8(body[5..].to_vec()).unwrap();
+            assert!(trailers.contains("grpc-status:14"), "{}", trailers);
+
+            // unknown content types get UNIMPLEMENTED in the headers
+            let req = Request::post(&url)
+                .header("content-type", "text/plain")
+                .body(Full::<Bytes>::from("hello"))
+                .unwrap();
+            let res = http_client.request(req).await.unwrap();
+            assert_eq!(res.status(), 200);
+            assert_eq!(res.headers()["grpc-status"], "12");
+
+            Ok(())
+        },
+    )
+    .await
+}
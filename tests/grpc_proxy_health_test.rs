@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, transport::Channel};
+use tonic_health::pb::{
+    HealthCheckRequest, health_check_response::ServingStatus, health_client::HealthClient,
+};
+
+use griffin::{
+    config::health_check_config::HealthCheckConfig,
+    test_support::preparation::{MockBackend, run_intergration_with_config},
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_proxy_health() -> Result<(), BoxError> {
+    // a backend whose health the test controls
+    let backend = MockBackend::start(None).await;
+    let backend_address = backend.address.clone();
+    let backend_health = backend.health.clone();
+
+    run_intergration_with_config(
+        |config| {
+            config.clusters[0].endpoints = vec![backend_address];
+            config.clusters[0].health_check = Some(HealthCheckConfig {
+                interval_ms: 20,
+                service: "helloworld.Greeter".to_string(),
+                healthy_threshold: 1,
+                unhealthy_threshold: 1,
+                ..HealthCheckConfig::default()
+            })
+        },
+        async |proxy_address| {
+            let channel = Channel::from_shared(format!("http://{}", proxy_address))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            let mut client = HealthClient::new(channel);
+            let check = |service: &str| HealthCheckRequest {
+                service: service.to_string(),
+            };
+
+            // the proxy, then a cluster, then an unknown service
+            let res = client.check(check("")).await.unwrap();
+            assert_eq!(res.into_inner().status(), ServingStatus::Serving);
+            let res = client.check(check("default")).await.unwrap();
+            assert_eq!(res.into_inner().status(), ServingStatus::Serving);
+            let status = client.check(check("unknown")).await.unwrap_err();
+            assert_eq!(status.code(), Code::NotFound);
+
+            // grpc-web clients get the same answer
+            let http_client = Client::builder(TokioExecutor::new()).build_http();
+            let req = Request::post(format!(
+                "http://{}/grpc.health.v1.Health/Check",
+                proxy_address
+            ))
+            .header("content-type", "application/grpc-web+proto")
+            .body(Full::<Bytes>::from(vec![0, 0, 0, 0, 0]))
+            .unwrap();
+            let res = http_client.request(req).await.unwrap();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&body[..7], &[0, 0, 0, 0, 2, 0x08, 1]);
+            assert_eq!(body[7], 0x80);
+
+            let probe = async |path: &str| {
+                let client = Client::builder(TokioExecutor::new()).build_http();
+                let req = Request::get(format!("http://{}{}", proxy_address, path))
+                    .body(Empty::<Bytes>::new())
+                    .unwrap();
+                let res = client.request(req).await.unwrap();
+                let status = res.status().as_u16();
+                let body = res.into_body().collect().await.unwrap().to_bytes();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            };
+            assert_eq!(probe("/readyz").await, (200, "SERVING".to_string()));
+
+            // watchers are told when the only backend fails its checks
+            let mut watch = client.watch(check("")).await.unwrap().into_inner();
+            let first = watch.message().await.unwrap().unwrap();
+            assert_eq!(first.status(), ServingStatus::Serving);
+            backend_health
+                .set_service_status(
+                    "helloworld.Greeter",
+                    tonic_health::ServingStatus::NotServing,
+                )
+                .await;
+            let next = tokio::time::timeout(Duration::from_secs(5), watch.message())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(next.status(), ServingStatus::NotServing);
+
+            // not ready, but still alive
+            assert_eq!(probe("/readyz").await, (503, "NOT_SERVING".to_string()));
+            assert_eq!(probe("/healthz").await, (200, "NOT_SERVING".to_string()));
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    backend.stop().await;
+    Ok(())
+}
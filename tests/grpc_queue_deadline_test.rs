@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tokio::time::Instant;
+use tonic::Request;
+
+use griffin::{
+    config::proxy_config::ProxyConfig,
+    core::grpc_timeout::parse_grpc_timeout,
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+        utils::message_to_frame,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_queue_deadline() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Delayed(
+            Duration::from_millis(500),
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.limits.max_in_flight = Some(1);
+    config.limits.queue_timeout_ms = 5000;
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let call = |name: &'static str, timeout: Duration| {
+        let mut client = client.clone();
+        let mut req = Request::new(HelloRequest { name: name.into() });
+        req.set_timeout(timeout);
+        tokio::spawn(async move { client.say_hello(req).await })
+    };
+
+    let first = call("Alice", Duration::from_secs(5));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // the deadline passes long before the queue timeout
+    // a grpc-web client, which does not enforce the timeout it sends itself
+    let req = http::Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc-web+proto")
+    .header("x-grpc-web", "1")
+    .header("grpc-timeout", "200m")
+    .body(Full::new(
+        message_to_frame(&HelloRequest { name: "Bob".into() }).freeze(),
+    ))?;
+    let start = Instant::now();
+    let res = Client::builder(TokioExecutor::new())
+        .build_http()
+        .request(req)
+        .await?;
+    let body = res.into_body().collect().await?.to_bytes();
+    assert!(String::from_utf8_lossy(&body).contains("grpc-status:4"));
+    assert!(start.elapsed() < Duration::from_millis(400));
+
+    // a call admitted after waiting is sent with the time left
+    let queued = call("Carol", Duration::from_secs(2));
+    assert_eq!(first.await??.into_inner().message, "Hello Alice!");
+    assert_eq!(queued.await??.into_inner().message, "Hello Carol!");
+    let calls = backend.calls();
+    assert_eq!(calls.len(), 2);
+    let timeout = parse_grpc_timeout(&calls[1]["grpc-timeout"]).unwrap();
+    assert!(timeout < Duration::from_millis(1900), "{:?}", timeout);
+
+    backend.stop();
+    Ok(())
+}
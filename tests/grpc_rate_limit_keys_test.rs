@@ -0,0 +1,79 @@
+use tonic::{Code, Request, transport::Channel};
+
+use griffin::{
+    config::{
+        proxy_config::ProxyConfig,
+        rate_limit_config::{RateLimitConfig, RateLimitKeyConfig},
+    },
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+/// The keys a limit tracks at most
+const MAX_KEYS: usize = 10_000;
+
+async fn say_hello(mut client: GreeterClient<Channel>, api_key: String) -> Result<(), Code> {
+    let mut request = Request::new(HelloRequest {
+        name: "Alice".into(),
+    });
+    request
+        .metadata_mut()
+        .insert("x-api-key", api_key.parse().unwrap());
+    client
+        .say_hello(request)
+        .await
+        .map(|_| ())
+        .map_err(|status| status.code())
+}
+
+#[tokio::test]
+async fn test_grpc_rate_limit_keys() -> Result<(), BoxError> {
+    let backend =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    // buckets never refill during the test
+    config.routes[0].rate_limits = vec![RateLimitConfig {
+        requests_per_second: 0.0001,
+        burst: 1,
+        key: RateLimitKeyConfig::Header("x-api-key".to_string()),
+    }];
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+
+    // every tracked key uses its bucket
+    for batch in 0..MAX_KEYS / 100 {
+        let mut calls = tokio::task::JoinSet::new();
+        for i in 0..100 {
+            let api_key = format!("key-{}", batch * 100 + i);
+            calls.spawn(say_hello(client.clone(), api_key));
+        }
+        while let Some(result) = calls.join_next().await {
+            assert_eq!(result?, Ok(()));
+        }
+    }
+
+    // the keys beyond share a single bucket
+    assert_eq!(say_hello(client.clone(), "extra-1".into()).await, Ok(()));
+    assert_eq!(
+        say_hello(client.clone(), "extra-2".into()).await,
+        Err(Code::ResourceExhausted)
+    );
+    // while the tracked keys keep their own
+    assert_eq!(
+        say_hello(client.clone(), "key-0".into()).await,
+        Err(Code::ResourceExhausted)
+    );
+
+    backend.stop();
+    Ok(())
+}
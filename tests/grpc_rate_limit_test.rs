@@ -0,0 +1,118 @@
+use bytes::{Buf, Bytes};
+use http_body_util::{BodyExt, Empty, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{
+        proxy_config::ProxyConfig,
+        rate_limit_config::{RateLimitConfig, RateLimitKeyConfig},
+    },
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+        utils::message_to_frame,
+    },
+};
+use tower::BoxError;
+
+async fn metrics(proxy_address: &str) -> String {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let res = client
+        .get(format!("http://{}/metrics", proxy_address).parse().unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_rate_limit() -> Result<(), BoxError> {
+    let backend =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.routes[0].rate_limits = vec![
+        RateLimitConfig {
+            requests_per_second: 0.1,
+            burst: 2,
+            key: RateLimitKeyConfig::Header("x-api-key".to_string()),
+        },
+        RateLimitConfig {
+            requests_per_second: 0.1,
+            burst: 4,
+            key: RateLimitKeyConfig::PeerIp,
+        },
+    ];
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = |api_key: &'static str| {
+        let mut request = Request::new(HelloRequest {
+            name: "Alice".into(),
+        });
+        request
+            .metadata_mut()
+            .insert("x-api-key", api_key.parse().unwrap());
+        request
+    };
+
+    // each api key has its own bucket
+    client.say_hello(hello("a")).await?;
+    client.say_hello(hello("a")).await?;
+    let status = client.say_hello(hello("a")).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    client.say_hello(hello("b")).await?;
+    client.say_hello(hello("b")).await?;
+
+    // every call comes from the same address
+    let status = client.say_hello(hello("c")).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(backend.calls().len(), 4);
+
+    // grpc-web clients get the status in a trailer frame
+    let req = http::Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc-web+proto")
+    .header("x-grpc-web", "1")
+    .header("x-api-key", "a")
+    .body(Full::new(
+        message_to_frame(&HelloRequest {
+            name: "Alice".into(),
+        })
+        .freeze(),
+    ))
+    .unwrap();
+    let res = Client::builder(TokioExecutor::new())
+        .build_http()
+        .request(req)
+        .await?;
+    assert_eq!(res.status(), 200);
+    let mut body = res.into_body().collect().await?.to_bytes();
+    assert_eq!(body.get_u8(), 0x80);
+    let len = body.get_u32() as usize;
+    let trailers = String::from_utf8(body[..len].to_vec())?;
+    assert!(trailers.contains("grpc-status:8"));
+
+    let metrics = metrics(&proxy_address).await;
+    let decisions = |limiter: &str, decision: &str, count: u32| {
+        format!(
+            "rate_limit_decisions_total{{decision=\"{}\",limiter=\"{}\",route=\"*\"}} {}",
+            decision, limiter, count
+        )
+    };
+    assert!(metrics.contains(&decisions("header:x-api-key", "allowed", 5)));
+    assert!(metrics.contains(&decisions("header:x-api-key", "limited", 2)));
+    assert!(metrics.contains(&decisions("peer_ip", "allowed", 4)));
+    assert!(metrics.contains(&decisions("peer_ip", "limited", 1)));
+
+    backend.stop();
+    Ok(())
+}
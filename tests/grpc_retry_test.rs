@@ -0,0 +1,65 @@
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, retry_policy_config::RetryPolicyConfig},
+    start_proxy,
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_retry() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, headers, request| {
+        let previous_attempts: u32 = headers
+            .get("grpc-previous-rpc-attempts")
+            .map_or(0, |value| value.to_str().unwrap().parse().unwrap());
+        match request.name.as_str() {
+            // fails twice, as during a rolling deploy
+            "flaky" if previous_attempts < 2 => Reply::Status(14),
+            "broken" => Reply::Status(14),
+            "invalid" => Reply::Status(3),
+            name => Reply::Message(format!("Hello {}!", name)),
+        }
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.routes[0].retry_policy = Some(RetryPolicyConfig {
+        initial_backoff_ms: 10,
+        max_backoff_ms: 50,
+        ..RetryPolicyConfig::default()
+    });
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let mut client = GreeterClient::connect(format!("http://{}", proxy_address)).await?;
+    let hello = |name: &str| Request::new(HelloRequest { name: name.into() });
+
+    // the request body is replayed on each retry
+    let res = client.say_hello(hello("flaky")).await?;
+    assert_eq!(res.into_inner().message, "Hello flaky!");
+    let attempts: Vec<_> = backend
+        .calls()
+        .iter()
+        .map(|headers| headers.get("grpc-previous-rpc-attempts").cloned())
+        .collect();
+    assert_eq!(attempts, [None, Some(1.into()), Some(2.into())]);
+
+    // the status of the last attempt once max_attempts is reached
+    let status = client.say_hello(hello("broken")).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(backend.calls().len(), 6);
+
+    // other statuses are not retried
+    let status = client.say_hello(hello("invalid")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(backend.calls().len(), 7);
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use futures_util::StreamExt;
+use tonic::{Code, Request};
+
+use griffin::{
+    config::{
+        cluster_config::ClusterConfig,
+        proxy_config::DEFAULT_CLUSTER,
+        route_config::{HeaderMatchConfig, RouteConfig},
+    },
+    test_support::{
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::run_intergration_with_config,
+    },
+};
+use tower::BoxError;
+
+fn hello(name: &str, tenant: Option<&str>) -> Request<HelloRequest> {
+    let mut req = Request::new(HelloRequest { name: name.into() });
+    if let Some(tenant) = tenant {
+        req.metadata_mut()
+            .insert("x-tenant", tenant.parse().unwrap());
+    }
+    req
+}
+
+#[tokio::test]
+async fn test_grpc_routing() -> Result<(), BoxError> {
+    // a port nothing listens on
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let closed_address = closed.local_addr()?.to_string();
+    drop(closed);
+
+    run_intergration_with_config(
+        |config| {
+            config
+                .clusters
+                .push(ClusterConfig::new("unreachable", closed_address));
+            let tenant = |value: &str| HeaderMatchConfig {
+                name: "x-tenant".to_string(),
+                value: Some(value.to_string()),
+            };
+            config.routes = vec![
+                RouteConfig {
+                    headers: vec![tenant("beta")],
+                    ..RouteConfig::new("/helloworld.Greeter/SayHello", "unreachable")
+                },
+                RouteConfig::new("/helloworld.Greeter/SayHello", DEFAULT_CLUSTER),
+                RouteConfig {
+                    headers: vec![tenant("regex:gamma|delta")],
+                    ..RouteConfig::new("/helloworld.Greeter/*", DEFAULT_CLUSTER)
+                },
+            ];
+        },
+        async move |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+
+            // exact method
+            let res = client.say_hello(hello("Alice", None)).await.unwrap();
+            assert_eq!(res.into_inner().message, "Hello Alice!");
+
+            // exact method and header, sent to the unreachable cluster
+            let status = client
+                .say_hello(hello("Alice", Some("beta")))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unavailable);
+
+            // service prefix and header regex
+            let res = client
+                .say_hello_stream(hello("Alice", Some("delta")))
+                .await
+                .unwrap();
+            let replies: Vec<_> = res.into_inner().collect().await;
+            assert_eq!(replies[0].as_ref().unwrap().message, "first ok");
+
+            // no route, the status arrives in the trailers
+            let res = client
+                .say_hello_stream(hello("Alice", Some("beta")))
+                .await
+                .unwrap();
+            let replies: Vec<_> = res.into_inner().collect().await;
+            let status = replies[0].as_ref().unwrap_err();
+            assert_eq!(status.code(), Code::Unimplemented);
+
+            Ok(())
+        },
+    )
+    .await
+}
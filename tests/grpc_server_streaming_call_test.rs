@@ -28,7 +28,7 @@ async fn test_grpc_server_streaming_call() -> Result<(), BoxError> {
                 Ok(msg) => {
                     replies.push(msg.message.clone());
                 }
-                Err(e) => {
+                Err(_) => {
                     break;
                 }
             }
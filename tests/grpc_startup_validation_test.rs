@@ -0,0 +1,35 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_startup_validation() -> Result<(), BoxError> {
+    // the proxy refuses to start instead of failing later in a background task
+    let mut griffin = Command::new(env!("CARGO_BIN_EXE_griffin"))
+        .args(["--proxy-port", "0", "--health-check-interval-ms", "0"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = griffin.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > Duration::from_secs(5) {
+            griffin.kill()?;
+            panic!("griffin started with an invalid configuration");
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    assert!(!status.success());
+    let output = griffin.wait_with_output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("clusters[0].health_check.interval_ms: Must be greater than 0"),
+        "{}",
+        stderr
+    );
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper::client::conn::http2;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use prost::Message;
+use rustls::{ClientConfig, RootCertStore};
+use rustls_pki_types::{CertificateDer, ServerName};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use griffin::{
+    config::tls_config::TlsConfig,
+    test_support::{
+        certificates::{self_signed, temp_dir, write_pem},
+        greeter::hello_world::{HelloReply, HelloRequest},
+        preparation::run_intergration_with_config,
+        utils::message_to_frame,
+    },
+};
+use tower::BoxError;
+
+// open a TLS connection trusting only `root`,
+// returning the HTTP/2 sender and the certificate served by the proxy
+async fn connect(
+    proxy_address: &str,
+    root: &CertificateDer<'static>,
+) -> (http2::SendRequest<Full<Bytes>>, CertificateDer<'static>) {
+    let mut roots = RootCertStore::empty();
+    roots.add(root.clone()).unwrap();
+    let mut config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    config.alpn_protocols = vec![b"h2".to_vec()];
+
+    let stream = TcpStream::connect(proxy_address).await.unwrap();
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    let (_, session) = stream.get_ref();
+    assert_eq!(session.alpn_protocol(), Some(&b"h2"[..]));
+    let served = session.peer_certificates().unwrap()[0].clone();
+
+    let (sender, conn) = http2::Builder::new(TokioExecutor::new())
+        .handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(conn);
+    (sender, served)
+}
+
+async fn say_hello(sender: &mut http2::SendRequest<Full<Bytes>>, name: &str) -> String {
+    let req_msg = HelloRequest {
+        name: name.to_string(),
+    };
+    let req = Request::post("https://localhost/helloworld.Greeter/SayHello")
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(Full::<Bytes>::from(message_to_frame(&req_msg).freeze()))
+        .unwrap();
+    let res = sender.send_request(req).await.unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    HelloReply::decode(&body[5..]).unwrap().message
+}
+
+#[tokio::test]
+async fn test_grpc_tls_listener() -> Result<(), BoxError> {
+    let dir = temp_dir("tls-listener");
+    let first = self_signed(&["localhost"]);
+    let (cert_path, key_path) = write_pem(&dir, "proxy", &first);
+
+    run_intergration_with_config(
+        |config| {
+            config.tls = Some(TlsConfig {
+                reload_interval_ms: 50,
+                ..TlsConfig::new(cert_path, key_path)
+            })
+        },
+        async |proxy_address| {
+            let (mut first_sender, served) = connect(&proxy_address, &first.cert_der).await;
+            assert_eq!(served, first.cert_der);
+            assert_eq!(say_hello(&mut first_sender, "Alice").await, "Hello Alice!");
+
+            // replace the certificate on disk
+            let second = self_signed(&["localhost"]);
+            write_pem(&dir, "proxy", &second);
+            tokio::time::sleep(Duration::from_millis(500)).await;
+
+            let (mut second_sender, served) = connect(&proxy_address, &second.cert_der).await;
+            assert_eq!(served, second.cert_der);
+            assert_eq!(say_hello(&mut second_sender, "Bob").await, "Hello Bob!");
+
+            // the connection opened before the reload is still served
+            assert_eq!(say_hello(&mut first_sender, "Carol").await, "Hello Carol!");
+            Ok(())
+        },
+    )
+    .await?;
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use tonic::{
+    Request,
+    transport::{Certificate, Identity, ServerTlsConfig},
+};
+
+use griffin::{
+    config::upstream_tls_config::UpstreamTlsConfig,
+    test_support::{
+        certificates::{self_signed, temp_dir, write_pem},
+        greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+        preparation::run_intergration_with_backend_tls,
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_upstream_mtls() -> Result<(), BoxError> {
+    let dir = temp_dir("upstream-mtls");
+    // the backend is reached by IP, its certificate only names backend.internal
+    let backend = self_signed(&["backend.internal"]);
+    let client = self_signed(&["griffin"]);
+    let (backend_cert_path, _) = write_pem(&dir, "backend", &backend);
+    let (client_cert_path, client_key_path) = write_pem(&dir, "client", &client);
+
+    // the backend requires a client certificate
+    let backend_tls = ServerTlsConfig::new()
+        .identity(Identity::from_pem(&backend.cert_pem, &backend.key_pem))
+        .client_ca_root(Certificate::from_pem(&client.cert_pem));
+
+    run_intergration_with_backend_tls(
+        backend_tls,
+        |config| {
+            config.clusters[0].tls = Some(UpstreamTlsConfig {
+                ca_path: Some(backend_cert_path),
+                client_cert_path: Some(client_cert_path),
+                client_key_path: Some(client_key_path),
+                server_name: Some("backend.internal".to_string()),
+                insecure_skip_verify: false,
+            })
+        },
+        async |proxy_address| {
+            let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                .await
+                .unwrap();
+
+            let res = client
+                .say_hello(Request::new(HelloRequest {
+                    name: "Alice".into(),
+                }))
+                .await
+                .unwrap();
+
+            assert_eq!(res.into_inner().message, "Hello Alice!");
+            Ok(())
+        },
+    )
+    .await?;
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use bytes::Bytes;
+use http::{Method, Request};
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+
+use griffin::test_support::{
+    greeter::hello_world::HelloRequest, preparation::run_intergration_with_config,
+    utils::message_to_frame,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_web_cors() -> Result<(), BoxError> {
+    run_intergration_with_config(
+        |config| {
+            config.cors.allowed_origins = vec!["https://*.example.com".to_string()];
+            config.cors.allowed_headers = vec!["x-api-key".to_string()];
+            config.cors.exposed_headers = vec!["custom-header".to_string()];
+        },
+        async move |proxy_address| {
+            let url = format!("http://{}/helloworld.Greeter/SayHello", proxy_address);
+            let client = Client::builder(TokioExecutor::new()).build_http();
+
+            // preflight from an allowed origin is answered by the proxy
+            let preflight = Request::builder()
+                .method(Method::OPTIONS)
+                .uri(&url)
+                .header("origin", "https://app.example.com")
+                .header("access-control-request-method", "POST")
+                .header("access-control-request-headers", "content-type,x-grpc-web")
+                .body(Full::<Bytes>::default())
+                .unwrap();
+            let res = client.request(preflight).await.unwrap();
+            assert_eq!(res.status(), 204);
+            let headers = res.headers();
+            assert_eq!(
+                headers["access-control-allow-origin"],
+                "https://app.example.com"
+            );
+            let allowed_headers = headers["access-control-allow-headers"].to_str().unwrap();
+            for name in ["x-grpc-web", "x-user-agent", "grpc-timeout", "x-api-key"] {
+                assert!(allowed_headers.contains(name), "missing {}", name);
+            }
+
+            // preflight from an unknown origin is rejected
+            let preflight = Request::builder()
+                .method(Method::OPTIONS)
+                .uri(&url)
+                .header("origin", "https://example.org")
+                .header("access-control-request-method", "POST")
+                .body(Full::<Bytes>::default())
+                .unwrap();
+            let res = client.request(preflight).await.unwrap();
+            assert_eq!(res.status(), 403);
+
+            // the actual call exposes the gRPC status headers
+            let req_msg = HelloRequest {
+                name: "Alice".to_string(),
+            };
+            let req = Request::post(&url)
+                .header("origin", "https://app.example.com")
+                .header("content-type", "application/grpc-web+proto")
+                .header("x-grpc-web", "1")
+                .body(Full::<Bytes>::from(message_to_frame(&req_msg).freeze()))
+                .unwrap();
+            let res = client.request(req).await.unwrap();
+            assert_eq!(res.status(), 200);
+            let headers = res.headers();
+            assert_eq!(
+                headers["access-control-allow-origin"],
+                "https://app.example.com"
+            );
+            let exposed_headers = headers["access-control-expose-headers"].to_str().unwrap();
+            for name in ["grpc-status", "grpc-message", "custom-header"] {
+                assert!(exposed_headers.contains(name), "missing {}", name);
+            }
+            res.into_body().collect().await.unwrap();
+
+            Ok(())
+        },
+    )
+    .await
+}
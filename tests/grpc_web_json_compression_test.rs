@@ -0,0 +1,68 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::{Value, json};
+
+use griffin::{
+    compression::encoding::Encoding,
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::DESCRIPTOR_SET_PATH,
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_web_json_compression() -> Result<(), BoxError> {
+    let backend = ScriptedBackend::start(|_, _, request| {
+        Reply::Compressed(
+            Encoding::Gzip,
+            Box::new(Reply::Message(format!("Hello {}!", request.name))),
+        )
+    })
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.clusters[0].compression.accept_encodings = vec!["gzip".to_string()];
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let message = r#"{"name":"Alice"}"#;
+    let mut framed = BytesMut::new();
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.put_slice(message.as_bytes());
+    // a client claiming to read gzip messages still gets JSON
+    let req = Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc-web+json")
+    .header("grpc-accept-encoding", "gzip")
+    .body(Full::new(framed.freeze()))?;
+    let res = Client::builder(TokioExecutor::new())
+        .build_http::<Full<Bytes>>()
+        .request(req)
+        .await?;
+    assert!(res.headers().get("grpc-encoding").is_none());
+    let body = res.into_body().collect().await?.to_bytes();
+
+    assert_eq!(body[0], 0);
+    let len = u32::from_be_bytes(body[1..5].try_into()?) as usize;
+    let reply: Value = serde_json::from_slice(&body[5..5 + len])?;
+    assert_eq!(reply, json!({ "message": "Hello Alice!" }));
+    assert!(String::from_utf8_lossy(&body[5 + len..]).contains("grpc-status:0"));
+    assert_eq!(
+        backend.calls()[0]["grpc-accept-encoding"],
+        "identity,gzip,deflate,zstd"
+    );
+
+    backend.stop();
+    Ok(())
+}
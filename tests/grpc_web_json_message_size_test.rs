@@ -0,0 +1,58 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::Value;
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::DESCRIPTOR_SET_PATH,
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_web_json_message_size() -> Result<(), BoxError> {
+    let backend =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.clusters[0].compression.max_message_bytes = 8 << 20;
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    // messages over the default 4 MiB, under the limit of the cluster
+    let name = "a".repeat(5 << 20);
+    let message = format!(r#"{{"name":"{}"}}"#, name);
+    let mut framed = BytesMut::new();
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.put_slice(message.as_bytes());
+    let req = Request::post(format!(
+        "http://{}/helloworld.Greeter/SayHello",
+        proxy_address
+    ))
+    .header("content-type", "application/grpc-web+json")
+    .body(Full::new(framed.freeze()))?;
+    let res = Client::builder(TokioExecutor::new())
+        .build_http::<Full<Bytes>>()
+        .request(req)
+        .await?;
+    let body = res.into_body().collect().await?.to_bytes();
+
+    assert_eq!(body[0], 0);
+    let len = u32::from_be_bytes(body[1..5].try_into()?) as usize;
+    let reply: Value = serde_json::from_slice(&body[5..5 + len])?;
+    assert_eq!(reply["message"], format!("Hello {}!", name));
+    assert!(String::from_utf8_lossy(&body[5 + len..]).contains("grpc-status:0"));
+
+    backend.stop();
+    Ok(())
+}
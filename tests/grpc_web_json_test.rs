@@ -0,0 +1,81 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::{Value, json};
+
+use griffin::{
+    config::{proxy_config::ProxyConfig, transcoding_config::TranscodingConfig},
+    start_proxy,
+    test_support::{
+        greeter::DESCRIPTOR_SET_PATH,
+        scripted_backend::{Reply, ScriptedBackend},
+    },
+};
+use tower::BoxError;
+
+/// The flags and payload of every frame of a grpc-web body
+fn frames(mut body: &[u8]) -> Vec<(u8, Bytes)> {
+    let mut frames = Vec::new();
+    while body.len() >= 5 {
+        let len = u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+        frames.push((body[0], Bytes::copy_from_slice(&body[5..5 + len])));
+        body = &body[5 + len..];
+    }
+    frames
+}
+
+#[tokio::test]
+async fn test_grpc_web_json() -> Result<(), BoxError> {
+    let backend =
+        ScriptedBackend::start(|_, _, request| Reply::Message(format!("Hello {}!", request.name)))
+            .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_address = listener.local_addr()?.to_string();
+    let mut config = ProxyConfig::new(backend.address.clone());
+    config.transcoding = Some(TranscodingConfig::new(DESCRIPTOR_SET_PATH));
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(start_proxy(listener, config, shutdown_rx));
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    let call = |method: &str, message: &str| {
+        let mut framed = BytesMut::new();
+        framed.put_u8(0);
+        framed.put_u32(message.len() as u32);
+        framed.put_slice(message.as_bytes());
+        let req = Request::post(format!(
+            "http://{}/helloworld.Greeter/{}",
+            proxy_address, method
+        ))
+        .header("content-type", "application/grpc-web+json")
+        .body(Full::new(framed.freeze()))
+        .unwrap();
+        let client = client.clone();
+        async move {
+            let res = client.request(req).await?;
+            assert_eq!(res.headers()["content-type"], "application/grpc-web+json");
+            let body = res.into_body().collect().await?.to_bytes();
+            Ok::<_, BoxError>(frames(&body))
+        }
+    };
+
+    // the JSON message reaches the server as protobuf, and back
+    let frames = call("SayHello", r#"{"name":"Alice"}"#).await?;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].0, 0);
+    let reply: Value = serde_json::from_slice(&frames[0].1)?;
+    assert_eq!(reply, json!({ "message": "Hello Alice!" }));
+    assert_eq!(frames[1].0, 0x80);
+    assert!(String::from_utf8_lossy(&frames[1].1).contains("grpc-status:0"));
+    assert_eq!(backend.calls()[0]["content-type"], "application/grpc");
+
+    // methods missing from the descriptor set cannot be converted
+    let frames = call("SayGoodbye", r#"{"name":"Bob"}"#).await?;
+    assert_eq!(frames.len(), 1);
+    assert!(String::from_utf8_lossy(&frames[0].1).contains("grpc-status:12"));
+    assert_eq!(backend.calls().len(), 1);
+
+    backend.stop();
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use bytes::Bytes;
+use http::Request;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+
+use griffin::test_support::{
+    greeter::hello_world::HelloRequest, preparation::run_intergration, utils::message_to_frame,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_web_same_origin() -> Result<(), BoxError> {
+    // no origin is allowed by the default configuration
+    run_intergration(async move |proxy_address| {
+        let url = format!("http://{}/helloworld.Greeter/SayHello", proxy_address);
+        let client = Client::builder(TokioExecutor::new()).build_http();
+        let call = |origin: String| {
+            let req_msg = HelloRequest {
+                name: "Alice".to_string(),
+            };
+            Request::post(&url)
+                .header("origin", origin)
+                .header("content-type", "application/grpc-web+proto")
+                .header("x-grpc-web", "1")
+                .body(Full::<Bytes>::from(message_to_frame(&req_msg).freeze()))
+                .unwrap()
+        };
+
+        // browsers send Origin on same-origin POST requests as well
+        let res = client
+            .request(call(format!("http://{}", proxy_address)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert!(!res.headers().contains_key("access-control-allow-origin"));
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("grpc-status:0"));
+
+        // while other origins are still rejected, including another scheme
+        for origin in [
+            "http://app.example.com".to_string(),
+            format!("https://{}", proxy_address),
+        ] {
+            let res = client.request(call(origin)).await.unwrap();
+            assert_eq!(res.status(), 403);
+        }
+
+        Ok(())
+    })
+    .await
+}
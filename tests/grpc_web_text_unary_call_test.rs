@@ -0,0 +1,82 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::{Buf, Bytes};
+use futures_util::stream;
+use http::Request;
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use prost::Message;
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloReply, HelloRequest},
+    preparation::run_intergration,
+    utils::message_to_frame,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_web_text_unary_call() -> Result<(), BoxError> {
+    run_intergration(async move |proxy_address| {
+        let url = format!("http://{}/helloworld.Greeter/SayHello", proxy_address);
+        let req_msg = HelloRequest {
+            name: "Alice".to_string(),
+        };
+        let encoded = STANDARD.encode(message_to_frame(&req_msg));
+
+        // split the base64 body in the middle of 4-byte groups
+        let chunks: Vec<Result<Frame<Bytes>, BoxError>> = encoded
+            .as_bytes()
+            .chunks(3)
+            .map(|chunk| Ok(Frame::data(Bytes::copy_from_slice(chunk))))
+            .collect();
+
+        let req = Request::post(url)
+            .header("content-type", "application/grpc-web-text")
+            .header("x-grpc-web", "1")
+            .body(StreamBody::new(stream::iter(chunks)))
+            .unwrap();
+
+        let client = Client::builder(TokioExecutor::new()).build_http();
+        let res = client.request(req).await.unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers()["content-type"],
+            "application/grpc-web-text+proto"
+        );
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        // every frame is encoded with its own padding
+        let mut decoded = Vec::new();
+        let mut start = 0;
+        for end in (4..=body.len()).step_by(4) {
+            if body[end - 1] == b'=' || end == body.len() {
+                STANDARD
+                    .decode_vec(&body[start..end], &mut decoded)
+                    .unwrap();
+                start = end;
+            }
+        }
+
+        let mut buf = Bytes::from(decoded);
+        let mut messages = Vec::new();
+        let mut trailers = String::new();
+        while buf.has_remaining() {
+            let flag = buf.get_u8();
+            let len = buf.get_u32() as usize;
+            let payload = buf.split_to(len);
+            if flag & 0x80 == 0 {
+                messages.push(HelloReply::decode(payload).unwrap());
+            } else {
+                trailers = String::from_utf8(payload.to_vec()).unwrap();
+            }
+        }
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message, "Hello Alice!");
+        assert!(trailers.contains("grpc-status:0"));
+
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use http::HeaderValue;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    client_async,
+    tungstenite::{Message, client::IntoClientRequest},
+};
+use tonic::Request;
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloRequest, greeter_client::GreeterClient},
+    preparation::run_intergration_with_config,
+    utils::message_to_frame,
+};
+use tower::BoxError;
+
+#[tokio::test]
+async fn test_grpc_websocket_connection_limit() -> Result<(), BoxError> {
+    run_intergration_with_config(
+        |config| config.limits.max_connections = Some(1),
+        async |proxy_address| {
+            let url = format!("ws://{}/helloworld.Greeter/SayHelloBiStream", proxy_address);
+            let mut req = url.into_client_request()?;
+            req.headers_mut().insert(
+                "sec-websocket-protocol",
+                HeaderValue::from_static("grpc-websockets"),
+            );
+            let stream = TcpStream::connect(&proxy_address).await?;
+            let (mut ws, _) = client_async(req, stream).await?;
+            let headers = "content-type: application/grpc-web+proto\r\nx-grpc-web: 1\r\n";
+            ws.send(Message::Binary(Bytes::from(headers))).await?;
+            let mut message = BytesMut::new();
+            message.put_u8(0);
+            message.put_slice(&message_to_frame(&HelloRequest {
+                name: "client request 1".to_string(),
+            }));
+            ws.send(Message::Binary(message.freeze())).await?;
+            ws.next().await.ok_or("WebSocket closed")??;
+
+            // the upgraded connection keeps the only connection slot
+            let call = async {
+                let mut client = GreeterClient::connect(format!("http://{}", proxy_address))
+                    .await
+                    .unwrap();
+                client
+                    .say_hello(Request::new(HelloRequest {
+                        name: "Alice".into(),
+                    }))
+                    .await
+                    .unwrap()
+                    .into_inner()
+                    .message
+            };
+            let mut call = std::pin::pin!(call);
+            assert!(
+                tokio::time::timeout(Duration::from_millis(300), call.as_mut())
+                    .await
+                    .is_err()
+            );
+
+            // which is released once the WebSocket call ends
+            ws.send(Message::Binary(Bytes::from_static(&[1]))).await?;
+            while ws.next().await.is_some() {}
+            assert_eq!(
+                tokio::time::timeout(Duration::from_secs(5), call).await?,
+                "Hello Alice!"
+            );
+            Ok(())
+        },
+    )
+    .await
+}
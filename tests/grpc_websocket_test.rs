@@ -0,0 +1,89 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use http::HeaderValue;
+use prost::Message as _;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    WebSocketStream, client_async,
+    tungstenite::{Message, client::IntoClientRequest},
+};
+
+use griffin::test_support::{
+    greeter::hello_world::{HelloReply, HelloRequest},
+    preparation::run_intergration,
+    utils::message_to_frame,
+};
+use tower::BoxError;
+
+/// Reads server messages until `buf` starts with a whole grpc-web frame,
+/// returning its flags and payload
+async fn next_frame(
+    ws: &mut WebSocketStream<TcpStream>,
+    buf: &mut BytesMut,
+) -> Result<(u8, Bytes), BoxError> {
+    loop {
+        if buf.len() >= 5 {
+            let len = u32::from_be_bytes(buf[1..5].try_into().unwrap()) as usize;
+            if buf.len() >= 5 + len {
+                let frame = buf.split_to(5 + len);
+                return Ok((frame[0], frame.freeze().slice(5..)));
+            }
+        }
+        match ws.next().await.ok_or("WebSocket closed")?? {
+            Message::Binary(data) => buf.extend_from_slice(&data),
+            message => return Err(format!("Unexpected message {:?}", message).into()),
+        }
+    }
+}
+
+fn request_message(name: &str) -> Message {
+    let mut message = BytesMut::new();
+    message.put_u8(0);
+    message.put_slice(&message_to_frame(&HelloRequest {
+        name: name.to_string(),
+    }));
+    Message::Binary(message.freeze())
+}
+
+#[tokio::test]
+async fn test_grpc_websocket_bidi_streaming() -> Result<(), BoxError> {
+    run_intergration(async move |proxy_address| {
+        let url = format!("ws://{}/helloworld.Greeter/SayHelloBiStream", proxy_address);
+        let mut req = url.into_client_request()?;
+        req.headers_mut().insert(
+            "sec-websocket-protocol",
+            HeaderValue::from_static("grpc-websockets"),
+        );
+        let stream = TcpStream::connect(&proxy_address).await?;
+        let (mut ws, res) = client_async(req, stream).await?;
+        assert_eq!(res.headers()["sec-websocket-protocol"], "grpc-websockets");
+
+        // the headers of the call come first
+        let headers = "content-type: application/grpc-web+proto\r\nx-grpc-web: 1\r\n";
+        ws.send(Message::Binary(Bytes::from(headers))).await?;
+        let mut buf = BytesMut::new();
+        let (flags, headers) = next_frame(&mut ws, &mut buf).await?;
+        assert_eq!(flags, 0x80);
+        assert!(String::from_utf8_lossy(&headers).contains("content-type:application/grpc-web"));
+
+        // each reply is received before the next request is sent
+        ws.send(request_message("client request 1")).await?;
+        let (flags, reply) = next_frame(&mut ws, &mut buf).await?;
+        assert_eq!(flags, 0);
+        assert_eq!(HelloReply::decode(reply)?.message, "first ok");
+
+        ws.send(request_message("client request 2")).await?;
+        let (flags, reply) = next_frame(&mut ws, &mut buf).await?;
+        assert_eq!(flags, 0);
+        assert_eq!(HelloReply::decode(reply)?.message, "second ok");
+
+        // half-closing the call ends the response with its trailers
+        ws.send(Message::Binary(Bytes::from_static(&[1]))).await?;
+        let (flags, trailers) = next_frame(&mut ws, &mut buf).await?;
+        assert_eq!(flags, 0x80);
+        assert!(String::from_utf8_lossy(&trailers).contains("grpc-status:0"));
+        assert!(matches!(ws.next().await, Some(Ok(Message::Close(_)))));
+        Ok(())
+    })
+    .await
+}